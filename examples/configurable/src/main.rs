@@ -2,9 +2,9 @@ use std::time::Instant;
 
 use crossbeam::channel::{unbounded, Receiver, Sender};
 use eframe::{run_native, App, CreationContext};
-use egui::{CollapsingHeader, Context, ScrollArea, Slider, Ui, Vec2};
+use egui::{CollapsingHeader, Context, Id, ScrollArea, Slider, Ui, Vec2};
 use egui_graphs::events::Event;
-use egui_graphs::{to_graph, Edge, Graph, GraphView, Node};
+use egui_graphs::{to_graph, Edge, EdgeRouting, FitToScreen, Graph, GraphView, Node};
 use fdg_sim::glam::Vec3;
 use fdg_sim::{ForceGraph, ForceGraphHelper, Simulation, SimulationParameters};
 use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableGraph};
@@ -17,6 +17,7 @@ mod settings;
 
 const SIMULATION_DT: f32 = 0.035;
 const EVENTS_LIMIT: usize = 100;
+const GRAPH_ID: &str = "configurable_graph";
 
 pub struct ConfigurableApp {
     g: Graph<(), (), Directed>,
@@ -124,18 +125,26 @@ impl ConfigurableApp {
     ///
     /// Changes location of nodes in `g` according to the locations in `sim`. If node from `g` is dragged its location is prioritized
     /// over the location of the corresponding node from `sim` and this location is set to the node from the `sim`.
+    /// Pinned nodes from `g` always keep their location.
     ///
     /// If node or edge is selected it is added to the corresponding selected field in `self`.
     fn sync_graph_with_simulation(&mut self) {
         self.selected_nodes = vec![];
 
-        let g_indices = self.g.g.node_indices().collect::<Vec<_>>();
+        let g_indices = self.g.stable_graph().node_indices().collect::<Vec<_>>();
         g_indices.iter().for_each(|g_n_idx| {
-            let g_n = self.g.g.node_weight_mut(*g_n_idx).unwrap();
+            let g_n = self.g.node_mut(*g_n_idx).unwrap();
             let sim_n = self.sim.get_graph_mut().node_weight_mut(*g_n_idx).unwrap();
 
-            let loc = sim_n.location;
-            g_n.set_location(Vec2::new(loc.x, loc.y));
+            // pinned nodes are not moved by the simulation
+            if g_n.pinned() {
+                let loc = g_n.location();
+                sim_n.location = Vec3::new(loc.x, loc.y, 0.);
+                sim_n.velocity = Vec3::ZERO;
+            } else {
+                let loc = sim_n.location;
+                g_n.set_location(Vec2::new(loc.x, loc.y));
+            }
 
             if g_n.selected() {
                 self.selected_nodes.push(g_n.clone());
@@ -168,7 +177,7 @@ impl ConfigurableApp {
         self.settings_graph = settings_graph;
         self.last_events = Default::default();
 
-        GraphView::<(), (), Directed>::reset_metadata(ui);
+        GraphView::<(), (), Directed>::reset_metadata_with_id(ui, Id::new(GRAPH_ID));
     }
 
     fn handle_events(&mut self) {
@@ -210,23 +219,23 @@ impl ConfigurableApp {
     }
 
     fn random_node_idx(&self) -> Option<NodeIndex> {
-        let nodes_cnt = self.g.g.node_count();
+        let nodes_cnt = self.g.node_count();
         if nodes_cnt == 0 {
             return None;
         }
 
         let random_n_idx = rand::thread_rng().gen_range(0..nodes_cnt);
-        self.g.g.node_indices().nth(random_n_idx)
+        self.g.stable_graph().node_indices().nth(random_n_idx)
     }
 
     fn random_edge_idx(&self) -> Option<EdgeIndex> {
-        let edges_cnt = self.g.g.edge_count();
+        let edges_cnt = self.g.edge_count();
         if edges_cnt == 0 {
             return None;
         }
 
         let random_e_idx = rand::thread_rng().gen_range(0..edges_cnt);
-        self.g.g.edge_indices().nth(random_e_idx)
+        self.g.stable_graph().edge_indices().nth(random_e_idx)
     }
 
     fn remove_random_node(&mut self) {
@@ -240,7 +249,7 @@ impl ConfigurableApp {
             return;
         }
 
-        let random_n = self.g.g.node_weight(random_n_idx.unwrap()).unwrap();
+        let random_n = self.g.node(random_n_idx.unwrap()).unwrap();
 
         // location of new node is in surrounging of random existing node
         let mut rng = rand::thread_rng();
@@ -249,27 +258,23 @@ impl ConfigurableApp {
             random_n.location().y + 10. + rng.gen_range(0. ..50.),
         );
 
-        let idx = self.g.g.add_node(Node::new(location, ()));
-        let n = self.g.g.node_weight_mut(idx).unwrap();
-        *n = n.with_label(format!("{:?}", idx));
+        let idx = self.g.add_node(Node::new(location, ()));
+        self.g
+            .node_mut(idx)
+            .unwrap()
+            .set_label(format!("{:?}", idx));
         let mut sim_node = fdg_sim::Node::new(idx.index().to_string().as_str(), ());
         sim_node.location = Vec3::new(location.x, location.y, 0.);
         self.sim.get_graph_mut().add_node(sim_node);
     }
 
     fn remove_node(&mut self, idx: NodeIndex) {
-        // before removing nodes we need to remove all edges connected to it
-        let neighbors = self.g.g.neighbors_undirected(idx).collect::<Vec<_>>();
-        neighbors.iter().for_each(|n| {
-            self.remove_edges(idx, *n);
-            self.remove_edges(*n, idx);
-        });
-
-        self.g.g.remove_node(idx).unwrap();
+        // both graphs remove edges connected to the node together with it
+        self.g.remove_node(idx).unwrap();
         self.sim.get_graph_mut().remove_node(idx).unwrap();
 
         // update edges count
-        self.settings_graph.count_edge = self.g.g.edge_count();
+        self.settings_graph.count_edge = self.g.edge_count();
     }
 
     fn add_random_edge(&mut self) {
@@ -280,7 +285,7 @@ impl ConfigurableApp {
     }
 
     fn add_edge(&mut self, start: NodeIndex, end: NodeIndex) {
-        self.g.g.add_edge(start, end, Edge::new(()));
+        self.g.add_edge(start, end, Edge::new(()));
         self.sim.get_graph_mut().add_edge(start, end, 1.);
     }
 
@@ -289,7 +294,7 @@ impl ConfigurableApp {
         if random_e_idx.is_none() {
             return;
         }
-        let endpoints = self.g.g.edge_endpoints(random_e_idx.unwrap()).unwrap();
+        let endpoints = self.g.edge_endpoints(random_e_idx.unwrap()).unwrap();
 
         self.remove_edge(endpoints.0, endpoints.1);
     }
@@ -298,45 +303,17 @@ impl ConfigurableApp {
     /// there can be multiple edges between two nodes in 2 graphs
     /// and we can't be sure that they are indexed the same way.
     fn remove_edge(&mut self, start: NodeIndex, end: NodeIndex) {
-        let g_idx = self.g.g.find_edge(start, end);
+        let g_idx = self.g.edges_connecting(start, end).next().map(|e| e.id());
         if g_idx.is_none() {
             return;
         }
 
-        self.g.g.remove_edge(g_idx.unwrap()).unwrap();
+        self.g.remove_edge(g_idx.unwrap()).unwrap();
 
         let sim_idx = self.sim.get_graph_mut().find_edge(start, end).unwrap();
         self.sim.get_graph_mut().remove_edge(sim_idx).unwrap();
     }
 
-    /// Removes all edges between two nodes
-    fn remove_edges(&mut self, start: NodeIndex, end: NodeIndex) {
-        let g_idxs = self
-            .g
-            .g
-            .edges_connecting(start, end)
-            .map(|e| e.id())
-            .collect::<Vec<_>>();
-        if g_idxs.is_empty() {
-            return;
-        }
-
-        g_idxs.iter().for_each(|e| {
-            self.g.g.remove_edge(*e).unwrap();
-        });
-
-        let sim_idxs = self
-            .sim
-            .get_graph()
-            .edges_connecting(start, end)
-            .map(|e| e.id())
-            .collect::<Vec<_>>();
-
-        sim_idxs.iter().for_each(|e| {
-            self.sim.get_graph_mut().remove_edge(*e).unwrap();
-        });
-    }
-
     fn draw_section_app(&mut self, ui: &mut Ui) {
         CollapsingHeader::new("App Config")
             .default_open(true)
@@ -376,22 +353,43 @@ impl ConfigurableApp {
         .default_open(true)
         .show(ui, |ui| {
             CollapsingHeader::new("Navigation").default_open(true).show(ui, |ui|{
-                if ui
-                    .checkbox(&mut self.settings_navigation.fit_to_screen_enabled, "fit_to_screen")
-                    .changed()
-                    && self.settings_navigation.fit_to_screen_enabled
-                {
-                    self.settings_navigation.zoom_and_pan_enabled = false
-                };
-                ui.label("Enable fit to screen to fit the graph to the screen on every frame.");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.settings_navigation.fit_to_screen, FitToScreen::Once, "once");
+                    ui.radio_value(&mut self.settings_navigation.fit_to_screen, FitToScreen::OnBoundsChange, "on_bounds_change");
+                    if ui
+                        .radio_value(&mut self.settings_navigation.fit_to_screen, FitToScreen::Always, "always")
+                        .changed()
+                    {
+                        self.settings_navigation.zoom_and_pan_enabled = false
+                    };
+                });
+                ui.label("Defines when the graph is fitted to the screen.");
+
+                ui.add_space(5.);
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings_navigation.fit_to_screen_animated, "animated");
+                    if ui.button("fit").clicked() {
+                        GraphView::<(), (), Directed>::request_fit_to_screen_with_id(
+                            ui,
+                            Id::new(GRAPH_ID),
+                        );
+                    }
+                });
+                ui.label("Animate fitting or fit the graph once on demand.");
 
                 ui.add_space(5.);
 
-                ui.add_enabled_ui(!self.settings_navigation.fit_to_screen_enabled, |ui| {
+                ui.checkbox(&mut self.settings_navigation.fit_to_screen_on_double_click, "fit_to_screen_on_double_click");
+                ui.label("Double click on the empty canvas to fit the graph.");
+
+                ui.add_space(5.);
+
+                ui.add_enabled_ui(self.settings_navigation.fit_to_screen != FitToScreen::Always, |ui| {
                     ui.vertical(|ui| {
                         ui.checkbox(&mut self.settings_navigation.zoom_and_pan_enabled, "zoom_and_pan");
                         ui.label("Zoom with ctrl + mouse wheel, pan with mouse drag.");
-                    }).response.on_disabled_hover_text("disable fit_to_screen to enable zoom_and_pan");
+                    }).response.on_disabled_hover_text("fit_to_screen other than always enables zoom_and_pan");
                 });
             });
 
@@ -404,6 +402,21 @@ impl ConfigurableApp {
 
                 ui.checkbox(&mut self.settings_style.labels_always, "labels_always");
                 ui.label("Wheter to show labels always or when interacted only.");
+
+                ui.add_space(5.);
+
+                ui.checkbox(&mut self.settings_style.grid_enabled, "grid_enabled");
+                ui.label("Draws background grid which scales with zoom.");
+
+                ui.add_space(5.);
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.settings_style.edge_routing, EdgeRouting::Straight, "straight");
+                    ui.radio_value(&mut self.settings_style.edge_routing, EdgeRouting::Orthogonal, "orthogonal");
+                    ui.radio_value(&mut self.settings_style.edge_routing, EdgeRouting::Spline, "spline");
+                    ui.radio_value(&mut self.settings_style.edge_routing, EdgeRouting::Bundled, "bundled");
+                });
+                ui.label("Defines how edges between different nodes are routed.");
             });
 
             CollapsingHeader::new("Interaction").show(ui, |ui| {
@@ -437,6 +450,26 @@ impl ConfigurableApp {
                     self.settings_interaction.selection_enabled = true;
                 }
                 ui.label("Enable multiselect to select multiple nodes.");
+
+                ui.add_space(5.);
+
+                ui.checkbox(&mut self.settings_interaction.snap_to_grid_enabled, "snap_to_grid_enabled");
+                ui.label("Snaps dragged node to the grid.");
+
+                ui.add_space(5.);
+
+                ui.checkbox(&mut self.settings_interaction.alignment_guides_enabled, "alignment_guides_enabled");
+                ui.label("Shows guides and aligns dragged node with centers of other nodes.");
+
+                ui.add_space(5.);
+
+                ui.checkbox(&mut self.settings_interaction.pinning_enabled, "pinning_enabled");
+                ui.label("Pins node after drag, double click on node toggles pin. Pinned nodes are not moved by the simulation.");
+
+                ui.add_space(5.);
+
+                ui.checkbox(&mut self.settings_interaction.pinned_dragging_locked, "pinned_dragging_locked");
+                ui.label("Pinned nodes can not be dragged.");
             });
 
             CollapsingHeader::new("Selected").default_open(true).show(ui, |ui| {
@@ -527,16 +560,26 @@ impl App for ConfigurableApp {
                 .with_selection_enabled(self.settings_interaction.selection_enabled)
                 .with_selection_multi_enabled(self.settings_interaction.selection_multi_enabled)
                 .with_dragging_enabled(self.settings_interaction.dragging_enabled)
-                .with_clicking_enabled(self.settings_interaction.clicking_enabled);
+                .with_clicking_enabled(self.settings_interaction.clicking_enabled)
+                .with_snap_to_grid_enabled(self.settings_interaction.snap_to_grid_enabled)
+                .with_alignment_guides_enabled(self.settings_interaction.alignment_guides_enabled)
+                .with_pinning_enabled(self.settings_interaction.pinning_enabled)
+                .with_pinned_dragging_locked(self.settings_interaction.pinned_dragging_locked);
             let settings_navigation = &egui_graphs::SettingsNavigation::new()
                 .with_zoom_and_pan_enabled(self.settings_navigation.zoom_and_pan_enabled)
-                .with_fit_to_screen_enabled(self.settings_navigation.fit_to_screen_enabled)
+                .with_fit_to_screen(self.settings_navigation.fit_to_screen)
+                .with_fit_to_screen_animated(self.settings_navigation.fit_to_screen_animated)
+                .with_fit_to_screen_on_double_click(self.settings_navigation.fit_to_screen_on_double_click)
+                .with_screen_padding(self.settings_navigation.screen_padding)
                 .with_zoom_speed(self.settings_navigation.zoom_speed);
             let settings_style = &egui_graphs::SettingsStyle::new()
                 .with_labels_always(self.settings_style.labels_always)
-                .with_edge_radius_weight(self.settings_style.edge_radius_weight);
+                .with_edge_radius_weight(self.settings_style.edge_radius_weight)
+                .with_grid_enabled(self.settings_style.grid_enabled)
+                .with_edge_routing(self.settings_style.edge_routing);
             ui.add(
                 &mut GraphView::new(&mut self.g)
+                    .with_id(Id::new(GRAPH_ID))
                     .with_interactions(settings_interaction)
                     .with_navigations(settings_navigation)
                     .with_styles(settings_style)
@@ -561,13 +604,13 @@ fn generate(settings: &SettingsGraph) -> (Graph<(), (), Directed>, Simulation<()
 
 fn construct_simulation(g: &Graph<(), (), Directed>) -> Simulation<(), f32> {
     // create force graph
-    let mut force_graph = ForceGraph::with_capacity(g.g.node_count(), g.g.edge_count());
-    g.g.node_indices().for_each(|idx| {
+    let mut force_graph = ForceGraph::with_capacity(g.node_count(), g.edge_count());
+    g.stable_graph().node_indices().for_each(|idx| {
         let idx = idx.index();
         force_graph.add_force_node(format!("{}", idx).as_str(), ());
     });
-    g.g.edge_indices().for_each(|idx| {
-        let (source, target) = g.g.edge_endpoints(idx).unwrap();
+    g.stable_graph().edge_indices().for_each(|idx| {
+        let (source, target) = g.edge_endpoints(idx).unwrap();
         force_graph.add_edge(source, target, 1.);
     });
 
@@ -1,3 +1,5 @@
+use egui_graphs::{EdgeRouting, FitToScreen};
+
 pub struct SettingsGraph {
     pub count_node: usize,
     pub count_edge: usize,
@@ -18,10 +20,16 @@ pub struct SettingsInteraction {
     pub clicking_enabled: bool,
     pub selection_enabled: bool,
     pub selection_multi_enabled: bool,
+    pub snap_to_grid_enabled: bool,
+    pub alignment_guides_enabled: bool,
+    pub pinning_enabled: bool,
+    pub pinned_dragging_locked: bool,
 }
 
 pub struct SettingsNavigation {
-    pub fit_to_screen_enabled: bool,
+    pub fit_to_screen: FitToScreen,
+    pub fit_to_screen_animated: bool,
+    pub fit_to_screen_on_double_click: bool,
     pub zoom_and_pan_enabled: bool,
     pub screen_padding: f32,
     pub zoom_speed: f32,
@@ -32,7 +40,9 @@ impl Default for SettingsNavigation {
         Self {
             screen_padding: 0.3,
             zoom_speed: 0.1,
-            fit_to_screen_enabled: true,
+            fit_to_screen: FitToScreen::Always,
+            fit_to_screen_animated: false,
+            fit_to_screen_on_double_click: true,
             zoom_and_pan_enabled: false,
         }
     }
@@ -41,6 +51,8 @@ impl Default for SettingsNavigation {
 pub struct SettingsStyle {
     pub edge_radius_weight: f32,
     pub labels_always: bool,
+    pub grid_enabled: bool,
+    pub edge_routing: EdgeRouting,
 }
 
 impl Default for SettingsStyle {
@@ -48,6 +60,8 @@ impl Default for SettingsStyle {
         Self {
             edge_radius_weight: 1.,
             labels_always: false,
+            grid_enabled: false,
+            edge_routing: EdgeRouting::Straight,
         }
     }
 }
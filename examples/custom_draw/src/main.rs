@@ -2,7 +2,7 @@ use eframe::{run_native, App, CreationContext};
 use egui::{
     epaint::TextShape, Context, FontFamily, FontId, Rect, Rounding, Shape, Stroke, Vec2,
 };
-use egui_graphs::{default_edges_draw, Graph, GraphView, SettingsInteraction};
+use egui_graphs::{default_edges_draw, Graph, GraphView, Layer, SettingsInteraction};
 use petgraph::{stable_graph::StableGraph, Directed};
 
 pub struct BasicApp {
@@ -26,7 +26,7 @@ impl App for BasicApp {
                             .with_dragging_enabled(true)
                             .with_selection_enabled(true),
                     )
-                    .with_custom_node_draw(|ctx, n, state, l| {
+                    .with_custom_node_draw(|ctx, (_, n), state, l| {
                         // lets draw a rect with label in the center for every node
 
                         // find node center location on the screen coordinates
@@ -50,7 +50,7 @@ impl App for BasicApp {
                         // then create label
                         let color = ctx.style().visuals.text_color();
                         let galley = ctx.fonts(|f| {
-                            f.layout(n.label(), FontId::new(rad, FontFamily::Monospace), color, size.x)
+                            f.layout(n.label().to_string(), FontId::new(rad, FontFamily::Monospace), color, size.x)
                         });
 
                         // we need to offset label by half its size to place it in the center of the rect
@@ -97,7 +97,7 @@ impl App for BasicApp {
 
                         // create the shape and add it to the layers
                         let shape_label = TextShape::new((center_loc + offset).to_pos2(), galley);
-                        l.add(shape_label);
+                        l.add_to(Layer::EdgeLabels, shape_label);
                     }),
             );
         });
@@ -0,0 +1,99 @@
+use egui::Vec2;
+
+/// Line along which the dragged node is aligned with centers of other nodes. Stores the coordinate
+/// of the line in graph coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlignmentGuide {
+    /// Vertical line with the given `x` coordinate.
+    Vertical(f32),
+    /// Horizontal line with the given `y` coordinate.
+    Horizontal(f32),
+}
+
+/// Snaps location to the nearest grid intersection. Non positive spacing disables snapping.
+pub fn snap_to_grid(loc: Vec2, spacing: f32) -> Vec2 {
+    if spacing <= 0. {
+        return loc;
+    }
+
+    Vec2::new(
+        (loc.x / spacing).round() * spacing,
+        (loc.y / spacing).round() * spacing,
+    )
+}
+
+/// Aligns location with the closest center from `others` on every axis if it is not further than `threshold`.
+///
+/// Returns aligned location and guides for the axes along which alignment happened.
+// `Option::is_none_or` suggested by clippy needs Rust 1.82
+#[allow(clippy::unnecessary_map_or)]
+pub fn align_to_nodes(
+    loc: Vec2,
+    others: impl Iterator<Item = Vec2>,
+    threshold: f32,
+) -> (Vec2, Vec<AlignmentGuide>) {
+    let mut closest_x: Option<f32> = None;
+    let mut closest_y: Option<f32> = None;
+
+    others.for_each(|other| {
+        let dx = (other.x - loc.x).abs();
+        if dx <= threshold && closest_x.map_or(true, |x| dx < (x - loc.x).abs()) {
+            closest_x = Some(other.x);
+        }
+
+        let dy = (other.y - loc.y).abs();
+        if dy <= threshold && closest_y.map_or(true, |y| dy < (y - loc.y).abs()) {
+            closest_y = Some(other.y);
+        }
+    });
+
+    let mut aligned = loc;
+    let mut guides = vec![];
+    if let Some(x) = closest_x {
+        aligned.x = x;
+        guides.push(AlignmentGuide::Vertical(x));
+    }
+    if let Some(y) = closest_y {
+        aligned.y = y;
+        guides.push(AlignmentGuide::Horizontal(y));
+    }
+
+    (aligned, guides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_to_grid() {
+        let snapped = snap_to_grid(Vec2::new(29., -11.), 20.);
+        assert_eq!(snapped, Vec2::new(20., -20.));
+
+        let not_snapped = snap_to_grid(Vec2::new(29., -11.), 0.);
+        assert_eq!(not_snapped, Vec2::new(29., -11.));
+    }
+
+    #[test]
+    fn test_align_to_nodes() {
+        let others = vec![Vec2::new(13., 100.), Vec2::new(11., 200.), Vec2::new(50., 52.)];
+
+        let (aligned, guides) = align_to_nodes(Vec2::new(10., 50.), others.into_iter(), 3.);
+
+        assert_eq!(aligned, Vec2::new(11., 52.));
+        assert_eq!(
+            guides,
+            vec![AlignmentGuide::Vertical(11.), AlignmentGuide::Horizontal(52.)]
+        );
+    }
+
+    #[test]
+    fn test_align_to_nodes_out_of_threshold() {
+        let others = vec![Vec2::new(20., 60.)];
+
+        let (aligned, guides) = align_to_nodes(Vec2::new(10., 50.), others.into_iter(), 3.);
+
+        assert_eq!(aligned, Vec2::new(10., 50.));
+        assert!(guides.is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+//! Conversions from the petgraph graph structures other than [`StableGraph`] and the way back to them.
+//!
+//! Nodes of the converted [`Graph`] keep the indices of the original structure: node indices of
+//! [`petgraph::Graph`] and [`MatrixGraph`] are the same and the node with index `i` of the
+//! [`GraphMap`] is its `i`-th node. [`SourceGraph`] uses it to find the original nodes, so view state
+//! like node positions and selection can be written back with [`Graph::write_back`].
+
+use egui::Vec2;
+use petgraph::{
+    graphmap::{GraphMap, NodeTrait},
+    matrix_graph::{MatrixGraph, Nullable},
+    stable_graph::{IndexType, NodeIndex, StableGraph},
+    visit::{IntoEdgeReferences, IntoNodeReferences, NodeIndexable},
+    EdgeType,
+};
+
+use crate::{to_graph, Graph, Node};
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> From<&petgraph::Graph<N, E, Ty, Ix>>
+    for Graph<N, E, Ty, Ix>
+{
+    fn from(value: &petgraph::Graph<N, E, Ty, Ix>) -> Self {
+        to_graph(&StableGraph::from(value.clone()))
+    }
+}
+
+impl<N, E, Ty, Ix> From<&GraphMap<N, E, Ty>> for Graph<N, E, Ty, Ix>
+where
+    N: NodeTrait,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    /// Node data are the nodes of the [`GraphMap`].
+    fn from(value: &GraphMap<N, E, Ty>) -> Self {
+        let mut g =
+            StableGraph::<N, E, Ty, Ix>::with_capacity(value.node_count(), value.edge_count());
+        value.nodes().for_each(|n| {
+            g.add_node(n);
+        });
+        value.all_edges().for_each(|(start, end, e)| {
+            let index = |n| NodeIndex::new(value.to_index(n));
+            g.add_edge(index(start), index(end), e.clone());
+        });
+
+        to_graph(&g)
+    }
+}
+
+impl<N, E, Ty, Null, Ix> From<&MatrixGraph<N, E, Ty, Null, Ix>> for Graph<N, E, Ty, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Null: Nullable<Wrapped = E>,
+    Ix: IndexType,
+{
+    fn from(value: &MatrixGraph<N, E, Ty, Null, Ix>) -> Self {
+        let mut g = StableGraph::<N, E, Ty, Ix>::default();
+
+        // removed nodes of the matrix are added and removed again to keep indices of the next nodes
+        let mut removed = vec![];
+        value.node_references().for_each(|(idx, n)| {
+            while g.node_bound() < idx.index() {
+                removed.push(g.add_node(n.clone()));
+            }
+            g.add_node(n.clone());
+        });
+        removed.into_iter().for_each(|idx| {
+            g.remove_node(idx);
+        });
+
+        value.edge_references().for_each(|(start, end, e)| {
+            g.add_edge(start, end, e.clone());
+        });
+
+        to_graph(&g)
+    }
+}
+
+/// Original graph structure the [`Graph`] was converted from. Finds the original node for the node
+/// of the converted graph, see the [module documentation](self).
+pub trait SourceGraph<Ix: IndexType> {
+    /// Identifier of the node in the original structure.
+    type NodeId;
+
+    /// Returns the original node identifier for the node index of the converted graph or `None`
+    /// if the original structure does not have such node.
+    fn source_node(&self, idx: NodeIndex<Ix>) -> Option<Self::NodeId>;
+}
+
+impl<N, E, Ty: EdgeType, Ix: IndexType> SourceGraph<Ix> for StableGraph<N, E, Ty, Ix> {
+    type NodeId = NodeIndex<Ix>;
+
+    fn source_node(&self, idx: NodeIndex<Ix>) -> Option<Self::NodeId> {
+        self.contains_node(idx).then_some(idx)
+    }
+}
+
+impl<N, E, Ty: EdgeType, Ix: IndexType> SourceGraph<Ix> for petgraph::Graph<N, E, Ty, Ix> {
+    type NodeId = NodeIndex<Ix>;
+
+    fn source_node(&self, idx: NodeIndex<Ix>) -> Option<Self::NodeId> {
+        self.node_weight(idx).map(|_| idx)
+    }
+}
+
+impl<N: NodeTrait, E, Ty: EdgeType, Ix: IndexType> SourceGraph<Ix> for GraphMap<N, E, Ty> {
+    /// Nodes of the [`GraphMap`] are identified by themselves.
+    type NodeId = N;
+
+    fn source_node(&self, idx: NodeIndex<Ix>) -> Option<Self::NodeId> {
+        (idx.index() < self.node_count()).then(|| self.from_index(idx.index()))
+    }
+}
+
+impl<N, E, Ty, Null, Ix> SourceGraph<Ix> for MatrixGraph<N, E, Ty, Null, Ix>
+where
+    Ty: EdgeType,
+    Null: Nullable<Wrapped = E>,
+    Ix: IndexType,
+{
+    type NodeId = NodeIndex<Ix>;
+
+    /// Nodes removed from the matrix after the conversion can not be detected, so the index
+    /// is only checked against the matrix size.
+    fn source_node(&self, idx: NodeIndex<Ix>) -> Option<Self::NodeId> {
+        (idx.index() < self.node_bound()).then_some(idx)
+    }
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Graph<N, E, Ty, Ix> {
+    /// Calls `f` for every node which exists in the original structure with the structure, the original
+    /// node identifier and the node of this graph, e.g. to store node positions in the node data.
+    ///
+    /// # Example
+    /// ```
+    /// use egui::Vec2;
+    /// use egui_graphs::Graph;
+    ///
+    /// let mut user_graph = petgraph::Graph::<Vec2, ()>::new();
+    /// let a = user_graph.add_node(Vec2::ZERO);
+    ///
+    /// let mut g = Graph::from(&user_graph);
+    /// g.node_mut(a).unwrap().set_location(Vec2::new(1., 2.));
+    ///
+    /// g.write_back(&mut user_graph, |user_graph, idx, n| user_graph[idx] = n.location());
+    /// assert_eq!(user_graph[a], Vec2::new(1., 2.));
+    /// ```
+    pub fn write_back<S: SourceGraph<Ix>>(
+        &self,
+        source: &mut S,
+        mut f: impl FnMut(&mut S, S::NodeId, &Node<N, Ix>),
+    ) {
+        self.nodes_iter().for_each(|(idx, n)| {
+            if let Some(id) = source.source_node(idx) {
+                f(source, id, n);
+            }
+        });
+    }
+
+    /// Returns locations of the nodes by their identifiers in the original structure.
+    pub fn source_locations<S: SourceGraph<Ix>>(&self, source: &S) -> Vec<(S::NodeId, Vec2)> {
+        self.nodes_iter()
+            .filter_map(|(idx, n)| source.source_node(idx).map(|id| (id, n.location())))
+            .collect()
+    }
+
+    /// Returns identifiers of the selected nodes in the original structure.
+    pub fn source_selected<S: SourceGraph<Ix>>(&self, source: &S) -> Vec<S::NodeId> {
+        self.nodes_iter()
+            .filter(|(_, n)| n.selected())
+            .filter_map(|(idx, _)| source.source_node(idx))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{
+        graphmap::{DiGraphMap, UnGraphMap},
+        matrix_graph::{DiMatrix, UnMatrix},
+        Directed, Undirected,
+    };
+
+    use crate::GraphExport;
+
+    use super::*;
+
+    fn endpoints<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+        g: &Graph<N, E, Ty, Ix>,
+    ) -> Vec<(usize, usize)> {
+        let mut res = g
+            .edges_iter()
+            .map(|(idx, _)| {
+                let (start, end) = g.edge_endpoints(idx).unwrap();
+                (start.index(), end.index())
+            })
+            .collect::<Vec<_>>();
+        res.sort();
+        res
+    }
+
+    #[test]
+    fn test_from_graph() {
+        let mut user_graph = petgraph::Graph::<&str, u8, Directed, u16>::default();
+        let a = user_graph.add_node("a");
+        let b = user_graph.add_node("b");
+        user_graph.add_edge(a, b, 1);
+        user_graph.add_edge(b, b, 2);
+
+        let mut g = Graph::from(&user_graph);
+
+        assert_eq!(g.node(b).unwrap().data, "b");
+        assert_eq!(g.node(b).unwrap().id(), b);
+        assert_eq!(endpoints(&g), vec![(0, 1), (1, 1)]);
+
+        // the whole drawing pipeline works with the custom index type
+        let svg = GraphExport::new(&mut g).to_svg();
+        assert!(svg.contains("<circle"));
+    }
+
+    #[test]
+    fn test_from_graph_map() {
+        let mut user_graph = DiGraphMap::<char, ()>::new();
+        user_graph.add_edge('x', 'y', ());
+        user_graph.add_edge('z', 'x', ());
+
+        let mut g = Graph::<_, _, _>::from(&user_graph);
+        assert_eq!(g.node(NodeIndex::new(2)).unwrap().data, 'z');
+        assert_eq!(endpoints(&g), vec![(0, 1), (2, 0)]);
+
+        let z = NodeIndex::new(2);
+        g.node_mut(z).unwrap().set_location(Vec2::new(1., 2.));
+        g.node_mut(z).unwrap().set_selected(true);
+        assert_eq!(g.source_selected(&user_graph), vec!['z']);
+        assert!(g
+            .source_locations(&user_graph)
+            .contains(&('z', Vec2::new(1., 2.))));
+        assert_eq!(
+            SourceGraph::<u32>::source_node(&user_graph, NodeIndex::new(3)),
+            None
+        );
+
+        let mut undirected = UnGraphMap::<u8, ()>::new();
+        undirected.add_edge(1, 2, ());
+        let g = Graph::<u8, (), Undirected, u8>::from(&undirected);
+        assert_eq!(g.g.edge_count(), 1);
+    }
+
+    #[test]
+    fn test_from_matrix_graph() {
+        let mut user_graph = DiMatrix::<&str, f32>::new();
+        let a = user_graph.add_node("a");
+        let b = user_graph.add_node("b");
+        let c = user_graph.add_node("c");
+        user_graph.add_edge(a, c, 0.5);
+        user_graph.add_edge(c, c, 1.);
+        user_graph.remove_node(b);
+
+        let g = Graph::from(&user_graph);
+        assert_eq!(g.g.node_count(), 2);
+        assert_eq!(g.node(c).unwrap().data, "c");
+        assert!(g.node(b).is_none());
+        assert_eq!(endpoints(&g), vec![(0, 2), (2, 2)]);
+
+        let mut undirected = UnMatrix::<(), ()>::new_undirected();
+        let a = undirected.add_node(());
+        let b = undirected.add_node(());
+        undirected.add_edge(a, b, ());
+        assert_eq!(Graph::from(&undirected).g.edge_count(), 1);
+    }
+
+    #[test]
+    fn test_write_back() {
+        let mut user_graph = StableGraph::<(Vec2, bool), ()>::new();
+        let a = user_graph.add_node(Default::default());
+        let b = user_graph.add_node(Default::default());
+
+        let mut g = to_graph(&user_graph);
+        g.node_mut(a).unwrap().set_location(Vec2::new(3., 4.));
+        g.node_mut(b).unwrap().set_selected(true);
+        user_graph.remove_node(a);
+
+        g.write_back(&mut user_graph, |user_graph, idx, n| {
+            user_graph[idx] = (n.location(), n.selected())
+        });
+
+        assert_eq!(user_graph[b], (g.node(b).unwrap().location(), true));
+        assert_eq!(g.source_selected(&user_graph), vec![b]);
+        assert_eq!(g.source_locations(&user_graph).len(), 1);
+    }
+}
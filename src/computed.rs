@@ -1,25 +1,30 @@
 use egui::{Rect, Vec2};
-use petgraph::{stable_graph::NodeIndex, EdgeType};
+use petgraph::{
+    stable_graph::{DefaultIx, IndexType, NodeIndex},
+    EdgeType,
+};
 
-use crate::{Graph, Node, SettingsStyle};
+use crate::{alignment::AlignmentGuide, Graph, Node, SettingsStyle};
 
 /// The struct stores selections, dragged node and computed elements states.
 #[derive(Debug, Clone)]
-pub struct ComputedState {
-    pub dragged: Option<NodeIndex>,
-    pub selected: Vec<NodeIndex>,
+pub struct ComputedState<Ix: IndexType = DefaultIx> {
+    pub dragged: Option<NodeIndex<Ix>>,
+    pub selected: Vec<NodeIndex<Ix>>,
+    pub alignment_guides: Vec<AlignmentGuide>,
 
     min: Vec2,
     max: Vec2,
     max_rad: f32,
 }
 
-impl Default for ComputedState {
+impl<Ix: IndexType> Default for ComputedState<Ix> {
     fn default() -> Self {
         Self {
             dragged: None,
 
             selected: Vec::new(),
+            alignment_guides: Vec::new(),
 
             min: Vec2::new(f32::MAX, f32::MAX),
             max: Vec2::new(f32::MIN, f32::MIN),
@@ -28,11 +33,34 @@ impl Default for ComputedState {
     }
 }
 
-impl ComputedState {
+impl<Ix: IndexType> ComputedState<Ix> {
+    /// Computes the state of all nodes of the graph and stores the computed node properties
+    /// in the nodes.
+    pub fn compute<N: Clone, E: Clone, Ty: EdgeType>(
+        g: &mut Graph<N, E, Ty, Ix>,
+        settings: &SettingsStyle,
+    ) -> Self {
+        let mut computed = ComputedState::default();
+
+        g.sync_outdated_ids();
+
+        let n_idxs = g.g.node_indices().collect::<Vec<_>>();
+        n_idxs.iter().for_each(|idx| {
+            let comp = computed.compute_for_node(g, *idx);
+
+            let n = g.node_mut(*idx).unwrap();
+            n.set_computed(comp);
+
+            computed.comp_iter_bounds(n, settings);
+        });
+
+        computed
+    }
+
     pub fn compute_for_node<N: Clone, E: Clone, Ty: EdgeType>(
         &mut self,
-        g: &Graph<N, E, Ty>,
-        idx: NodeIndex,
+        g: &Graph<N, E, Ty, Ix>,
+        idx: NodeIndex<Ix>,
     ) -> ComputedNode {
         let n = g.node(idx).unwrap();
 
@@ -48,8 +76,8 @@ impl ComputedState {
         }
     }
 
-    pub fn comp_iter_bounds<N: Clone>(&mut self, n: &Node<N>, settings: &SettingsStyle) {
-        let rad = n.radius() + n.num_connections() as f32 * settings.edge_radius_weight;
+    pub fn comp_iter_bounds<N: Clone>(&mut self, n: &Node<N, Ix>, settings: &SettingsStyle) {
+        let rad = n.half_size(settings).max_elem();
         if rad > self.max_rad {
             self.max_rad = rad;
         }
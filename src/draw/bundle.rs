@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use egui::{Pos2, Vec2};
+
+use super::stroke::{point_at, polyline_length};
+
+/// Number of times the edges subdivision is doubled. Edges are split into 2 segments in the first
+/// cycle and into 32 segments in the last one.
+const CYCLES: usize = 5;
+
+/// Number of iterations in the first cycle. Every next cycle has [`ITERATIONS_RATE`] times less.
+const ITERATIONS: usize = 90;
+const ITERATIONS_RATE: f32 = 2. / 3.;
+
+/// Distance points are moved by the unit force in the first cycle. It is halved every next cycle.
+const STEP: f32 = 0.1;
+
+/// Stiffness of the springs keeping the edge points together.
+const SPRING: f32 = 0.1;
+
+/// Edges with lower compatibility do not attract each other.
+const COMPATIBILITY_THRESHOLD: f32 = 0.6;
+
+/// Largest number of edges attracting every edge, the most compatible ones are kept.
+const MAX_COMPATIBLE: usize = 16;
+
+/// Largest distance between the midpoints of compatible edges relative to the length of the edge.
+/// Edges which lengths differ more than 3 times have scale compatibility below the threshold, so
+/// the average length of compatible edges is less than 2 lengths of the edge, and the position
+/// compatibility is below the threshold if the midpoints are further than 2/3 of the average length.
+const MAX_MIDPOINT_DISTANCE: f32 = 4. / 3.;
+
+/// Largest number of compared edge pairs and point attractions computed by the bundling. Iterations
+/// are reduced for large graphs so the bundling does not stall the frame, graphs which can not be
+/// bundled even with a single iteration per cycle are not bundled.
+const MAX_WORK: usize = 4_000_000;
+
+/// Bundles edges given by their start and end points with the force-directed edge bundling.
+///
+/// Every edge is split into points which are attracted to the corresponding points of compatible
+/// edges, i.e. the edges with similar direction, length and position. Returns polylines for all
+/// edges in the same order. Edges direction does not matter, opposite edges are bundled as well.
+///
+/// Returns `None` if bundling the edges would take more than [`MAX_WORK`].
+pub fn bundle_edges(edges: &[(Pos2, Pos2)]) -> Option<Vec<Vec<Pos2>>> {
+    // edges are oriented the same way, so their points with the same index are close to each other
+    let flipped = edges
+        .iter()
+        .map(|(start, end)| (start.x, start.y) > (end.x, end.y))
+        .collect::<Vec<_>>();
+    let oriented = edges
+        .iter()
+        .zip(flipped.iter())
+        .map(|((start, end), flip)| match flip {
+            true => (*end, *start),
+            false => (*start, *end),
+        })
+        .collect::<Vec<_>>();
+
+    let (compatible, scanned) = compatible_edges(&oriented)?;
+    let attractions = compatible.iter().map(|c| c.len()).sum();
+    let iterations = iterations(attractions, MAX_WORK - scanned)?;
+
+    let mut polylines = oriented
+        .iter()
+        .map(|(start, end)| vec![*start, *end])
+        .collect::<Vec<_>>();
+
+    let mut step = STEP;
+    iterations
+        .into_iter()
+        .enumerate()
+        .for_each(|(cycle, iterations)| {
+            polylines = polylines
+                .iter()
+                .map(|polyline| subdivide(polyline, segments(cycle)))
+                .collect();
+
+            (0..iterations).for_each(|_| {
+                let forces = polylines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, polyline)| forces(polyline, &polylines, &compatible[i]))
+                    .collect::<Vec<_>>();
+                polylines
+                    .iter_mut()
+                    .zip(forces)
+                    .for_each(|(polyline, forces)| {
+                        polyline
+                            .iter_mut()
+                            .zip(forces)
+                            .for_each(|(p, f)| *p += f * step);
+                    });
+            });
+
+            step /= 2.;
+        });
+
+    let polylines = polylines
+        .into_iter()
+        .zip(flipped)
+        .map(|(mut polyline, flip)| {
+            if flip {
+                polyline.reverse();
+            }
+            polyline
+        })
+        .collect();
+    Some(polylines)
+}
+
+/// Returns the most compatible edges for every edge and the number of compared edge pairs and
+/// visited cells. Only the edges with midpoints close enough to be compatible are compared, they are
+/// found in the grid of the edge midpoints. Returns `None` if the search exceeds [`MAX_WORK`].
+fn compatible_edges(edges: &[(Pos2, Pos2)]) -> Option<(Vec<Vec<usize>>, usize)> {
+    let lengths = edges
+        .iter()
+        .map(|(start, end)| start.distance(*end))
+        .collect::<Vec<_>>();
+    let midpoints = edges
+        .iter()
+        .map(|(start, end)| *start + (*end - *start) / 2.)
+        .collect::<Vec<_>>();
+
+    let cell = (lengths.iter().sum::<f32>() / edges.len().max(1) as f32).max(1.);
+    let cell_of = |p: Pos2| ((p.x / cell).floor() as i64, (p.y / cell).floor() as i64);
+    let mut grid = HashMap::<(i64, i64), Vec<usize>>::new();
+    midpoints
+        .iter()
+        .enumerate()
+        .for_each(|(i, m)| grid.entry(cell_of(*m)).or_default().push(i));
+
+    let mut work = 0;
+    let mut compatible = Vec::with_capacity(edges.len());
+    for (i, p) in edges.iter().enumerate() {
+        let (x, y) = cell_of(midpoints[i]);
+        let k = (MAX_MIDPOINT_DISTANCE * lengths[i] / cell).ceil() as i64;
+        let near = |(cx, cy): &(i64, i64)| (cx - x).abs() <= k && (cy - y).abs() <= k;
+
+        // long edges are compared with the edges of all near cells instead of visiting empty cells
+        let side = 2. * k as f32 + 1.;
+        let candidates = match side * side > grid.len() as f32 {
+            true => {
+                work += grid.len();
+                grid.iter()
+                    .filter(|(c, _)| near(c))
+                    .flat_map(|(_, edges)| edges)
+                    .collect::<Vec<_>>()
+            }
+            false => {
+                work += (side * side) as usize;
+                (x - k..=x + k)
+                    .flat_map(|cx| (y - k..=y + k).map(move |cy| (cx, cy)))
+                    .filter_map(|c| grid.get(&c))
+                    .flatten()
+                    .collect::<Vec<_>>()
+            }
+        };
+        work += candidates.len();
+        if work > MAX_WORK {
+            return None;
+        }
+
+        let mut res = candidates
+            .into_iter()
+            .filter(|j| **j != i)
+            .map(|j| (*j, compatibility(*p, edges[*j])))
+            .filter(|(_, c)| *c >= COMPATIBILITY_THRESHOLD)
+            .collect::<Vec<_>>();
+        // ties are ordered by the index, so the result does not depend on the grid order
+        res.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        res.truncate(MAX_COMPATIBLE);
+        compatible.push(res.into_iter().map(|(j, _)| j).collect());
+    }
+
+    Some((compatible, work))
+}
+
+/// Number of segments the edges are split into in the cycle.
+fn segments(cycle: usize) -> usize {
+    2 << cycle
+}
+
+/// Returns number of iterations for every cycle. `attractions` is the number of compatible edge pairs,
+/// iterations are reduced if the bundling would exceed the `budget`. Returns `None` if even a single
+/// iteration per cycle exceeds it.
+fn iterations(attractions: usize, budget: usize) -> Option<Vec<usize>> {
+    let iterations = (0..CYCLES)
+        .map(|cycle| ITERATIONS as f64 * ITERATIONS_RATE.powi(cycle as i32) as f64)
+        .collect::<Vec<_>>();
+    let costs = (0..CYCLES)
+        .map(|cycle| (attractions * (segments(cycle) - 1)) as f64)
+        .collect::<Vec<_>>();
+
+    let min_work = costs.iter().sum::<f64>();
+    if min_work > budget as f64 {
+        return None;
+    }
+
+    // every cycle has at least one iteration, the rest of the budget is shared proportionally
+    let extra_work = iterations
+        .iter()
+        .zip(costs.iter())
+        .map(|(iterations, cost)| (iterations - 1.) * cost)
+        .sum::<f64>();
+    let scale = match extra_work > 0. {
+        true => ((budget as f64 - min_work) / extra_work).min(1.),
+        false => 1.,
+    };
+
+    let res = iterations
+        .into_iter()
+        .map(|iterations| 1 + ((iterations - 1.) * scale) as usize)
+        .collect();
+    Some(res)
+}
+
+/// Computes forces for all points of the polyline. Forces for the end points are always zero.
+fn forces(polyline: &[Pos2], polylines: &[Vec<Pos2>], compatible: &[usize]) -> Vec<Vec2> {
+    let last = polyline.len() - 1;
+    let subdivisions = last as f32;
+    let length = polyline[0].distance(polyline[last]);
+    let spring = SPRING / (length * subdivisions).max(f32::EPSILON);
+
+    (0..=last)
+        .map(|k| {
+            if k == 0 || k == last {
+                return Vec2::ZERO;
+            }
+
+            let p = polyline[k];
+            let spring_force = spring * ((polyline[k - 1] - p) + (polyline[k + 1] - p));
+            let electrostatic_force = compatible
+                .iter()
+                .map(|j| polylines[*j][k] - p)
+                .filter(|d| d.length() > f32::EPSILON)
+                .map(|d| d.normalized())
+                .fold(Vec2::ZERO, |acc, d| acc + d);
+
+            spring_force + electrostatic_force
+        })
+        .collect()
+}
+
+/// Resamples the polyline into the given number of segments of equal length keeping its ends intact.
+fn subdivide(polyline: &[Pos2], segments: usize) -> Vec<Pos2> {
+    let total = polyline_length(polyline);
+    let mut res = vec![polyline[0]];
+    res.extend((1..segments).map(|i| point_at(polyline, total * i as f32 / segments as f32)));
+    res.push(*polyline.last().unwrap());
+    res
+}
+
+/// Computes how much two edges should be bundled together. It is the product of the angle, scale,
+/// position and visibility compatibilities and lies in `[0, 1]`.
+pub fn compatibility(p: (Pos2, Pos2), q: (Pos2, Pos2)) -> f32 {
+    let (vp, vq) = (p.1 - p.0, q.1 - q.0);
+    let (lp, lq) = (vp.length(), vq.length());
+    if lp <= f32::EPSILON || lq <= f32::EPSILON {
+        return 0.;
+    }
+
+    let angle = (vp.dot(vq) / (lp * lq)).abs();
+
+    let avg = (lp + lq) / 2.;
+    let scale = 2. / (avg / lp.min(lq) + lp.max(lq) / avg);
+
+    let (mp, mq) = (p.0 + vp / 2., q.0 + vq / 2.);
+    let position = avg / (avg + mp.distance(mq));
+
+    let visibility = visibility(p, q).min(visibility(q, p));
+
+    angle * scale * position * visibility
+}
+
+/// Measures how much the projection of `q` onto the line of `p` overlaps `p`.
+fn visibility(p: (Pos2, Pos2), q: (Pos2, Pos2)) -> f32 {
+    let project = |point: Pos2| {
+        let v = p.1 - p.0;
+        p.0 + v * (point - p.0).dot(v) / v.length_sq()
+    };
+    let (i0, i1) = (project(q.0), project(q.1));
+    let length = i0.distance(i1);
+    if length <= f32::EPSILON {
+        return 0.;
+    }
+
+    let mp = p.0 + (p.1 - p.0) / 2.;
+    let mi = i0 + (i1 - i0) / 2.;
+    (1. - 2. * mp.distance(mi) / length).max(0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibility() {
+        let p = (Pos2::new(0., 0.), Pos2::new(100., 0.));
+
+        assert!((compatibility(p, p) - 1.).abs() < 1e-6);
+        assert!(
+            compatibility(p, (Pos2::new(0., 5.), Pos2::new(100., 5.))) > COMPATIBILITY_THRESHOLD
+        );
+        assert_eq!(
+            compatibility(p, (Pos2::new(50., -50.), Pos2::new(50., 50.))),
+            0.
+        );
+        assert!(
+            compatibility(p, (Pos2::new(500., 0.), Pos2::new(600., 0.))) < COMPATIBILITY_THRESHOLD
+        );
+    }
+
+    #[test]
+    fn test_bundle_edges_keeps_ends() {
+        let edges = [
+            (Pos2::new(0., 0.), Pos2::new(100., 0.)),
+            (Pos2::new(100., 10.), Pos2::new(0., 10.)),
+        ];
+
+        let bundled = bundle_edges(&edges).unwrap();
+
+        bundled
+            .iter()
+            .zip(edges.iter())
+            .for_each(|(polyline, (start, end))| {
+                assert_eq!(polyline.first(), Some(start));
+                assert_eq!(polyline.last(), Some(end));
+            });
+    }
+
+    #[test]
+    fn test_bundle_edges_attracts_compatible() {
+        let edges = [
+            (Pos2::new(0., 0.), Pos2::new(100., 0.)),
+            (Pos2::new(0., 10.), Pos2::new(100., 10.)),
+        ];
+
+        let bundled = bundle_edges(&edges).unwrap();
+
+        let middle = bundled[0].len() / 2;
+        assert!(bundled[0][middle].distance(bundled[1][middle]) < 10.);
+    }
+
+    #[test]
+    fn test_subdivide() {
+        let polyline = [Pos2::new(0., 0.), Pos2::new(4., 0.)];
+
+        assert_eq!(
+            subdivide(&polyline, 2),
+            vec![Pos2::new(0., 0.), Pos2::new(2., 0.), Pos2::new(4., 0.)]
+        );
+    }
+
+    #[test]
+    fn test_iterations_are_capped() {
+        let work = |attractions: usize| {
+            iterations(attractions, MAX_WORK)
+                .unwrap()
+                .into_iter()
+                .enumerate()
+                .map(|(cycle, iterations)| iterations * attractions * (segments(cycle) - 1))
+                .sum::<usize>()
+        };
+
+        assert_eq!(iterations(10, MAX_WORK).unwrap()[0], ITERATIONS);
+        assert!(work(500 * MAX_COMPATIBLE) <= MAX_WORK);
+        assert!(work(MAX_WORK / 57) <= MAX_WORK);
+
+        // a single iteration per cycle exceeds the budget
+        assert_eq!(iterations(MAX_WORK / 57 + 1, MAX_WORK), None);
+        assert_eq!(iterations(1, 0), None);
+
+        // the first cycle already moves the middle point of the edge
+        assert_eq!(segments(0), 2);
+    }
+
+    #[test]
+    fn test_compatible_edges_match_all_pairs() {
+        // pseudo random edges of different lengths
+        let mut seed = 7_u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as f32 / 65_536. * 500.
+        };
+        let edges = (0..300)
+            .map(|_| {
+                let start = Pos2::new(random(), random());
+                let end = start + Vec2::new(random(), random()) / 5.;
+                (start, end)
+            })
+            .collect::<Vec<_>>();
+
+        let (compatible, _) = compatible_edges(&edges).unwrap();
+
+        edges.iter().enumerate().for_each(|(i, p)| {
+            let mut expected = edges
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| i != *j)
+                .map(|(j, q)| (j, compatibility(*p, *q)))
+                .filter(|(_, c)| *c >= COMPATIBILITY_THRESHOLD)
+                .collect::<Vec<_>>();
+            expected.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            expected.truncate(MAX_COMPATIBLE);
+            let expected = expected.into_iter().map(|(j, _)| j).collect::<Vec<_>>();
+            assert_eq!(compatible[i], expected);
+        });
+    }
+
+    #[test]
+    fn test_large_graphs_are_not_bundled() {
+        // every edge is compared with every other edge
+        let edges = vec![(Pos2::new(0., 0.), Pos2::new(100., 0.)); 3_000];
+
+        assert_eq!(bundle_edges(&edges), None);
+    }
+
+    #[test]
+    fn test_compatible_edges_are_capped() {
+        let edges = (0..MAX_COMPATIBLE + 10)
+            .map(|i| (Pos2::new(0., i as f32), Pos2::new(100., i as f32)))
+            .collect::<Vec<_>>();
+
+        let bundled = bundle_edges(&edges).unwrap();
+
+        assert_eq!(bundled.len(), edges.len());
+        assert_eq!(bundled[0].len(), segments(CYCLES - 1) + 1);
+    }
+}
@@ -1,15 +1,25 @@
-use egui::Context;
-use petgraph::{stable_graph::NodeIndex, EdgeType};
+use egui::{Context, Id, Pos2, Vec2};
+use petgraph::{
+    stable_graph::{DefaultIx, EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
 
 use crate::{Edge, Graph, Metadata, Node, SettingsStyle};
 
 use super::Layers;
 
 /// Contains all the data about current widget state which is needed for custom drawing functions.
-pub struct WidgetState<'a, N: Clone, E: Clone, Ty: EdgeType> {
-    pub g: &'a Graph<N, E, Ty>,
+pub struct WidgetState<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    /// Id of the widget, see [`crate::GraphView::with_id`]. Caches of the drawing functions are
+    /// stored per widget id.
+    pub id: Id,
+    pub g: &'a Graph<N, E, Ty, Ix>,
     pub style: &'a SettingsStyle,
     pub meta: &'a Metadata,
+    /// Indices of the selected nodes computed at the beginning of the frame.
+    pub selected: &'a [NodeIndex<Ix>],
+    /// Index of the dragged node computed at the beginning of the frame.
+    pub dragged: Option<NodeIndex<Ix>>,
 }
 
 /// Allows to fully customize what shape would be drawn for node.
@@ -17,21 +27,189 @@ pub struct WidgetState<'a, N: Clone, E: Clone, Ty: EdgeType> {
 ///
 /// Parameters:
 /// - egui context, is needed for computing node props and styles;
-/// - node reference, contains all node data;
+/// - node index and node reference, contains all node data;
 /// - widget state with references to graph, style and metadata;
 /// - when you create a shape, add it to the layers.
-pub type FnNodeDraw<N, E, Ty> =
-    fn(&Context, n: &Node<N>, &WidgetState<N, E, Ty>, &mut Layers);
+pub type FnNodeDraw<N, E, Ty, Ix = DefaultIx> =
+    fn(&Context, (NodeIndex<Ix>, &Node<N, Ix>), &WidgetState<N, E, Ty, Ix>, &mut Layers);
 
 /// Allows to fully customize what shape would be drawn for an edge.
 /// The function is **called once for every node pair** which has edges connecting them. So make sure you have drawn all the edges which are passed to the function.
+/// In directed graphs edges going in the opposite directions are passed in separate calls. In undirected graphs all
+/// edges between two nodes are passed in one call with the node with the lower index first.
 ///
 /// Parameters:
 /// - egui context, is needed for computing node props and styles;
 /// - start node index and end node index;
-/// - vector of edges, all edges between start and end nodes;
+/// - vector of edges with their indices, all edges between start and end nodes;
 /// - widget state with references to graph, style and metadata;
 /// - when you create a shape, add it to the layers.
-pub type FnEdgeDraw<N, E, Ty> =
-    fn(&Context, (NodeIndex, NodeIndex), Vec<&Edge<E>>, &WidgetState<N, E, Ty>, &mut Layers);
+pub type FnEdgeDraw<N, E, Ty, Ix = DefaultIx> = fn(
+    &Context,
+    (NodeIndex<Ix>, NodeIndex<Ix>),
+    Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+    &WidgetState<N, E, Ty, Ix>,
+    &mut Layers,
+);
+
+
+/// Draws nodes. It is implemented for all functions and closures with the [`FnNodeDraw`] signature,
+/// so the drawer can capture application state like a highlighted path or a search term. Implement
+/// it for your own type if the drawer needs more complex state.
+pub trait NodeDrawer<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    /// Called for every node in the graph. See [`FnNodeDraw`] for the parameters description.
+    fn draw(
+        &self,
+        ctx: &Context,
+        n: (NodeIndex<Ix>, &Node<N, Ix>),
+        state: &WidgetState<N, E, Ty, Ix>,
+        l: &mut Layers,
+    );
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, F> NodeDrawer<N, E, Ty, Ix> for F
+where
+    F: Fn(&Context, (NodeIndex<Ix>, &Node<N, Ix>), &WidgetState<N, E, Ty, Ix>, &mut Layers),
+{
+    fn draw(
+        &self,
+        ctx: &Context,
+        n: (NodeIndex<Ix>, &Node<N, Ix>),
+        state: &WidgetState<N, E, Ty, Ix>,
+        l: &mut Layers,
+    ) {
+        self(ctx, n, state, l)
+    }
+}
+
+/// Draws edges. It is implemented for all functions and closures with the [`FnEdgeDraw`] signature,
+/// so the drawer can capture application state. Implement it for your own type if the drawer needs
+/// more complex state.
+pub trait EdgeDrawer<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    /// Called once for every node pair which has edges connecting them. See [`FnEdgeDraw`] for the
+    /// parameters description.
+    fn draw(
+        &self,
+        ctx: &Context,
+        bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+        edges: Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+        state: &WidgetState<N, E, Ty, Ix>,
+        l: &mut Layers,
+    );
 
+    /// Checks if the screen position is on any of the self loops of the node, so the click selects
+    /// the node. The default checks the loops drawn by [`crate::default_edges_draw`], override it if
+    /// the drawer draws loops differently.
+    fn loops_contain(
+        &self,
+        g: &Graph<N, E, Ty, Ix>,
+        style: &SettingsStyle,
+        meta: &Metadata,
+        idx: NodeIndex<Ix>,
+        pos: Pos2,
+    ) -> bool {
+        super::loops_contain(g, style, meta, idx, pos)
+    }
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, F> EdgeDrawer<N, E, Ty, Ix> for F
+where
+    F: Fn(
+        &Context,
+        (NodeIndex<Ix>, NodeIndex<Ix>),
+        Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+        &WidgetState<N, E, Ty, Ix>,
+        &mut Layers,
+    ),
+{
+    fn draw(
+        &self,
+        ctx: &Context,
+        bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+        edges: Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+        state: &WidgetState<N, E, Ty, Ix>,
+        l: &mut Layers,
+    ) {
+        self(ctx, bounds, edges, state, l)
+    }
+}
+
+/// Allows to customize how the node under the pointer is detected.
+///
+/// Parameters:
+/// - widget metadata with current zoom and pan;
+/// - node reference, contains all node data;
+/// - pointer position in graph coordinates;
+/// - style settings.
+pub type FnNodeDetect<N, Ix = DefaultIx> =
+    fn(&Metadata, &Node<N, Ix>, Vec2, &SettingsStyle) -> bool;
+
+/// Detects whether the node is under the pointer. It is implemented for all functions and closures
+/// with the [`FnNodeDetect`] signature, so the detector can capture application state.
+pub trait NodeDetector<N: Clone, Ix: IndexType = DefaultIx> {
+    /// Called for every node until the first one under the pointer is found. See [`FnNodeDetect`]
+    /// for the parameters description.
+    fn detect(
+        &self,
+        meta: &Metadata,
+        n: &Node<N, Ix>,
+        pos_in_graph: Vec2,
+        style: &SettingsStyle,
+    ) -> bool;
+}
+
+impl<N: Clone, Ix: IndexType, F> NodeDetector<N, Ix> for F
+where
+    F: Fn(&Metadata, &Node<N, Ix>, Vec2, &SettingsStyle) -> bool,
+{
+    fn detect(
+        &self,
+        meta: &Metadata,
+        n: &Node<N, Ix>,
+        pos_in_graph: Vec2,
+        style: &SettingsStyle,
+    ) -> bool {
+        self(meta, n, pos_in_graph, style)
+    }
+}
+
+/// Default node detector. Checks if the position is inside the node shape.
+pub fn default_node_detect<N: Clone, Ix: IndexType>(
+    meta: &Metadata,
+    n: &Node<N, Ix>,
+    pos_in_graph: Vec2,
+    settings_style: &SettingsStyle,
+) -> bool {
+    let half_size = n.screen_half_size(meta, settings_style) / meta.zoom;
+    n.shape().contains(half_size, pos_in_graph - n.location())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::HashSet};
+
+    use super::*;
+
+    #[test]
+    fn test_closure_detector() {
+        let meta = Metadata::default();
+        let style = SettingsStyle::default();
+        let mut n = Node::<(), DefaultIx>::new(Vec2::ZERO, ());
+        n.set_id(NodeIndex::new(1));
+
+        let hidden = HashSet::from([NodeIndex::new(1)]);
+        let calls = Cell::new(0);
+        let detector = |meta: &Metadata, n: &Node<(), DefaultIx>, pos, style: &SettingsStyle| {
+            calls.set(calls.get() + 1);
+            !hidden.contains(&n.id()) && default_node_detect(meta, n, pos, style)
+        };
+
+        assert!(default_node_detect(&meta, &n, Vec2::ZERO, &style));
+        assert!(!detector.detect(&meta, &n, Vec2::ZERO, &style));
+
+        n.set_id(NodeIndex::new(0));
+        assert!(detector.detect(&meta, &n, Vec2::ZERO, &style));
+        assert!(!detector.detect(&meta, &n, Vec2::new(1000., 0.), &style));
+        assert_eq!(calls.get(), 3);
+    }
+}
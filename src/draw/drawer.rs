@@ -1,89 +1,151 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use egui::Painter;
-use petgraph::{stable_graph::NodeIndex, EdgeType};
+use egui::{Context, Id, Painter, Shape};
+use petgraph::{
+    stable_graph::{DefaultIx, EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
 
-use crate::{settings::SettingsStyle, Edge, Graph, Metadata};
+use crate::{computed::ComputedState, settings::SettingsStyle, Edge, Graph, Metadata};
 
 use super::{
-    custom::{FnEdgeDraw, FnNodeDraw, WidgetState},
-    layers::Layers,
+    custom::{EdgeDrawer, NodeDrawer, WidgetState},
+    grid::{draw_alignment_guides, draw_grid},
+    layers::{Layer, Layers},
 };
 
+/// Function painting application shapes under or over the graph.
+pub type CanvasDraw<'a, N, E, Ty, Ix = DefaultIx> =
+    dyn Fn(&Context, &WidgetState<N, E, Ty, Ix>, &mut Layers) + 'a;
+
+/// Functions which are called by the drawer to fill the layers.
+pub struct DrawHooks<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    pub node: &'a dyn NodeDrawer<N, E, Ty, Ix>,
+    pub edge: &'a dyn EdgeDrawer<N, E, Ty, Ix>,
+    pub background: Option<&'a CanvasDraw<'a, N, E, Ty, Ix>>,
+    pub foreground: Option<&'a CanvasDraw<'a, N, E, Ty, Ix>>,
+}
+
 /// Mapping for 2 nodes and all edges between them
-type EdgeMap<'a, E> = HashMap<(NodeIndex, NodeIndex), Vec<&'a Edge<E>>>;
+type EdgeMap<'a, E, Ix> =
+    BTreeMap<(NodeIndex<Ix>, NodeIndex<Ix>), Vec<(EdgeIndex<Ix>, &'a Edge<E, Ix>)>>;
 
-pub struct Drawer<'a, N: Clone, E: Clone, Ty: EdgeType> {
+pub struct Drawer<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> {
     p: Painter,
+    id: Id,
 
-    g: &'a Graph<N, E, Ty>,
+    g: &'a Graph<N, E, Ty, Ix>,
     style: &'a SettingsStyle,
     meta: &'a Metadata,
+    comp: &'a ComputedState<Ix>,
 
-    node_draw_fn: FnNodeDraw<N, E, Ty>,
-    edge_draw_fn: FnEdgeDraw<N, E, Ty>,
+    hooks: DrawHooks<'a, N, E, Ty, Ix>,
 }
 
-impl<'a, N: Clone, E: Clone, Ty: EdgeType> Drawer<'a, N, E, Ty> {
+impl<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Drawer<'a, N, E, Ty, Ix> {
     pub fn new(
         p: Painter,
-        g: &'a Graph<N, E, Ty>,
+        id: Id,
+        g: &'a Graph<N, E, Ty, Ix>,
         style: &'a SettingsStyle,
         meta: &'a Metadata,
-        node_draw_fn: FnNodeDraw<N, E, Ty>,
-        edge_draw_fn: FnEdgeDraw<N, E, Ty>,
+        comp: &'a ComputedState<Ix>,
+        hooks: DrawHooks<'a, N, E, Ty, Ix>,
     ) -> Self {
         Drawer {
             g,
             p,
+            id,
             style,
             meta,
-            node_draw_fn,
-            edge_draw_fn
+            comp,
+            hooks,
         }
     }
 
     pub fn draw(self) {
+        let l = self.fill_layers();
+        l.draw(self.p)
+    }
+
+    /// Returns all shapes of the frame in the drawing order without painting them.
+    pub(crate) fn into_shapes(self) -> Vec<Shape> {
+        self.fill_layers().into_shapes()
+    }
+
+    fn fill_layers(&self) -> Layers {
         let mut l = Layers::default();
 
+        l.set_layer(Layer::Background);
+        if self.style.grid_enabled {
+            draw_grid(self.p.ctx(), self.p.clip_rect(), self.meta, self.style, &mut l);
+        }
+        self.fill_layers_canvas(self.hooks.background, &mut l);
+
+        l.set_layer(Layer::Edges);
         self.fill_layers_edges(&mut l);
+
+        l.set_layer(Layer::Overlays);
+        l.set_z_index(0);
+        let guides = &self.comp.alignment_guides;
+        draw_alignment_guides(self.p.ctx(), self.p.clip_rect(), self.meta, guides, &mut l);
+
+        l.set_layer(Layer::Nodes);
         self.fill_layers_nodes(&mut l);
 
-        l.draw(self.p)
+        l.set_layer(Layer::Foreground);
+        l.set_z_index(0);
+        self.fill_layers_canvas(self.hooks.foreground, &mut l);
+
+        l
     }
 
-    fn fill_layers_nodes(&self, l: &mut Layers) {
-        let state = &WidgetState {
+    fn widget_state(&self) -> WidgetState<'_, N, E, Ty, Ix> {
+        WidgetState {
+            id: self.id,
             g: self.g,
             meta: self.meta,
             style: self.style,
-        };
-        self.g
-            .nodes_iter()
-            .for_each(|(_, n)| {
-                (self.node_draw_fn)(self.p.ctx(), n, state, l)
-            });
+            selected: &self.comp.selected,
+            dragged: self.comp.dragged,
+        }
+    }
+
+    fn fill_layers_canvas(&self, hook: Option<&CanvasDraw<N, E, Ty, Ix>>, l: &mut Layers) {
+        if let Some(hook) = hook {
+            hook(self.p.ctx(), &self.widget_state(), l);
+        }
+    }
+
+    fn fill_layers_nodes(&self, l: &mut Layers) {
+        let state = &self.widget_state();
+        self.g.nodes_iter().for_each(|n| {
+            l.set_z_index(n.1.z_index());
+            self.hooks.node.draw(self.p.ctx(), n, state, l);
+        });
     }
 
     fn fill_layers_edges(&self, l: &mut Layers) {
-        let mut edge_map: EdgeMap<E> = HashMap::new();
+        let mut edge_map: EdgeMap<E, Ix> = BTreeMap::new();
 
         self.g.edges_iter().for_each(|(idx, e)| {
             let (source, target) = self.g.edge_endpoints(idx).unwrap();
+            // edges of undirected graph are grouped regardless of the order they were added in
+            let key = match self.g.is_directed() {
+                true => (source, target),
+                false => (source.min(target), source.max(target)),
+            };
             // compute map with edges between 2 nodes
-            edge_map.entry((source, target)).or_default().push(e);
+            edge_map.entry(key).or_default().push((idx, e));
         });
 
-        let state = &WidgetState {
-            g: self.g,
-            meta: self.meta,
-            style: self.style,
-        };
+        let state = &self.widget_state();
 
-        edge_map
-            .into_iter()
-            .for_each(|((start, end), edges)| 
-                (self.edge_draw_fn)(self.p.ctx(), (start, end), edges, state, l)
-            );
+        edge_map.into_iter().for_each(|(bounds, edges)| {
+            // all edges between two nodes are drawn at once, so they share the highest z-index
+            let z_index = edges.iter().map(|(_, e)| e.z_index()).max().unwrap_or_default();
+            l.set_z_index(z_index);
+            self.hooks.edge.draw(self.p.ctx(), bounds, edges, state, l);
+        });
     }
 }
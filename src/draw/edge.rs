@@ -1,175 +1,689 @@
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, SQRT_2, TAU};
 
 use egui::{
     epaint::{CubicBezierShape, QuadraticBezierShape},
-    Color32, Context, Pos2, Shape, Stroke, Vec2,
+    Color32, Context, Pos2, Rect, Shape, Stroke, Vec2,
 };
-use petgraph::{stable_graph::NodeIndex, EdgeType};
+use petgraph::{
+    stable_graph::{EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
+
+use crate::{Edge, EdgeMarker, EdgeRouting, Graph, Metadata, Node, SettingsStyle};
+
+use super::{
+    bundle::bundle_edges,
+    custom::WidgetState,
+    marker::{line_end, marker_shape},
+    route::{
+        bend, direction, distance_to_polyline, orthogonal_route, round_corners, spline_route,
+    },
+    route_cache::{Bundles, RouteCache},
+    stroke::add_patterned_path,
+    Layers,
+};
+
+/// Distance kept between routed edges and nodes they go around in graph coordinates.
+const ROUTE_MARGIN: f32 = 5.;
+
+/// Radius of the orthogonal edges corners in graph coordinates.
+const ROUTE_CORNER_RADIUS: f32 = 8.;
 
-use crate::{Edge, Node};
+/// Angle by which every next looped edge of the node is wider at its base.
+const LOOP_STACK_ANGLE: f32 = PI / 18.;
 
-use super::{custom::WidgetState, Layers};
+/// Distance from the looped edge line in screen pixels which is still considered a hit.
+const LOOP_HIT_DISTANCE: f32 = 3.;
 
-pub fn default_edges_draw<N: Clone, E: Clone, Ty: EdgeType>(
+pub fn default_edges_draw<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
     ctx: &Context,
-    bounds: (NodeIndex, NodeIndex),
-    edges: Vec<&Edge<E>>,
-    state: &WidgetState<N, E, Ty>,
+    bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+    edges: Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+    state: &WidgetState<N, E, Ty, Ix>,
     l: &mut Layers,
 ) {
     let (idx_start, idx_end) = bounds;
-    let mut order = edges.len();
-    edges.iter().for_each(|e| {
-        let n_start = state.g.node(idx_start).unwrap();
-        let n_end = state.g.node(idx_end).unwrap();
+    let n_start = state.g.node(idx_start).unwrap();
+    let n_end = state.g.node(idx_end).unwrap();
+
+    if idx_start == idx_end {
+        let mut order = edges.len();
+        edges.iter().for_each(|(_, e)| {
+            order -= 1;
+            draw_edge_looped(ctx, l, idx_start, e, order, state);
+        });
+        return;
+    }
 
-        order -= 1;
+    // edges going in the opposite direction are drawn by another call but share the same space
+    let opposite = match state.g.is_directed() {
+        true => state.g.edges_connecting(idx_end, idx_start).count(),
+        false => 0,
+    };
+    let offsets = parallel_offsets(edges.len(), opposite, idx_start > idx_end);
+
+    edges.iter().zip(offsets).for_each(|((_, e), offset)| {
+        match state.style.edge_routing {
+            EdgeRouting::Straight => draw_edge_basic(ctx, l, n_start, n_end, e, offset, state),
+            EdgeRouting::Orthogonal => draw_edge_orthogonal(ctx, l, bounds, e, offset, state),
+            EdgeRouting::Spline => draw_edge_spline(ctx, l, bounds, e, offset, state),
+            EdgeRouting::Bundled => draw_edge_bundled(ctx, l, bounds, e, offset, state),
+        };
+    });
+}
 
-        if idx_start == idx_end {
-            draw_edge_looped(ctx, l, n_start, e, order, state);
-        } else {
-            draw_edge_basic(ctx, l, n_start, n_end, e, order, state);
-        }
+/// Computes offsets of `count` parallel edges in multiples of the edge curve size.
+///
+/// All edges between two nodes, including `opposite` edges going in the other direction, are spread
+/// symmetrically around the straight line between the nodes. Edges going from the node with the lower
+/// index come first. Offsets are measured to the left of every edge direction, so edges going in
+/// opposite directions with the same offset lie on the opposite sides of the line.
+fn parallel_offsets(count: usize, opposite: usize, reversed: bool) -> Vec<f32> {
+    let center = (count + opposite) as f32 / 2. - 0.5;
+    let first = match reversed {
+        true => opposite,
+        false => 0,
+    };
+
+    (first..first + count)
+        .map(|slot| match reversed {
+            true => center - slot as f32,
+            false => slot as f32 - center,
+        })
+        .collect()
+}
+
+/// Returns control point of the quadratic curve connecting the points. The curve is bent to the left
+/// of its direction by `offset`, which is the control point distance from the straight line.
+fn curve_control_point(start: Pos2, end: Pos2, offset: f32) -> Pos2 {
+    let dir = direction(start, end);
+    start + (end - start) / 2. + Vec2::new(-dir.y, dir.x) * offset
+}
+
+fn draw_edge_orthogonal<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    ctx: &Context,
+    l: &mut Layers,
+    bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+    e: &Edge<E, Ix>,
+    offset: f32,
+    state: &WidgetState<N, E, Ty, Ix>,
+) {
+    let route = orthogonal_edge_route(bounds, e, offset, state);
+    let route = round_corners(&route, ROUTE_CORNER_RADIUS * state.meta.zoom);
+    draw_edge_polyline(ctx, l, e, route, state);
+}
+
+/// Computes the orthogonal route of the edge in screen coordinates clipped by the node shapes.
+fn orthogonal_edge_route<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+    e: &Edge<E, Ix>,
+    offset: f32,
+    state: &WidgetState<N, E, Ty, Ix>,
+) -> Vec<Pos2> {
+    let n_start = state.g.node(bounds.0).unwrap();
+    let n_end = state.g.node(bounds.1).unwrap();
+    let loc_start = n_start.screen_location(state.meta).to_pos2();
+    let loc_end = n_end.screen_location(state.meta).to_pos2();
+    let margin = ROUTE_MARGIN * state.meta.zoom;
+
+    // only nodes near the edge can be crossed by it
+    let area = Rect::from_two_pos(loc_start, loc_end).expand(margin);
+    let obstacles = state
+        .g
+        .nodes_iter()
+        .filter(|(idx, _)| *idx != bounds.0 && *idx != bounds.1)
+        .map(|(_, n)| screen_rect(n, state).expand(margin))
+        .filter(|r| r.intersects(area))
+        .collect::<Vec<_>>();
+
+    let offset = offset * e.curve_size() * state.meta.zoom / 2.;
+    let half_sizes = (
+        n_start.screen_half_size(state.meta, state.style),
+        n_end.screen_half_size(state.meta, state.style),
+    );
+    let route = orthogonal_route(loc_start, loc_end, half_sizes, offset, margin, &obstacles);
+    clip_route(route, (n_start, n_end), (loc_start, loc_end), state)
+}
+
+fn draw_edge_spline<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    ctx: &Context,
+    l: &mut Layers,
+    bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+    e: &Edge<E, Ix>,
+    offset: f32,
+    state: &WidgetState<N, E, Ty, Ix>,
+) {
+    let n_start = state.g.node(bounds.0).unwrap();
+    let n_end = state.g.node(bounds.1).unwrap();
+
+    // routes are computed in graph coordinates so they survive zoom and pan
+    let route_offset = offset * e.curve_size() / 2.;
+    let key = (bounds.0.index(), bounds.1.index(), route_offset.to_bits());
+    let route = RouteCache::route(ctx, state, key, || {
+        // obstacles are expanded by the margin in the route search
+        let obstacles = state
+            .g
+            .nodes_iter()
+            .filter(|(idx, _)| *idx != bounds.0 && *idx != bounds.1)
+            .map(|(_, n)| {
+                Rect::from_center_size(n.location().to_pos2(), n.half_size(state.style) * 2.)
+            })
+            .collect::<Vec<_>>();
+        let (start, end) = (n_start.location().to_pos2(), n_end.location().to_pos2());
+        spline_route(start, end, route_offset, ROUTE_MARGIN, &obstacles)
     });
+    let Some(route) = route else {
+        draw_edge_basic(ctx, l, n_start, n_end, e, offset, state);
+        return;
+    };
+
+    let route = route
+        .into_iter()
+        .map(|p| (p.to_vec2() * state.meta.zoom + state.meta.pan).to_pos2())
+        .collect();
+    let loc_start = n_start.screen_location(state.meta).to_pos2();
+    let loc_end = n_end.screen_location(state.meta).to_pos2();
+    let route = clip_route(route, (n_start, n_end), (loc_start, loc_end), state);
+
+    draw_edge_polyline(ctx, l, e, route, state);
 }
 
-fn draw_edge_basic<N: Clone, E: Clone, Ty: EdgeType>(
+fn draw_edge_bundled<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
     ctx: &Context,
     l: &mut Layers,
-    n_start: &Node<N>,
-    n_end: &Node<N>,
-    e: &Edge<E>,
-    order: usize,
-    state: &WidgetState<N, E, Ty>,
+    bounds: (NodeIndex<Ix>, NodeIndex<Ix>),
+    e: &Edge<E, Ix>,
+    offset: f32,
+    state: &WidgetState<N, E, Ty, Ix>,
+) {
+    let n_start = state.g.node(bounds.0).unwrap();
+    let n_end = state.g.node(bounds.1).unwrap();
+
+    let bundle = RouteCache::bundle(ctx, state, bounds, || compute_bundles(state));
+    let Some(bundle) = bundle else {
+        draw_edge_basic(ctx, l, n_start, n_end, e, offset, state);
+        return;
+    };
+
+    let route = bundle
+        .into_iter()
+        .map(|p| (p.to_vec2() * state.meta.zoom + state.meta.pan).to_pos2())
+        .collect::<Vec<_>>();
+    let route = bend(&route, offset * e.curve_size() * state.meta.zoom / 2.);
+    let loc_start = n_start.screen_location(state.meta).to_pos2();
+    let loc_end = n_end.screen_location(state.meta).to_pos2();
+    let route = clip_route(route, (n_start, n_end), (loc_start, loc_end), state);
+
+    draw_edge_polyline(ctx, l, e, route, state);
+}
+
+/// Bundles edges between all pairs of different nodes in graph coordinates.
+fn compute_bundles<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    state: &WidgetState<N, E, Ty, Ix>,
+) -> Bundles {
+    let mut pairs = state
+        .g
+        .edges_iter()
+        .filter_map(|(idx, _)| state.g.edge_endpoints(idx))
+        .filter(|(start, end)| start != end)
+        .map(|(start, end)| match state.g.is_directed() {
+            true => (start, end),
+            false => (start.min(end), start.max(end)),
+        })
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs.dedup();
+
+    let lines = pairs
+        .iter()
+        .map(|(start, end)| {
+            let loc = |idx: NodeIndex<Ix>| state.g.node(idx).unwrap().location().to_pos2();
+            (loc(*start), loc(*end))
+        })
+        .collect::<Vec<_>>();
+
+    // edges of too large graphs are drawn straight
+    let Some(bundled) = bundle_edges(&lines) else {
+        return Bundles::new();
+    };
+
+    pairs
+        .into_iter()
+        .map(|(start, end)| (start.index(), end.index()))
+        .zip(bundled)
+        .collect()
+}
+
+/// Cuts the parts of the route which are inside the nodes shapes, so it starts and ends at the nodes boundaries.
+fn clip_route<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    route: Vec<Pos2>,
+    nodes: (&Node<N, Ix>, &Node<N, Ix>),
+    centers: (Pos2, Pos2),
+    state: &WidgetState<N, E, Ty, Ix>,
+) -> Vec<Pos2> {
+    let clip_start = |route: Vec<Pos2>, n: &Node<N, Ix>, center: Pos2| {
+        let half_size = n.screen_half_size(state.meta, state.style);
+        match route
+            .iter()
+            .position(|p| !n.shape().contains(half_size, *p - center))
+        {
+            Some(i) if i > 0 => {
+                let (inside, outside) = (route[i - 1], route[i]);
+                let dir = direction(inside, outside);
+                let dist = n.shape().boundary_distance_from(half_size, inside - center, dir);
+                let mut res = vec![inside + dir * dist];
+                res.extend_from_slice(&route[i..]);
+                res
+            }
+            _ => route,
+        }
+    };
+
+    let route = clip_start(route, nodes.0, centers.0);
+    let mut route = clip_start(route.into_iter().rev().collect(), nodes.1, centers.1);
+    route.reverse();
+
+    route
+}
+
+/// Draws the edge along the polyline going from the start node boundary to the end node boundary.
+fn draw_edge_polyline<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    ctx: &Context,
+    l: &mut Layers,
+    e: &Edge<E, Ix>,
+    mut points: Vec<Pos2>,
+    state: &WidgetState<N, E, Ty, Ix>,
+) {
+    if points.len() < 2 {
+        return;
+    }
+
+    let (marker_start, marker_end) = e.markers(state.g.is_directed());
+    let tip_size = e.tip_size() * state.meta.zoom;
+    let stroke = Stroke::new(e.width() * state.meta.zoom, e.color(ctx));
+
+    let last = points.len() - 1;
+    let tips = (points[0], points[last]);
+    let dirs = (direction(points[1], points[0]), direction(points[last - 1], points[last]));
+
+    points[0] = line_end(marker_start, tips.0, dirs.0, tip_size, stroke.width);
+    points[last] = line_end(marker_end, tips.1, dirs.1, tip_size, stroke.width);
+
+    match e.is_plain() {
+        true => l.add(Shape::line(points, stroke)),
+        false => add_edge_path(ctx, l, e, &points, stroke, state),
+    };
+
+    add_markers(l, e, (marker_start, marker_end), tips, dirs, stroke, state);
+}
+
+/// Bounding rect of the node on the screen.
+fn screen_rect<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    n: &Node<N, Ix>,
+    state: &WidgetState<N, E, Ty, Ix>,
+) -> Rect {
+    Rect::from_center_size(
+        n.screen_location(state.meta).to_pos2(),
+        n.screen_half_size(state.meta, state.style) * 2.,
+    )
+}
+
+fn draw_edge_basic<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    ctx: &Context,
+    l: &mut Layers,
+    n_start: &Node<N, Ix>,
+    n_end: &Node<N, Ix>,
+    e: &Edge<E, Ix>,
+    offset: f32,
+    state: &WidgetState<N, E, Ty, Ix>,
 ) {
     let loc_start = n_start.screen_location(state.meta).to_pos2();
     let loc_end = n_end.screen_location(state.meta).to_pos2();
-    let rad_start = n_start.screen_radius(state.meta, state.style);
-    let rad_end = n_end.screen_radius(state.meta, state.style);
 
     let vec = loc_end - loc_start;
     let dist: f32 = vec.length();
     let dir = vec / dist;
 
-    let start_node_radius_vec = Vec2::new(rad_start, rad_start) * dir;
-    let end_node_radius_vec = Vec2::new(rad_end, rad_end) * dir;
+    // edges are attached to the node shapes boundaries
+    let rad_start = n_start
+        .shape()
+        .boundary_distance(n_start.screen_half_size(state.meta, state.style), dir);
+    let rad_end = n_end
+        .shape()
+        .boundary_distance(n_end.screen_half_size(state.meta, state.style), -dir);
 
-    let tip_end = loc_start + vec - end_node_radius_vec;
+    let tip_start = loc_start + rad_start * dir;
+    let tip_end = loc_end - rad_end * dir;
 
-    let edge_start = loc_start + start_node_radius_vec;
-    let edge_end = match state.g.is_directed() {
-        true => tip_end - e.tip_size() * state.meta.zoom * dir,
-        false => tip_end,
-    };
+    let (marker_start, marker_end) = e.markers(state.g.is_directed());
+    let tip_size = e.tip_size() * state.meta.zoom;
 
     let color = e.color(ctx);
     let stroke_edge = Stroke::new(e.width() * state.meta.zoom, color);
-    let stroke_tip = Stroke::new(0., color);
 
     // draw straight edge
-    if order == 0 {
-        let tip_start_1 =
-            tip_end - e.tip_size() * state.meta.zoom * rotate_vector(dir, e.tip_angle());
-        let tip_start_2 =
-            tip_end - e.tip_size() * state.meta.zoom * rotate_vector(dir, -e.tip_angle());
-
-        let shape = Shape::line_segment([edge_start, edge_end], stroke_edge);
-        l.add(shape);
-
-        // draw tips for directed edges
-        if state.g.is_directed() {
-            let shape_tip =
-                Shape::convex_polygon(vec![tip_end, tip_start_1, tip_start_2], color, stroke_tip);
-            l.add(shape_tip);
-        }
+    if offset == 0. {
+        let edge_start = line_end(marker_start, tip_start, -dir, tip_size, stroke_edge.width);
+        let edge_end = line_end(marker_end, tip_end, dir, tip_size, stroke_edge.width);
 
+        match e.is_plain() {
+            true => l.add(Shape::line_segment([edge_start, edge_end], stroke_edge)),
+            false => add_edge_path(ctx, l, e, &[edge_start, edge_end], stroke_edge, state),
+        };
+
+        let markers = (marker_start, marker_end);
+        add_markers(l, e, markers, (tip_start, tip_end), (-dir, dir), stroke_edge, state);
         return;
     }
 
     // draw curved edge
-    let dir_perpendicular = Vec2::new(-dir.y, dir.x);
-    let center_point = (edge_start + edge_end.to_vec2()).to_vec2() / 2.0;
-    let control_point = (center_point
-        + dir_perpendicular * e.curve_size() * state.meta.zoom * order as f32)
-        .to_pos2();
-
-    let tip_vec = control_point - tip_end;
-    let tip_dir = tip_vec / tip_vec.length();
-    let tip_size = e.tip_size() * state.meta.zoom;
-
-    let arrow_tip_dir_1 = rotate_vector(tip_dir, e.tip_angle()) * tip_size;
-    let arrow_tip_dir_2 = rotate_vector(tip_dir, -e.tip_angle()) * tip_size;
+    let control_point =
+        curve_control_point(tip_start, tip_end, e.curve_size() * state.meta.zoom * offset);
 
-    let tip_start_1 = tip_end + arrow_tip_dir_1;
-    let tip_start_2 = tip_end + arrow_tip_dir_2;
+    let dir_start = (tip_start - control_point).normalized();
+    let dir_end = (tip_end - control_point).normalized();
 
-    let edge_end_curved = point_between(tip_start_1, tip_start_2);
+    let edge_start = line_end(marker_start, tip_start, dir_start, tip_size, stroke_edge.width);
+    let edge_end = line_end(marker_end, tip_end, dir_end, tip_size, stroke_edge.width);
 
-    // draw curved not selected
     let shape_curved = QuadraticBezierShape::from_points_stroke(
-        [edge_start, control_point, edge_end_curved],
+        [edge_start, control_point, edge_end],
         false,
         Color32::TRANSPARENT,
         stroke_edge,
     );
-    l.add(shape_curved);
+    match e.is_plain() {
+        true => l.add(shape_curved),
+        false => add_edge_path(ctx, l, e, &shape_curved.flatten(None), stroke_edge, state),
+    };
 
-    let shape_tip_curved =
-        Shape::convex_polygon(vec![tip_end, tip_start_1, tip_start_2], color, stroke_tip);
-    l.add(shape_tip_curved);
+    let markers = (marker_start, marker_end);
+    let dirs = (dir_start, dir_end);
+    add_markers(l, e, markers, (tip_start, tip_end), dirs, stroke_edge, state);
 }
 
-fn draw_edge_looped<N: Clone, E: Clone, Ty: EdgeType>(
+/// Adds markers for both ends of the edge. Tips are the points on the nodes boundaries and
+/// directions point from the edge line to the tips.
+fn add_markers<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    l: &mut Layers,
+    e: &Edge<E, Ix>,
+    markers: (EdgeMarker, EdgeMarker),
+    tips: (Pos2, Pos2),
+    dirs: (Vec2, Vec2),
+    stroke: Stroke,
+    state: &WidgetState<N, E, Ty, Ix>,
+) {
+    let tip_size = e.tip_size() * state.meta.zoom;
+    [(markers.0, tips.0, dirs.0), (markers.1, tips.1, dirs.1)]
+        .into_iter()
+        .filter_map(|(marker, tip, dir)| {
+            marker_shape(marker, tip, dir, tip_size, e.tip_angle(), stroke)
+        })
+        .for_each(|shape| l.add(shape));
+}
+
+fn draw_edge_looped<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
     ctx: &Context,
     l: &mut Layers,
-    node: &Node<N>,
-    e: &Edge<E>,
+    idx: NodeIndex<Ix>,
+    e: &Edge<E, Ix>,
     order: usize,
-    state: &WidgetState<N, E, Ty>,
+    state: &WidgetState<N, E, Ty, Ix>,
 ) {
-    let rad = node.screen_radius(state.meta, state.style);
-    let center = node.screen_location(state.meta);
-    let center_horizon_angle = PI / 4.;
-    let y_intersect = center.y - rad * center_horizon_angle.sin();
-
-    let edge_start = Pos2::new(center.x - rad * center_horizon_angle.cos(), y_intersect);
-    let edge_end = Pos2::new(center.x + rad * center_horizon_angle.cos(), y_intersect);
+    let [start, control_1, control_2, end] = loop_curve(state.g, state.style, state.meta, idx, order);
 
-    let loop_size = rad * (state.style.edge_looped_size + order as f32);
+    let (marker_start, marker_end) = e.markers(state.g.is_directed());
+    let tip_size = e.tip_size() * state.meta.zoom;
+    let stroke = Stroke::new(e.width() * state.meta.zoom, e.color(ctx));
 
-    let control_point1 = Pos2::new(center.x + loop_size, center.y - loop_size);
-    let control_point2 = Pos2::new(center.x - loop_size, center.y - loop_size);
+    let dirs = (direction(control_1, start), direction(control_2, end));
+    let edge_start = line_end(marker_start, start, dirs.0, tip_size, stroke.width);
+    let edge_end = line_end(marker_end, end, dirs.1, tip_size, stroke.width);
 
-    let stroke = Stroke::new(e.width() * state.meta.zoom, e.color(ctx));
     let shape = CubicBezierShape::from_points_stroke(
-        [edge_end, control_point1, control_point2, edge_start],
+        [edge_start, control_1, control_2, edge_end],
         false,
         Color32::TRANSPARENT,
         stroke,
     );
 
-    l.add(shape);
+    match e.is_plain() {
+        true => l.add(shape),
+        false => add_edge_path(ctx, l, e, &shape.flatten(None), stroke, state),
+    };
+
+    add_markers(l, e, (marker_start, marker_end), (start, end), dirs, stroke, state);
+}
+
+/// Computes screen points of the cubic curve of the looped edge with the given order.
+///
+/// Loops are placed in the largest angular gap between the edges going to other nodes. Every next
+/// loop is bigger and starts further from the loop axis, so stacked loops do not cross each other.
+fn loop_curve<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &Graph<N, E, Ty, Ix>,
+    style: &SettingsStyle,
+    meta: &Metadata,
+    idx: NodeIndex<Ix>,
+    order: usize,
+) -> [Pos2; 4] {
+    let node = g.node(idx).unwrap();
+    let angles = g
+        .g
+        .neighbors_undirected(idx)
+        .filter(|neighbor| *neighbor != idx)
+        .map(|neighbor| g.node(neighbor).unwrap().location() - node.location())
+        .filter(|v| *v != Vec2::ZERO)
+        .map(|v| v.y.atan2(v.x))
+        .collect();
+    let axis = Vec2::angled(loop_angle(angles));
+
+    let half_size = node.screen_half_size(meta, style);
+    let center = node.screen_location(meta).to_pos2();
+    let loop_size =
+        node.shape().boundary_distance(half_size, axis) * (style.edge_looped_size + order as f32);
+    let spread = (FRAC_PI_4 + order as f32 * LOOP_STACK_ANGLE).min(FRAC_PI_2);
+
+    let dir_start = rotate_vector(axis, -spread);
+    let dir_end = rotate_vector(axis, spread);
+    let start = center + dir_start * node.shape().boundary_distance(half_size, dir_start);
+    let end = center + dir_end * node.shape().boundary_distance(half_size, dir_end);
+
+    // control points are placed like for the loop of a unit square spanning the loop size
+    let control_1 = center + rotate_vector(axis, -FRAC_PI_4) * loop_size * SQRT_2;
+    let control_2 = center + rotate_vector(axis, FRAC_PI_4) * loop_size * SQRT_2;
+
+    [start, control_1, control_2, end]
+}
+
+/// Returns the angle in the middle of the largest gap between the given angles. If there are
+/// no angles the loop is placed above the node.
+fn loop_angle(mut angles: Vec<f32>) -> f32 {
+    if angles.is_empty() {
+        return -FRAC_PI_2;
+    }
+
+    angles.iter_mut().for_each(|a| *a = a.rem_euclid(TAU));
+    angles.sort_by(f32::total_cmp);
+
+    let last = angles[angles.len() - 1];
+    let (mut gap_start, mut gap) = (last, angles[0] + TAU - last);
+    angles.windows(2).for_each(|w| {
+        if w[1] - w[0] > gap {
+            (gap_start, gap) = (w[0], w[1] - w[0]);
+        }
+    });
+
+    gap_start + gap / 2.
+}
+
+/// Checks if the screen position is on any of the looped edges of the node.
+pub fn loops_contain<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &Graph<N, E, Ty, Ix>,
+    style: &SettingsStyle,
+    meta: &Metadata,
+    idx: NodeIndex<Ix>,
+    pos: Pos2,
+) -> bool {
+    g.edges_connecting(idx, idx)
+        .enumerate()
+        .any(|(order, e)| {
+            let tolerance = e.weight().width() * meta.zoom / 2. + LOOP_HIT_DISTANCE;
+            let curve = CubicBezierShape::from_points_stroke(
+                loop_curve(g, style, meta, idx, order),
+                false,
+                Color32::TRANSPARENT,
+                Stroke::NONE,
+            );
+            distance_to_polyline(&curve.flatten(None), pos) <= tolerance
+        })
+}
+
+/// Strokes the edge path with the edge pattern and flow animation.
+fn add_edge_path<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    ctx: &Context,
+    l: &mut Layers,
+    e: &Edge<E, Ix>,
+    path: &[Pos2],
+    stroke: Stroke,
+    state: &WidgetState<N, E, Ty, Ix>,
+) {
+    let time = ctx.input(|i| i.time) as f32;
+    add_patterned_path(
+        l,
+        path,
+        stroke,
+        e.stroke_pattern(),
+        e.flow(),
+        e.flow_speed() * state.meta.zoom,
+        time,
+    );
 }
 
 /// rotates vector by angle
-fn rotate_vector(vec: Vec2, angle: f32) -> Vec2 {
+pub fn rotate_vector(vec: Vec2, angle: f32) -> Vec2 {
     let cos = angle.cos();
     let sin = angle.sin();
     Vec2::new(cos * vec.x - sin * vec.y, sin * vec.x + cos * vec.y)
 }
 
-/// finds point exactly in the middle between 2 points
-fn point_between(p1: Pos2, p2: Pos2) -> Pos2 {
-    let base = p1 - p2;
-    let base_len = base.length();
-    let dir = base / base_len;
-    p1 - (base_len / 2.) * dir
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parallel_offsets_single_edge_is_straight() {
+        assert_eq!(parallel_offsets(1, 0, false), vec![0.]);
+        assert_eq!(parallel_offsets(1, 0, true), vec![0.]);
+    }
+
+    #[test]
+    fn test_parallel_offsets_are_symmetric() {
+        assert_eq!(parallel_offsets(2, 0, false), vec![-0.5, 0.5]);
+        assert_eq!(parallel_offsets(3, 0, false), vec![-1., 0., 1.]);
+    }
+
+    #[test]
+    fn test_parallel_offsets_opposite_directions() {
+        // one edge in each direction
+        assert_eq!(parallel_offsets(1, 1, false), vec![-0.5]);
+        assert_eq!(parallel_offsets(1, 1, true), vec![-0.5]);
+
+        // two edges from the lower index and one back
+        assert_eq!(parallel_offsets(2, 1, false), vec![-1., 0.]);
+        assert_eq!(parallel_offsets(1, 2, true), vec![-1.]);
+    }
+
+    #[test]
+    fn test_opposite_edges_do_not_overlap() {
+        let (a, b) = (Pos2::new(0., 0.), Pos2::new(10., 0.));
+
+        let forward = curve_control_point(a, b, parallel_offsets(1, 1, false)[0]);
+        let backward = curve_control_point(b, a, parallel_offsets(1, 1, true)[0]);
+
+        assert_eq!(forward, Pos2::new(5., -0.5));
+        assert_eq!(backward, Pos2::new(5., 0.5));
+    }
+
+    #[test]
+    fn test_parallel_edges_are_evenly_spaced() {
+        let (a, b) = (Pos2::new(0., 0.), Pos2::new(0., 10.));
+
+        let mut points = parallel_offsets(2, 2, false)
+            .into_iter()
+            .map(|offset| curve_control_point(a, b, offset))
+            .collect::<Vec<_>>();
+        points.extend(
+            parallel_offsets(2, 2, true)
+                .into_iter()
+                .map(|offset| curve_control_point(b, a, offset)),
+        );
+
+        let mut xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+        xs.sort_by(f32::total_cmp);
+        assert_eq!(xs, vec![-1.5, -0.5, 0.5, 1.5]);
+        points.iter().for_each(|p| assert_eq!(p.y, 5.));
+    }
+
+    #[test]
+    fn test_orthogonal_parallel_edges_start_at_nodes() {
+        let mut g = Graph::<(), (), petgraph::Directed>::new(Default::default());
+        let a = g.add_node(Node::new(Vec2::new(0., 0.), ()));
+        let b = g.add_node(Node::new(Vec2::new(100., 60.), ()));
+        (0..3).for_each(|_| {
+            g.add_edge(a, b, Edge::new(()));
+        });
+        let (style, meta) = (SettingsStyle::default(), Metadata::default());
+        let state = WidgetState {
+            id: egui::Id::null(),
+            g: &g,
+            style: &style,
+            meta: &meta,
+            selected: &[],
+            dragged: None,
+        };
+
+        let routes = g
+            .edges_iter()
+            .zip(parallel_offsets(3, 0, false))
+            .map(|((_, e), offset)| orthogonal_edge_route((a, b), e, offset, &state))
+            .collect::<Vec<_>>();
+
+        routes.iter().for_each(|route| {
+            let (first, last) = (route[0], *route.last().unwrap());
+            let start_dist = first.to_vec2().length();
+            let end_dist = (last - Pos2::new(100., 60.)).length();
+            // the edge starts and ends at the node boundaries, not in the empty space around them
+            let radius = |idx| g.node(idx).unwrap().screen_radius(&meta, &style);
+            assert!((start_dist - radius(a)).abs() < 0.1);
+            assert!((end_dist - radius(b)).abs() < 0.1);
+        });
+        assert_ne!(routes[0], routes[1]);
+        assert_ne!(routes[1], routes[2]);
+    }
+
+    #[test]
+    fn test_loop_angle_without_edges_is_above() {
+        assert_eq!(loop_angle(vec![]), -FRAC_PI_2);
+    }
+
+    #[test]
+    fn test_loop_angle_is_opposite_to_single_edge() {
+        assert!((loop_angle(vec![0.]) - PI).abs() < 1e-6);
+        assert!((loop_angle(vec![FRAC_PI_2]).rem_euclid(TAU) - 3. * FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_loop_angle_in_largest_gap() {
+        // edges to the right, to the bottom and to the left, the largest gap is at the top
+        let angle = loop_angle(vec![0., FRAC_PI_2, PI]);
+        assert!((angle - 3. * FRAC_PI_2).abs() < 1e-6);
+
+        // the gap crossing zero angle
+        let angle = loop_angle(vec![FRAC_PI_2, PI, 3. * FRAC_PI_2]);
+        assert!(angle.rem_euclid(TAU) < 1e-6 || (angle - TAU).abs() < 1e-6);
+    }
+
     #[test]
     fn test_rotate_vector() {
         let vec = Vec2::new(1.0, 0.0);
@@ -178,11 +692,4 @@ mod tests {
         assert!((rotated.x - 0.0).abs() < 1e-6);
         assert!((rotated.y - 1.0).abs() < 1e-6);
     }
-
-    #[test]
-    fn test_point_between() {
-        let m = point_between(Pos2::new(0.0, 0.0), Pos2::new(2.0, 0.0));
-        assert!((m.x - 1.0).abs() < 1e-6);
-        assert!((m.y).abs() < 1e-6);
-    }
 }
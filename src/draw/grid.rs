@@ -0,0 +1,88 @@
+use egui::{Context, Pos2, Rect, Shape, Stroke};
+
+use crate::{alignment::AlignmentGuide, Metadata, SettingsStyle};
+
+use super::{Layer, Layers};
+
+/// Minimal distance between grid lines on the screen. When zoomed out the grid spacing
+/// is doubled until lines are at least this far apart.
+const GRID_MIN_SCREEN_STEP: f32 = 8.;
+
+pub fn draw_grid(ctx: &Context, rect: Rect, meta: &Metadata, style: &SettingsStyle, l: &mut Layers) {
+    if style.grid_spacing <= 0. {
+        return;
+    }
+
+    let mut step = style.grid_spacing;
+    while step * meta.zoom < GRID_MIN_SCREEN_STEP {
+        step *= 2.;
+    }
+
+    let min = (rect.min.to_vec2() - meta.pan) / meta.zoom;
+    let max = (rect.max.to_vec2() - meta.pan) / meta.zoom;
+
+    let color = ctx
+        .style()
+        .visuals
+        .widgets
+        .noninteractive
+        .bg_stroke
+        .color
+        .gamma_multiply(0.5);
+    let stroke = Stroke::new(1., color);
+
+    let mut x = (min.x / step).floor() * step;
+    while x <= max.x {
+        let screen_x = x * meta.zoom + meta.pan.x;
+        l.add_to(Layer::Background, Shape::line_segment(
+            [
+                Pos2::new(screen_x, rect.min.y),
+                Pos2::new(screen_x, rect.max.y),
+            ],
+            stroke,
+        ));
+        x += step;
+    }
+
+    let mut y = (min.y / step).floor() * step;
+    while y <= max.y {
+        let screen_y = y * meta.zoom + meta.pan.y;
+        l.add_to(Layer::Background, Shape::line_segment(
+            [
+                Pos2::new(rect.min.x, screen_y),
+                Pos2::new(rect.max.x, screen_y),
+            ],
+            stroke,
+        ));
+        y += step;
+    }
+}
+
+pub fn draw_alignment_guides(
+    ctx: &Context,
+    rect: Rect,
+    meta: &Metadata,
+    guides: &[AlignmentGuide],
+    l: &mut Layers,
+) {
+    let stroke = Stroke::new(1., ctx.style().visuals.selection.stroke.color);
+    guides.iter().for_each(|guide| {
+        let points = match *guide {
+            AlignmentGuide::Vertical(x) => {
+                let screen_x = x * meta.zoom + meta.pan.x;
+                [
+                    Pos2::new(screen_x, rect.min.y),
+                    Pos2::new(screen_x, rect.max.y),
+                ]
+            }
+            AlignmentGuide::Horizontal(y) => {
+                let screen_y = y * meta.zoom + meta.pan.y;
+                [
+                    Pos2::new(rect.min.x, screen_y),
+                    Pos2::new(rect.max.x, screen_y),
+                ]
+            }
+        };
+        l.add_top(Shape::line_segment(points, stroke));
+    });
+}
@@ -1,30 +1,117 @@
 use egui::{Painter, Shape};
 
-/// Represents drawing layers of the frame. The bottom layer is drawn first, the top
-/// layer is drawn last.
+/// Drawing layers of the frame listed from the bottom to the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Layer {
+    /// Grid and shapes painted by the background hook.
+    Background,
+    #[default]
+    Edges,
+    EdgeLabels,
+    Nodes,
+    NodeLabels,
+    /// Alignment guides and interacted elements.
+    Overlays,
+    /// Shapes painted by the foreground hook.
+    Foreground,
+}
+
+/// Represents drawing layers of the frame. Shapes are drawn layer by layer from the bottom to the top.
+/// Inside of a layer shapes of the elements with higher z-index are drawn later, shapes with the same
+/// z-index are drawn in the order they were added.
 #[derive(Default)]
 pub struct Layers {
-    bottom: Vec<Shape>,
-    top: Vec<Shape>,
+    shapes: Vec<(Layer, i32, Shape)>,
+
+    /// Layer used by [`Layers::add`]. It is set by the drawer for every drawing stage.
+    layer: Layer,
+    /// Z-index of the element which is being drawn.
+    z_index: i32,
 }
 
 impl Layers {
-    /// Default drawing layer. This is the bottom layer.
+    /// Adds the shape to the default layer of the current drawing stage: [`Layer::Edges`] for edges,
+    /// [`Layer::Nodes`] for nodes and [`Layer::Background`] or [`Layer::Foreground`] for the hooks.
     pub fn add(&mut self, shape: impl Into<Shape>) {
-        self.bottom.push(shape.into());
+        self.add_to(self.layer, shape);
     }
 
-    /// Drawing layer for prioritizing shapes drawings. This is the top layer.
+    /// Drawing layer for prioritizing shapes drawings. This is the [`Layer::Overlays`] layer.
     pub fn add_top(&mut self, shape: impl Into<Shape>) {
-        self.top.push(shape.into());
+        self.add_to(Layer::Overlays, shape);
+    }
+
+    /// Adds the shape to the given layer.
+    pub fn add_to(&mut self, layer: Layer, shape: impl Into<Shape>) {
+        self.shapes.push((layer, self.z_index, shape.into()));
+    }
+
+    pub(crate) fn set_layer(&mut self, layer: Layer) {
+        self.layer = layer;
+    }
+
+    pub(crate) fn set_z_index(&mut self, z_index: i32) {
+        self.z_index = z_index;
     }
 
     pub(crate) fn draw(self, p: Painter) {
-        self.bottom.into_iter().for_each(|shape| {
-            p.add(shape);
-        });
-        self.top.into_iter().for_each(|shape| {
+        self.into_shapes().into_iter().for_each(|shape| {
             p.add(shape);
         });
     }
+
+    /// Returns all shapes in the drawing order.
+    pub(crate) fn into_shapes(mut self) -> Vec<Shape> {
+        // sort is stable so the order of addition is kept for the same layer and z-index
+        self.shapes.sort_by_key(|(layer, z_index, _)| (*layer, *z_index));
+        self.shapes.into_iter().map(|(_, _, shape)| shape).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Color32, Pos2};
+
+    use super::*;
+
+    fn circle(radius: f32) -> Shape {
+        Shape::circle_filled(Pos2::ZERO, radius, Color32::WHITE)
+    }
+
+    fn radii(shapes: Vec<Shape>) -> Vec<f32> {
+        shapes
+            .into_iter()
+            .map(|shape| match shape {
+                Shape::Circle(circle) => circle.radius,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_layers_order() {
+        let mut l = Layers::default();
+        l.set_layer(Layer::Nodes);
+        l.add(circle(1.));
+        l.add_to(Layer::Background, circle(2.));
+        l.add_top(circle(3.));
+        l.add_to(Layer::Edges, circle(4.));
+        l.add(circle(5.));
+
+        assert_eq!(radii(l.into_shapes()), vec![2., 4., 1., 5., 3.]);
+    }
+
+    #[test]
+    fn test_z_index_order() {
+        let mut l = Layers::default();
+        l.set_z_index(1);
+        l.add(circle(1.));
+        l.set_z_index(-1);
+        l.add(circle(2.));
+        l.set_z_index(0);
+        l.add(circle(3.));
+        l.add_to(Layer::Nodes, circle(4.));
+
+        assert_eq!(radii(l.into_shapes()), vec![2., 3., 1., 4.]);
+    }
 }
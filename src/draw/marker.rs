@@ -0,0 +1,105 @@
+use egui::{Pos2, Shape, Stroke, Vec2};
+
+use crate::EdgeMarker;
+
+use super::edge::rotate_vector;
+
+/// Radius of the circle marker relative to the marker size.
+const CIRCLE_SCALE: f32 = 0.25;
+
+/// Half length of the bar marker relative to the marker size.
+const BAR_SCALE: f32 = 0.35;
+
+/// Returns how much the edge line should be shortened at the end with the marker.
+///
+/// `size` and `width` are the marker size and the edge stroke width in screen coordinates.
+pub fn marker_length(marker: EdgeMarker, size: f32, width: f32) -> f32 {
+    match marker {
+        EdgeMarker::None => 0.,
+        EdgeMarker::Triangle | EdgeMarker::Diamond => size,
+        EdgeMarker::OpenArrow => width,
+        EdgeMarker::Circle => 2. * CIRCLE_SCALE * size,
+        EdgeMarker::Bar => width / 2.,
+    }
+}
+
+/// Creates shape of the marker pointing to the `tip` along the `dir` unit vector.
+pub fn marker_shape(
+    marker: EdgeMarker,
+    tip: Pos2,
+    dir: Vec2,
+    size: f32,
+    angle: f32,
+    stroke: Stroke,
+) -> Option<Shape> {
+    let color = stroke.color;
+    let side_1 = tip - size * rotate_vector(dir, angle);
+    let side_2 = tip - size * rotate_vector(dir, -angle);
+    let perpendicular = Vec2::new(-dir.y, dir.x);
+
+    match marker {
+        EdgeMarker::None => None,
+        EdgeMarker::Triangle => Some(Shape::convex_polygon(
+            vec![tip, side_1, side_2],
+            color,
+            Stroke::new(0., color),
+        )),
+        EdgeMarker::OpenArrow => Some(Shape::line(vec![side_1, tip, side_2], stroke)),
+        EdgeMarker::Diamond => {
+            let middle = tip - dir * size / 2.;
+            let half_width = size * angle.sin();
+            Some(Shape::convex_polygon(
+                vec![
+                    tip,
+                    middle + perpendicular * half_width,
+                    tip - dir * size,
+                    middle - perpendicular * half_width,
+                ],
+                color,
+                Stroke::new(0., color),
+            ))
+        }
+        EdgeMarker::Circle => {
+            let rad = CIRCLE_SCALE * size;
+            Some(Shape::circle_filled(tip - dir * rad, rad, color))
+        }
+        EdgeMarker::Bar => {
+            let half = perpendicular * BAR_SCALE * size;
+            Some(Shape::line_segment([tip + half, tip - half], stroke))
+        }
+    }
+}
+
+/// Returns point where the edge line ends for the given marker.
+pub fn line_end(marker: EdgeMarker, tip: Pos2, dir: Vec2, size: f32, width: f32) -> Pos2 {
+    tip - dir * marker_length(marker, size, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Color32;
+
+    use super::*;
+
+    #[test]
+    fn test_line_end() {
+        let tip = Pos2::new(10., 0.);
+        assert_eq!(line_end(EdgeMarker::None, tip, Vec2::X, 4., 2.), tip);
+        assert_eq!(line_end(EdgeMarker::Triangle, tip, Vec2::X, 4., 2.), Pos2::new(6., 0.));
+        assert_eq!(line_end(EdgeMarker::Circle, tip, Vec2::X, 4., 2.), Pos2::new(8., 0.));
+        assert_eq!(line_end(EdgeMarker::Bar, tip, Vec2::X, 4., 2.), Pos2::new(9., 0.));
+    }
+
+    #[test]
+    fn test_marker_shape_none() {
+        let shape = marker_shape(
+            EdgeMarker::None,
+            Pos2::ZERO,
+            Vec2::X,
+            4.,
+            0.2,
+            Stroke::new(1., Color32::WHITE),
+        );
+        assert!(shape.is_none());
+    }
+}
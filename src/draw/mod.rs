@@ -1,11 +1,20 @@
+mod bundle;
 mod custom;
 mod drawer;
 mod edge;
+mod grid;
 mod layers;
+mod marker;
 mod node;
+mod route;
+mod route_cache;
+mod stroke;
 
-pub use self::custom::{FnEdgeDraw, FnNodeDraw, WidgetState};
-pub use self::drawer::Drawer;
-pub use self::edge::default_edges_draw;
-pub use self::layers::Layers;
+pub use self::custom::{
+    default_node_detect, EdgeDrawer, FnEdgeDraw, FnNodeDetect, FnNodeDraw, NodeDetector,
+    NodeDrawer, WidgetState,
+};
+pub use self::drawer::{CanvasDraw, DrawHooks, Drawer};
+pub use self::edge::{default_edges_draw, loops_contain};
+pub use self::layers::{Layer, Layers};
 pub use self::node::default_node_draw;
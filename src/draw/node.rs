@@ -1,38 +1,61 @@
 use egui::{
     epaint::{CircleShape, TextShape},
-    Context, FontFamily, FontId, Pos2, Stroke,
+    Context, FontFamily, FontId, Pos2, Shape,
 };
-use petgraph::EdgeType;
+use petgraph::{
+    stable_graph::{IndexType, NodeIndex},
+    EdgeType,
+};
+
+use crate::{Node, NodeShape};
 
-use crate::Node;
+use super::{custom::WidgetState, Layer, Layers};
 
-use super::{custom::WidgetState, Layers};
+/// Size of the outline marking pinned nodes relative to the node size.
+const PIN_RING_SCALE: f32 = 1.4;
 
-pub fn default_node_draw<N: Clone, E: Clone, Ty: EdgeType>(
+pub fn default_node_draw<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
     ctx: &Context,
-    n: &Node<N>,
-    state: &WidgetState<N, E, Ty>,
+    (_, n): (NodeIndex<Ix>, &Node<N, Ix>),
+    state: &WidgetState<N, E, Ty, Ix>,
     l: &mut Layers,
 ) {
     let is_interacted = n.selected() || n.dragged();
     let loc = n.screen_location(state.meta).to_pos2();
-    let rad = match is_interacted {
-        true => n.screen_radius(state.meta, state.style) * 1.5,
-        false => n.screen_radius(state.meta, state.style),
+    let half_size = match is_interacted {
+        true => n.screen_half_size(state.meta, state.style) * 1.5,
+        false => n.screen_half_size(state.meta, state.style),
     };
+    let rad = half_size.y;
 
     let color = n.color(ctx);
-    let shape_node = CircleShape {
-        center: loc,
-        radius: rad,
-        fill: color,
-        stroke: Stroke::new(1., color),
+    let stroke = n.stroke(ctx);
+    let shape_node: Shape = match n.shape() {
+        NodeShape::Circle => CircleShape {
+            center: loc,
+            radius: rad,
+            fill: color,
+            stroke,
+        }
+        .into(),
+        shape => Shape::convex_polygon(shape.outline(loc, half_size), color, stroke),
     };
     match is_interacted {
         true => l.add_top(shape_node),
-        false => l.add(shape_node),
+        false => l.add_to(Layer::Nodes, shape_node),
     };
 
+    if n.pinned() {
+        let shape_pin = match n.shape() {
+            NodeShape::Circle => Shape::circle_stroke(loc, rad * PIN_RING_SCALE, stroke),
+            shape => Shape::closed_line(shape.outline(loc, half_size * PIN_RING_SCALE), stroke),
+        };
+        match is_interacted {
+            true => l.add_top(shape_pin),
+            false => l.add_to(Layer::Nodes, shape_pin),
+        };
+    }
+
     let show_label = state.style.labels_always || is_interacted;
     if !show_label {
         return;
@@ -52,6 +75,6 @@ pub fn default_node_draw<N: Clone, E: Clone, Ty: EdgeType>(
     let shape_label = TextShape::new(label_pos, galley);
     match is_interacted {
         true => l.add_top(shape_label),
-        false => l.add(shape_label),
+        false => l.add_to(Layer::NodeLabels, shape_label),
     };
 }
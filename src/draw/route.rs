@@ -0,0 +1,571 @@
+use std::{cmp::Reverse, collections::BinaryHeap, f32::consts::PI};
+
+use egui::{Pos2, Rect, Vec2};
+
+/// Number of points used to approximate every rounded corner.
+const CORNER_SEGMENTS: usize = 6;
+
+/// Number of points used to approximate every span of the spline route.
+const SPLINE_SEGMENTS: usize = 8;
+
+/// Maximum number of times the spline route is searched again with the obstacles it crossed.
+const SPLINE_ITERATIONS: usize = 4;
+
+/// Maximum number of obstacles the spline route is searched around. The search checks visibility
+/// between all obstacle corners, so routes through denser areas are drawn straight.
+const SPLINE_MAX_OBSTACLES: usize = 32;
+
+/// Largest offset of the orthogonal route ends in fractions of the node half size. It keeps
+/// the ends inside of the node shape, so the route can be clipped by the node boundary.
+const END_OFFSET_LIMIT: f32 = 0.5;
+
+/// Computes orthogonal route between centers of two nodes which avoids `obstacles` if possible.
+///
+/// The route starts at `start` center and ends at `end` center, so it should be clipped by the
+/// node shapes before drawing. `offset` shifts the route to separate parallel edges, `margin` is
+/// the distance kept between the route and obstacles it goes around. Offset of the route ends
+/// is limited by `half_sizes` of the start and end nodes, so the route always leaves the nodes.
+pub fn orthogonal_route(
+    start: Pos2,
+    end: Pos2,
+    half_sizes: (Vec2, Vec2),
+    offset: f32,
+    margin: f32,
+    obstacles: &[Rect],
+) -> Vec<Pos2> {
+    let mid = start + (end - start) / 2.;
+    let horizontal_first = (end.x - start.x).abs() >= (end.y - start.y).abs();
+
+    let mut xs = vec![mid.x + offset];
+    let mut ys = vec![mid.y + offset];
+    obstacles.iter().for_each(|r| {
+        xs.push(r.min.x - margin + offset);
+        xs.push(r.max.x + margin + offset);
+        ys.push(r.min.y - margin + offset);
+        ys.push(r.max.y + margin + offset);
+    });
+
+    let end_offset = |half: f32| {
+        let limit = half * END_OFFSET_LIMIT;
+        offset.clamp(-limit, limit)
+    };
+    let (sx, sy) = (
+        start.x + end_offset(half_sizes.0.x),
+        start.y + end_offset(half_sizes.0.y),
+    );
+    let (ex, ey) = (
+        end.x + end_offset(half_sizes.1.x),
+        end.y + end_offset(half_sizes.1.y),
+    );
+
+    let mut candidates = vec![];
+    let hvh = xs.iter().map(|x| {
+        vec![
+            Pos2::new(start.x, sy),
+            Pos2::new(*x, sy),
+            Pos2::new(*x, ey),
+            Pos2::new(end.x, ey),
+        ]
+    });
+    let vhv = ys.iter().map(|y| {
+        vec![
+            Pos2::new(sx, start.y),
+            Pos2::new(sx, *y),
+            Pos2::new(ex, *y),
+            Pos2::new(ex, end.y),
+        ]
+    });
+    match horizontal_first {
+        true => {
+            candidates.extend(hvh);
+            candidates.extend(vhv);
+        }
+        false => {
+            candidates.extend(vhv);
+            candidates.extend(hvh);
+        }
+    };
+    candidates.push(vec![
+        Pos2::new(start.x, sy),
+        Pos2::new(ex, sy),
+        Pos2::new(ex, end.y),
+    ]);
+    candidates.push(vec![
+        Pos2::new(sx, start.y),
+        Pos2::new(sx, ey),
+        Pos2::new(end.x, ey),
+    ]);
+
+    // the first candidate is the preferred one, others are used only if they collide less
+    // or have the same number of collisions and are shorter
+    candidates
+        .into_iter()
+        .map(simplify)
+        .enumerate()
+        .min_by(|(i_a, a), (i_b, b)| {
+            let key_a = (collisions(a, obstacles), *i_a != 0, path_length(a));
+            let key_b = (collisions(b, obstacles), *i_b != 0, path_length(b));
+            key_a
+                .0
+                .cmp(&key_b.0)
+                .then(key_a.1.cmp(&key_b.1))
+                .then(key_a.2.total_cmp(&key_b.2))
+        })
+        .map(|(_, route)| route)
+        .unwrap()
+}
+
+/// Computes smooth route between centers of two nodes which goes around `obstacles` if possible.
+///
+/// The shortest path is searched in the visibility graph built from the corners of the obstacles
+/// expanded by `margin` and then interpolated with a spline. At first only obstacles near the
+/// straight line between the nodes are considered, the ones crossed by the found path are added
+/// to the search until the path is clear. The route is straight if it has to go around more than
+/// [`SPLINE_MAX_OBSTACLES`]. `offset` bends the route sideways to separate parallel edges.
+pub fn spline_route(
+    start: Pos2,
+    end: Pos2,
+    offset: f32,
+    margin: f32,
+    obstacles: &[Rect],
+) -> Vec<Pos2> {
+    let area = Rect::from_two_pos(start, end).expand(margin);
+    let mut considered = obstacles
+        .iter()
+        .map(|r| r.intersects(area))
+        .collect::<Vec<_>>();
+
+    let mut path = vec![start, end];
+    for _ in 0..SPLINE_ITERATIONS {
+        let active = obstacles
+            .iter()
+            .zip(considered.iter())
+            .filter(|(_, c)| **c)
+            .map(|(r, _)| *r)
+            .collect::<Vec<_>>();
+        if active.len() > SPLINE_MAX_OBSTACLES {
+            path = vec![start, end];
+            break;
+        }
+        path = visibility_path(start, end, margin, &active);
+
+        let mut crossed = false;
+        obstacles
+            .iter()
+            .zip(considered.iter_mut())
+            .filter(|(r, c)| !**c && path.windows(2).any(|w| segment_intersects_rect(w[0], w[1], r)))
+            .for_each(|(_, c)| {
+                *c = true;
+                crossed = true;
+            });
+        if !crossed {
+            break;
+        }
+    }
+
+    bend(&smooth(&path), offset)
+}
+
+/// Finds the shortest polyline from `start` to `end` which does not cross any of the obstacles.
+/// Returns the straight line if there is no such polyline.
+fn visibility_path(start: Pos2, end: Pos2, margin: f32, obstacles: &[Rect]) -> Vec<Pos2> {
+    let mut points = vec![start, end];
+    points.extend(
+        obstacles
+            .iter()
+            .flat_map(|r| {
+                let r = r.expand(margin);
+                [r.left_top(), r.right_top(), r.right_bottom(), r.left_bottom()]
+            })
+            .filter(|p| !obstacles.iter().any(|r| r.contains(*p))),
+    );
+    let visible = |a: Pos2, b: Pos2| !obstacles.iter().any(|r| segment_intersects_rect(a, b, r));
+
+    // dijkstra on the complete graph of points, visibility is checked lazily
+    let mut dist = vec![f32::INFINITY; points.len()];
+    let mut prev = vec![None; points.len()];
+    let mut done = vec![false; points.len()];
+    // bits of non-negative floats are ordered the same way as the floats
+    let mut queue = BinaryHeap::from([Reverse((0_u32, 0))]);
+    dist[0] = 0.;
+    while let Some(Reverse((_, current))) = queue.pop() {
+        if current == 1 {
+            break;
+        }
+        if done[current] {
+            continue;
+        }
+        done[current] = true;
+
+        (0..points.len()).filter(|next| !done[*next]).for_each(|next| {
+            let d = dist[current] + points[current].distance(points[next]);
+            if d < dist[next] && visible(points[current], points[next]) {
+                dist[next] = d;
+                prev[next] = Some(current);
+                queue.push(Reverse((d.to_bits(), next)));
+            }
+        });
+    }
+
+    if !dist[1].is_finite() {
+        return vec![start, end];
+    }
+
+    let mut path = vec![end];
+    let mut current = 1;
+    while let Some(p) = prev[current] {
+        path.push(points[p]);
+        current = p;
+    }
+    path.reverse();
+
+    path
+}
+
+/// Interpolates the polyline with the Catmull-Rom spline going through all of its points.
+pub fn smooth(points: &[Pos2]) -> Vec<Pos2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let last = points.len() - 1;
+    let mut res = vec![points[0]];
+    (0..last).for_each(|i| {
+        let p0 = points[i.saturating_sub(1)].to_vec2();
+        let p1 = points[i].to_vec2();
+        let p2 = points[i + 1].to_vec2();
+        let p3 = points[(i + 2).min(last)].to_vec2();
+        (1..=SPLINE_SEGMENTS).for_each(|s| {
+            let t = s as f32 / SPLINE_SEGMENTS as f32;
+            let p = 0.5
+                * (2. * p1
+                    + (p2 - p0) * t
+                    + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t * t
+                    + (3. * p1 - p0 - 3. * p2 + p3) * t * t * t);
+            res.push(p.to_pos2());
+        });
+    });
+
+    res
+}
+
+/// Shifts the polyline sideways by `offset` in the middle keeping its ends in place.
+pub fn bend(points: &[Pos2], offset: f32) -> Vec<Pos2> {
+    if offset == 0. || points.len() < 2 {
+        return points.to_vec();
+    }
+
+    let points = match points.len() {
+        2 => (0..=SPLINE_SEGMENTS)
+            .map(|i| points[0].lerp(points[1], i as f32 / SPLINE_SEGMENTS as f32))
+            .collect(),
+        _ => points.to_vec(),
+    };
+    let total = path_length(&points);
+    let last = points.len() - 1;
+
+    let mut passed = 0.;
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            if i > 0 {
+                passed += points[i - 1].distance(*p);
+            }
+            let dir = direction(points[i.saturating_sub(1)], points[(i + 1).min(last)]);
+            *p + Vec2::new(-dir.y, dir.x) * offset * (PI * passed / total).sin()
+        })
+        .collect()
+}
+
+/// Replaces corners of the polyline with arcs of the given radius.
+pub fn round_corners(points: &[Pos2], radius: f32) -> Vec<Pos2> {
+    if points.len() < 3 || radius <= 0. {
+        return points.to_vec();
+    }
+
+    let mut res = vec![points[0]];
+    points.windows(3).for_each(|w| {
+        let (prev, corner, next) = (w[0], w[1], w[2]);
+        let r = radius
+            .min(prev.distance(corner) / 2.)
+            .min(corner.distance(next) / 2.);
+
+        let from = corner + (prev - corner).normalized() * r;
+        let to = corner + (next - corner).normalized() * r;
+        (0..=CORNER_SEGMENTS).for_each(|i| {
+            let t = i as f32 / CORNER_SEGMENTS as f32;
+            let a = from.lerp(corner, t);
+            let b = corner.lerp(to, t);
+            res.push(a.lerp(b, t));
+        });
+    });
+    res.push(*points.last().unwrap());
+
+    res
+}
+
+/// Removes zero length segments and merges collinear ones.
+pub fn simplify(points: Vec<Pos2>) -> Vec<Pos2> {
+    let mut res: Vec<Pos2> = vec![];
+    points.into_iter().for_each(|p| {
+        if res.last() == Some(&p) {
+            return;
+        }
+        if res.len() >= 2 {
+            let (a, b) = (res[res.len() - 2], res[res.len() - 1]);
+            let cross = (b - a).x * (p - b).y - (b - a).y * (p - b).x;
+            if cross.abs() < f32::EPSILON && (b - a).dot(p - b) >= 0. {
+                res.pop();
+            }
+        }
+        res.push(p);
+    });
+
+    res
+}
+
+pub fn path_length(points: &[Pos2]) -> f32 {
+    points.windows(2).map(|w| w[0].distance(w[1])).sum()
+}
+
+/// Returns the distance from the point to the closest segment of the polyline.
+pub fn distance_to_polyline(points: &[Pos2], p: Pos2) -> f32 {
+    points
+        .windows(2)
+        .map(|w| {
+            let v = w[1] - w[0];
+            let t = match v.length_sq() > 0. {
+                true => ((p - w[0]).dot(v) / v.length_sq()).clamp(0., 1.),
+                false => 0.,
+            };
+            p.distance(w[0] + v * t)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Counts how many obstacles are crossed by the polyline.
+fn collisions(points: &[Pos2], obstacles: &[Rect]) -> usize {
+    obstacles
+        .iter()
+        .filter(|r| points.windows(2).any(|w| segment_intersects_rect(w[0], w[1], r)))
+        .count()
+}
+
+/// Checks if the segment crosses the rect using the Liang-Barsky clipping.
+pub fn segment_intersects_rect(a: Pos2, b: Pos2, r: &Rect) -> bool {
+    let d = b - a;
+    let mut t0: f32 = 0.;
+    let mut t1: f32 = 1.;
+    let checks = [
+        (-d.x, a.x - r.min.x),
+        (d.x, r.max.x - a.x),
+        (-d.y, a.y - r.min.y),
+        (d.y, r.max.y - a.y),
+    ];
+    for (p, q) in checks {
+        if p == 0. {
+            if q < 0. {
+                return false;
+            }
+            continue;
+        }
+        let t = q / p;
+        if p < 0. {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 > t1 {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Direction of the segment from `from` to `to`. Returns zero vector for zero length segment.
+pub fn direction(from: Pos2, to: Pos2) -> Vec2 {
+    let v = to - from;
+    match v.length() > 0. {
+        true => v / v.length(),
+        false => Vec2::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF_SIZES: (Vec2, Vec2) = (Vec2::splat(5.), Vec2::splat(5.));
+
+    #[test]
+    fn test_orthogonal_route_no_obstacles() {
+        let route = orthogonal_route(
+            Pos2::new(0., 0.),
+            Pos2::new(10., 4.),
+            HALF_SIZES,
+            0.,
+            1.,
+            &[],
+        );
+
+        assert_eq!(
+            route,
+            vec![
+                Pos2::new(0., 0.),
+                Pos2::new(5., 0.),
+                Pos2::new(5., 4.),
+                Pos2::new(10., 4.),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_orthogonal_route_is_axis_aligned() {
+        let route = orthogonal_route(
+            Pos2::new(0., 0.),
+            Pos2::new(3., 10.),
+            HALF_SIZES,
+            2.,
+            1.,
+            &[],
+        );
+
+        route.windows(2).for_each(|w| {
+            assert!(w[0].x == w[1].x || w[0].y == w[1].y);
+        });
+    }
+
+    #[test]
+    fn test_orthogonal_route_avoids_obstacle() {
+        let obstacle = Rect::from_center_size(Pos2::new(5., 2.), Vec2::new(2., 2.));
+
+        let route = orthogonal_route(
+            Pos2::new(0., 0.),
+            Pos2::new(10., 4.),
+            HALF_SIZES,
+            0.,
+            1.,
+            &[obstacle],
+        );
+
+        assert_eq!(collisions(&route, &[obstacle]), 0);
+    }
+
+    #[test]
+    fn test_orthogonal_route_ends_stay_in_nodes() {
+        let (start, end) = (Pos2::new(0., 0.), Pos2::new(100., 50.));
+
+        let routes = [-10., 0., 10.]
+            .map(|offset| orthogonal_route(start, end, HALF_SIZES, offset, 1., &[]));
+
+        routes.iter().for_each(|route| {
+            assert!((route[0] - start).length() < 5.);
+            assert!((*route.last().unwrap() - end).length() < 5.);
+        });
+        assert_eq!(routes[0][0], Pos2::new(0., -2.5));
+        // the middle segments keep the whole offset
+        assert_eq!(routes[0][1].x, 40.);
+        assert_eq!(routes[2][1].x, 60.);
+    }
+
+    #[test]
+    fn test_spline_route_no_obstacles() {
+        let route = spline_route(Pos2::new(0., 0.), Pos2::new(10., 4.), 0., 1., &[]);
+
+        assert_eq!(route, vec![Pos2::new(0., 0.), Pos2::new(10., 4.)]);
+    }
+
+    #[test]
+    fn test_spline_route_avoids_obstacle() {
+        let obstacle = Rect::from_center_size(Pos2::new(50., 0.), Vec2::new(10., 10.));
+
+        let route = spline_route(Pos2::new(0., 0.), Pos2::new(100., 0.), 0., 5., &[obstacle]);
+
+        assert_eq!(route.first(), Some(&Pos2::new(0., 0.)));
+        assert_eq!(route.last(), Some(&Pos2::new(100., 0.)));
+        assert_eq!(collisions(&route, &[obstacle]), 0);
+    }
+
+    #[test]
+    fn test_spline_route_adds_crossed_obstacles() {
+        // the second obstacle is far from the straight line but blocks the way around the first one
+        let obstacles = [
+            Rect::from_center_size(Pos2::new(50., 0.), Vec2::new(10., 10.)),
+            Rect::from_center_size(Pos2::new(50., -20.), Vec2::new(10., 30.)),
+        ];
+
+        let route = spline_route(Pos2::new(0., 0.), Pos2::new(100., 0.), 0., 1., &obstacles);
+
+        assert_eq!(collisions(&route, &obstacles), 0);
+    }
+
+    #[test]
+    fn test_spline_route_too_many_obstacles() {
+        let obstacles = (0..=SPLINE_MAX_OBSTACLES)
+            .map(|i| Rect::from_center_size(Pos2::new(i as f32 * 10., 0.), Vec2::new(4., 4.)))
+            .collect::<Vec<_>>();
+
+        let route = spline_route(Pos2::new(-10., 0.), Pos2::new(400., 0.), 0., 1., &obstacles);
+
+        assert_eq!(route, vec![Pos2::new(-10., 0.), Pos2::new(400., 0.)]);
+    }
+
+    #[test]
+    fn test_bend() {
+        let bent = bend(&[Pos2::new(0., 0.), Pos2::new(10., 0.)], 2.);
+
+        assert_eq!(bent.first(), Some(&Pos2::new(0., 0.)));
+        assert!((bent[SPLINE_SEGMENTS / 2] - Pos2::new(5., 2.)).length() < 1e-4);
+        assert!((*bent.last().unwrap() - Pos2::new(10., 0.)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_smooth_goes_through_points() {
+        let points = [Pos2::new(0., 0.), Pos2::new(10., 5.), Pos2::new(20., 0.)];
+
+        let smoothed = smooth(&points);
+
+        assert_eq!(smoothed.len(), 2 * SPLINE_SEGMENTS + 1);
+        assert_eq!(smoothed[SPLINE_SEGMENTS], Pos2::new(10., 5.));
+        assert_eq!(smoothed.last(), Some(&Pos2::new(20., 0.)));
+    }
+
+    #[test]
+    fn test_distance_to_polyline() {
+        let points = [Pos2::new(0., 0.), Pos2::new(10., 0.), Pos2::new(10., 10.)];
+
+        assert_eq!(distance_to_polyline(&points, Pos2::new(5., 3.)), 3.);
+        assert_eq!(distance_to_polyline(&points, Pos2::new(12., 5.)), 2.);
+        assert_eq!(distance_to_polyline(&points, Pos2::new(-3., -4.)), 5.);
+    }
+
+    #[test]
+    fn test_round_corners() {
+        let points = [Pos2::new(0., 0.), Pos2::new(10., 0.), Pos2::new(10., 10.)];
+
+        let rounded = round_corners(&points, 2.);
+
+        assert_eq!(rounded.first(), Some(&Pos2::new(0., 0.)));
+        assert_eq!(rounded.last(), Some(&Pos2::new(10., 10.)));
+        assert_eq!(rounded[1], Pos2::new(8., 0.));
+        assert_eq!(rounded[CORNER_SEGMENTS + 1], Pos2::new(10., 2.));
+    }
+
+    #[test]
+    fn test_simplify() {
+        let points = vec![
+            Pos2::new(0., 0.),
+            Pos2::new(5., 0.),
+            Pos2::new(5., 0.),
+            Pos2::new(10., 0.),
+            Pos2::new(10., 5.),
+        ];
+
+        assert_eq!(
+            simplify(points),
+            vec![Pos2::new(0., 0.), Pos2::new(10., 0.), Pos2::new(10., 5.)]
+        );
+    }
+}
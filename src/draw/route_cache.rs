@@ -0,0 +1,276 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use egui::{Context, Id, Pos2};
+use petgraph::{
+    stable_graph::{IndexType, NodeIndex},
+    EdgeType,
+};
+
+use super::custom::WidgetState;
+
+/// Identifies the route by the indices of the edge nodes and the bits of the edge offset among
+/// parallel edges. Indices are stored as `usize` so the cache does not depend on the graph index type.
+type RouteKey = (usize, usize, u32);
+
+/// Bundled polylines of all edges between every pair of nodes given by their indices.
+pub type Bundles = HashMap<(usize, usize), Vec<Pos2>>;
+
+/// Stores routes computed in graph coordinates in the egui temp memory.
+///
+/// All routes are dropped as soon as location or size of any node changes, because every
+/// node can be an obstacle for every route. Adding or removing an edge drops them too, because
+/// it changes offsets of parallel edges and edge bundles.
+#[derive(Clone, Default)]
+pub struct RouteCache {
+    frame: Option<u64>,
+    signature: u64,
+    dragging: bool,
+    /// Whether the graph has not changed since the previous frame.
+    settled: bool,
+    routes: HashMap<RouteKey, Vec<Pos2>>,
+    bundles: Option<Bundles>,
+}
+
+impl RouteCache {
+    /// Returns the cached route or computes it with `compute`. Cached route is reused only if nodes
+    /// have not changed since it was computed.
+    ///
+    /// Returns `None` while the layout changes every frame, e.g. while it is animated or any node
+    /// is dragged, because routes around obstacles are too expensive to be computed every frame.
+    pub fn route<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+        ctx: &Context,
+        state: &WidgetState<N, E, Ty, Ix>,
+        key: RouteKey,
+        compute: impl FnOnce() -> Vec<Pos2>,
+    ) -> Option<Vec<Pos2>> {
+        let (settled, cached) = Self::with_cache(ctx, state, |cache| {
+            (cache.settled, cache.routes.get(&key).cloned())
+        });
+        if cached.is_some() {
+            return cached;
+        }
+        if !settled {
+            // routes are computed in the next frame if the layout does not change anymore
+            ctx.request_repaint();
+            return None;
+        }
+
+        let route = compute();
+        Self::with_cache(ctx, state, |cache| {
+            cache.routes.insert(key, route.clone());
+        });
+
+        Some(route)
+    }
+
+    /// Returns the bundled polyline for the edges between the nodes. All bundles are computed at once
+    /// with `compute` when the first of them is requested after nodes have changed.
+    ///
+    /// Returns `None` while any node is dragged or the layout changes every frame, e.g. while it is
+    /// animated, because bundling is too expensive to be done every frame. Returns `None` as well if
+    /// `compute` has not bundled the edges, e.g. because the graph is too large.
+    pub fn bundle<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+        ctx: &Context,
+        state: &WidgetState<N, E, Ty, Ix>,
+        nodes: (NodeIndex<Ix>, NodeIndex<Ix>),
+        compute: impl FnOnce() -> Bundles,
+    ) -> Option<Vec<Pos2>> {
+        let (dragging, settled, computed) = Self::with_cache(ctx, state, |cache| {
+            (cache.dragging, cache.settled, cache.bundles.is_some())
+        });
+        if dragging {
+            return None;
+        }
+        if !settled {
+            // bundles are computed in the next frame if the layout does not change anymore
+            ctx.request_repaint();
+            return None;
+        }
+        if !computed {
+            let bundles = compute();
+            Self::with_cache(ctx, state, |cache| cache.bundles = Some(bundles));
+        }
+
+        Self::with_cache(ctx, state, |cache| {
+            cache
+                .bundles
+                .as_ref()
+                .and_then(|b| b.get(&(nodes.0.index(), nodes.1.index())).cloned())
+        })
+    }
+
+    fn with_cache<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, R>(
+        ctx: &Context,
+        state: &WidgetState<N, E, Ty, Ix>,
+        f: impl FnOnce(&mut RouteCache) -> R,
+    ) -> R {
+        // every widget has its own cache, because graphs of the widgets have different routes
+        let id = Id::new("egui_graphs_route_cache").with(state.id);
+        let frame = ctx.frame_nr();
+
+        ctx.data_mut(|data| {
+            let cache = data.get_temp_mut_or_default::<RouteCache>(id);
+
+            // the graph can change only between frames so the signature is computed once per frame
+            if cache.frame != Some(frame) {
+                // the graph shown for the first time is considered settled, e.g. for the export
+                let first = cache.frame.is_none();
+                cache.frame = Some(frame);
+                cache.dragging = state.g.nodes_iter().any(|(_, n)| n.dragged());
+                let signature = graph_signature(state);
+                cache.settled = first || signature == cache.signature;
+                if signature != cache.signature {
+                    cache.signature = signature;
+                    cache.routes.clear();
+                    cache.bundles = None;
+                }
+            }
+
+            f(cache)
+        })
+    }
+}
+
+/// Hashes locations and sizes of all nodes and endpoints of all edges.
+fn graph_signature<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    state: &WidgetState<N, E, Ty, Ix>,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.g.nodes_iter().for_each(|(idx, n)| {
+        let half_size = n.half_size(state.style);
+        idx.index().hash(&mut hasher);
+        [n.location().x, n.location().y, half_size.x, half_size.y]
+            .iter()
+            .for_each(|v| v.to_bits().hash(&mut hasher));
+    });
+    state.g.edges_iter().for_each(|(idx, _)| {
+        let (start, end) = state.g.edge_endpoints(idx).unwrap();
+        (idx.index(), start.index(), end.index()).hash(&mut hasher);
+    });
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use crate::{to_graph, Metadata, SettingsStyle};
+
+    use super::*;
+
+    #[test]
+    fn test_cache_per_widget() {
+        let ctx = Context::default();
+        let style = SettingsStyle::default();
+        let meta = Metadata::default();
+
+        let mut user_g = StableGraph::<(), ()>::new();
+        let a = user_g.add_node(());
+        let b = user_g.add_node(());
+        user_g.add_edge(a, b, ());
+        let graphs = [to_graph(&user_g), to_graph(&user_g)];
+
+        // both widgets are shown in the same frame and have the same edge keys
+        let routes = graphs
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                let state = WidgetState {
+                    id: Id::new(i),
+                    g,
+                    style: &style,
+                    meta: &meta,
+                    selected: &[],
+                    dragged: None,
+                };
+                RouteCache::route(&ctx, &state, (0, 1, 0), || {
+                    vec![Pos2::new(i as f32, i as f32)]
+                })
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(routes[0], Some(vec![Pos2::new(0., 0.)]));
+        assert_eq!(routes[1], Some(vec![Pos2::new(1., 1.)]));
+    }
+
+    #[test]
+    fn test_routes_wait_for_settled_layout() {
+        let ctx = Context::default();
+        let style = SettingsStyle::default();
+        let meta = Metadata::default();
+
+        let mut user_g = StableGraph::<(), ()>::new();
+        let a = user_g.add_node(());
+        let b = user_g.add_node(());
+        user_g.add_edge(a, b, ());
+        let mut g = to_graph(&user_g);
+
+        let route = |g: &crate::Graph<(), (), petgraph::Directed>| {
+            ctx.begin_frame(Default::default());
+            let state = WidgetState {
+                id: Id::null(),
+                g,
+                style: &style,
+                meta: &meta,
+                selected: &[],
+                dragged: None,
+            };
+            let res = RouteCache::route(&ctx, &state, (0, 1, 0), || vec![Pos2::ZERO]);
+            let _ = ctx.end_frame();
+            res
+        };
+
+        // the graph shown for the first time is routed at once
+        assert!(route(&g).is_some());
+
+        // moved nodes are drawn straight until the layout does not change for a frame
+        g.node_mut(a).unwrap().set_location(egui::Vec2::new(1., 1.));
+        assert!(route(&g).is_none());
+        assert!(route(&g).is_some());
+    }
+
+    #[test]
+    fn test_bundles_wait_for_settled_layout() {
+        let ctx = Context::default();
+        let style = SettingsStyle::default();
+        let meta = Metadata::default();
+
+        let mut user_g = StableGraph::<(), ()>::new();
+        let a = user_g.add_node(());
+        let b = user_g.add_node(());
+        user_g.add_edge(a, b, ());
+        let mut g = to_graph(&user_g);
+
+        let bundle = |g: &crate::Graph<(), (), petgraph::Directed>| {
+            ctx.begin_frame(Default::default());
+            let state = WidgetState {
+                id: Id::null(),
+                g,
+                style: &style,
+                meta: &meta,
+                selected: &[],
+                dragged: None,
+            };
+            let res = RouteCache::bundle(&ctx, &state, (a, b), || {
+                HashMap::from([((0, 1), vec![Pos2::ZERO])])
+            });
+            let _ = ctx.end_frame();
+            res
+        };
+
+        // the graph shown for the first time is bundled at once
+        assert!(bundle(&g).is_some());
+
+        // moved nodes are drawn straight until the layout does not change for a frame
+        g.node_mut(a).unwrap().set_location(egui::Vec2::new(1., 1.));
+        assert!(bundle(&g).is_none());
+        assert!(bundle(&g).is_some());
+
+        g.node_mut(a).unwrap().set_dragged(true);
+        assert!(bundle(&g).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+use egui::{Pos2, Shape, Stroke};
+
+use crate::{EdgeFlow, StrokePattern};
+
+use super::Layers;
+
+/// Distance between dots of the dotted pattern in multiples of the edge width.
+const DOT_SPACING: f32 = 3.;
+
+/// Distance between flow particles in multiples of the edge width.
+const PARTICLE_SPACING: f32 = 12.;
+
+/// Radius of flow particles in multiples of the edge width.
+const PARTICLE_RADIUS: f32 = 1.5;
+
+/// Shortest period of the pattern in screen pixels. Patterns with shorter periods are not
+/// distinguishable from the solid line and would need too many shapes.
+pub const MIN_PERIOD: f32 = 1.;
+
+/// Largest number of pattern periods along the path, longer paths are drawn solid.
+pub const MAX_PERIODS: f32 = 10_000.;
+
+/// Strokes the path according to the pattern and flow animation.
+///
+/// `speed` is the flow speed in screen pixels per second and `time` is the current time in seconds.
+pub fn add_patterned_path(
+    l: &mut Layers,
+    path: &[Pos2],
+    stroke: Stroke,
+    pattern: &StrokePattern,
+    flow: EdgeFlow,
+    speed: f32,
+    time: f32,
+) {
+    if stroke.width <= 0. {
+        return;
+    }
+
+    let width = stroke.width;
+    let offset = match flow {
+        EdgeFlow::None => 0.,
+        _ => time * speed,
+    };
+    let pattern_offset = match flow {
+        EdgeFlow::MarchingAnts => offset,
+        _ => 0.,
+    };
+
+    match pattern {
+        StrokePattern::Dotted if !is_drawable(path, DOT_SPACING * width) => {
+            l.add(Shape::line(path.to_vec(), stroke));
+        }
+        StrokePattern::Dotted => {
+            points_along(path, DOT_SPACING * width, pattern_offset)
+                .into_iter()
+                .for_each(|p| l.add(Shape::circle_filled(p, width / 2., stroke.color)));
+        }
+        StrokePattern::Solid if flow != EdgeFlow::MarchingAnts => {
+            l.add(Shape::line(path.to_vec(), stroke));
+        }
+        _ => {
+            let dashes = match pattern {
+                StrokePattern::Solid => StrokePattern::Dashed.dashes(),
+                _ => pattern.dashes(),
+            };
+            let dashes = dashes.iter().map(|d| d * width).collect::<Vec<_>>();
+            // dash_polyline returns the whole path as one dash for too short periods
+            dash_polyline(path, &dashes, pattern_offset)
+                .into_iter()
+                .for_each(|dash| l.add(Shape::line(dash, stroke)));
+        }
+    };
+
+    if flow == EdgeFlow::Particles && is_drawable(path, PARTICLE_SPACING * width) {
+        points_along(path, PARTICLE_SPACING * width, offset)
+            .into_iter()
+            .for_each(|p| l.add(Shape::circle_filled(p, PARTICLE_RADIUS * width, stroke.color)));
+    }
+}
+
+/// Whether the pattern with the period can be placed along the path, see [`MIN_PERIOD`] and [`MAX_PERIODS`].
+fn is_drawable(points: &[Pos2], period: f32) -> bool {
+    period >= MIN_PERIOD && polyline_length(points) / period <= MAX_PERIODS
+}
+
+/// Splits polyline into dashes. `dashes` are alternating dash and gap lengths, `offset` shifts
+/// the pattern along the polyline direction. Returns the whole polyline if the pattern period
+/// is too short to be drawn.
+pub fn dash_polyline(points: &[Pos2], dashes: &[f32], offset: f32) -> Vec<Vec<Pos2>> {
+    let period: f32 = dashes.iter().sum();
+    if points.len() < 2 || dashes.len() < 2 || !is_drawable(points, period) {
+        return vec![points.to_vec()];
+    }
+
+    let total = polyline_length(points);
+    let mut res = vec![];
+
+    // the first period may start before the beginning of the polyline
+    let mut start = offset.rem_euclid(period) - period;
+    while start < total {
+        let mut pos = start;
+        dashes.iter().enumerate().for_each(|(i, len)| {
+            let (from, to) = (pos, pos + len);
+            pos = to;
+
+            // odd elements are gaps
+            if i % 2 == 1 {
+                return;
+            }
+
+            let (from, to) = (from.max(0.), to.min(total));
+            if from < to {
+                res.push(sub_polyline(points, from, to));
+            }
+        });
+        start += period;
+    }
+
+    res
+}
+
+/// Returns points placed along the polyline every `spacing` starting from `offset`. Returns no
+/// points if the spacing is too short.
+pub fn points_along(points: &[Pos2], spacing: f32, offset: f32) -> Vec<Pos2> {
+    if points.len() < 2 || !is_drawable(points, spacing) {
+        return vec![];
+    }
+
+    let total = polyline_length(points);
+    let mut res = vec![];
+    let mut dist = offset.rem_euclid(spacing);
+    while dist <= total {
+        res.push(point_at(points, dist));
+        dist += spacing;
+    }
+
+    res
+}
+
+pub fn polyline_length(points: &[Pos2]) -> f32 {
+    points.windows(2).map(|w| w[0].distance(w[1])).sum()
+}
+
+/// Finds the point on the polyline at the given distance from its start.
+pub fn point_at(points: &[Pos2], dist: f32) -> Pos2 {
+    let mut passed = 0.;
+    for w in points.windows(2) {
+        let len = w[0].distance(w[1]);
+        if passed + len >= dist && len > 0. {
+            return w[0].lerp(w[1], (dist - passed) / len);
+        }
+        passed += len;
+    }
+
+    *points.last().unwrap()
+}
+
+/// Cuts the part of the polyline between `from` and `to` distances from its start.
+fn sub_polyline(points: &[Pos2], from: f32, to: f32) -> Vec<Pos2> {
+    let mut res = vec![point_at(points, from)];
+
+    let mut passed = 0.;
+    for w in points.windows(2) {
+        passed += w[0].distance(w[1]);
+        if passed > from && passed < to {
+            res.push(w[1]);
+        }
+    }
+
+    res.push(point_at(points, to));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dash_polyline() {
+        let points = [Pos2::new(0., 0.), Pos2::new(10., 0.)];
+
+        let dashes = dash_polyline(&points, &[2., 3.], 0.);
+
+        assert_eq!(
+            dashes,
+            vec![
+                vec![Pos2::new(0., 0.), Pos2::new(2., 0.)],
+                vec![Pos2::new(5., 0.), Pos2::new(7., 0.)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dash_polyline_offset() {
+        let points = [Pos2::new(0., 0.), Pos2::new(10., 0.)];
+
+        let dashes = dash_polyline(&points, &[2., 3.], 1.);
+
+        assert_eq!(
+            dashes,
+            vec![
+                vec![Pos2::new(1., 0.), Pos2::new(3., 0.)],
+                vec![Pos2::new(6., 0.), Pos2::new(8., 0.)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dash_polyline_corner() {
+        let points = [Pos2::new(0., 0.), Pos2::new(2., 0.), Pos2::new(2., 2.)];
+
+        let dashes = dash_polyline(&points, &[3., 1.], 0.);
+
+        assert_eq!(
+            dashes[0],
+            vec![Pos2::new(0., 0.), Pos2::new(2., 0.), Pos2::new(2., 1.)]
+        );
+    }
+
+    #[test]
+    fn test_points_along() {
+        let points = [Pos2::new(0., 0.), Pos2::new(10., 0.)];
+
+        let res = points_along(&points, 4., 5.);
+
+        assert_eq!(res, vec![Pos2::new(1., 0.), Pos2::new(5., 0.), Pos2::new(9., 0.)]);
+    }
+
+    #[test]
+    fn test_degenerate_patterns() {
+        let points = [Pos2::new(0., 0.), Pos2::new(1e6, 0.)];
+
+        assert_eq!(dash_polyline(&points, &[0., 0.], 0.), vec![points.to_vec()]);
+        assert_eq!(
+            dash_polyline(&points, &[0.1, 0.1], 0.),
+            vec![points.to_vec()]
+        );
+        assert!(points_along(&points, f32::EPSILON, 0.).is_empty());
+
+        let stroke = |width| Stroke::new(width, egui::Color32::WHITE);
+        let draw = |width, pattern: &StrokePattern| {
+            let mut l = Layers::default();
+            add_patterned_path(
+                &mut l,
+                &points,
+                stroke(width),
+                pattern,
+                EdgeFlow::Particles,
+                10.,
+                1.,
+            );
+            l.into_shapes().len()
+        };
+
+        assert_eq!(draw(0., &StrokePattern::Dotted), 0);
+        assert_eq!(draw(0., &StrokePattern::Custom(vec![0., 0.])), 0);
+        assert_eq!(draw(1., &StrokePattern::Custom(vec![0., 0.])), 1);
+        assert_eq!(draw(1e-6, &StrokePattern::Dotted), 1);
+    }
+}
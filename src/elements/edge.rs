@@ -1,38 +1,55 @@
 use egui::{Color32, Context};
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, IndexType};
 use serde::{Deserialize, Serialize};
 
-use super::StyleEdge;
+use super::{EdgeFlow, EdgeMarker, StrokePattern, StyleEdge};
 
 /// Stores properties of an edge that can be changed. Used to apply changes to the graph.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Edge<E: Clone> {
+pub struct Edge<E: Clone, Ix: IndexType = DefaultIx> {
     /// Client data
     pub data: E,
 
+    #[serde(default)]
+    id: EdgeIndex<Ix>,
+
     style: StyleEdge,
 }
 
-impl<E: Clone+Default> Default for Edge<E> {
+impl<E: Clone+Default, Ix: IndexType> Default for Edge<E, Ix> {
     fn default() -> Self {
         Self {
             style: Default::default(),
 
+            id: Default::default(),
             data: Default::default(),
         }
     }
 }
 
-impl<E: Clone> Edge<E> {
+impl<E: Clone, Ix: IndexType> Edge<E, Ix> {
     pub fn new(data: E) -> Self {
         Self {
             data,
+            id: Default::default(),
             style: Default::default()
         }
     }
 
-    pub fn map_data<NE: Clone, F: Fn(E) -> NE>(self, f: F) -> Edge<NE> {
+    /// Returns index of the edge in the [`crate::Graph`]. It is assigned when the edge is added to the graph
+    /// with the crate helpers or when the graph is created and kept up to date while the graph is displayed.
+    pub fn id(&self) -> EdgeIndex<Ix> {
+        self.id
+    }
+
+    pub(crate) fn set_id(&mut self, id: EdgeIndex<Ix>) {
+        self.id = id;
+    }
+
+    pub fn map_data<NE: Clone, F: Fn(E) -> NE>(self, f: F) -> Edge<NE, Ix> {
         Edge {
             data: (f)(self.data),
+            id: self.id,
             style: self.style
         }
     }
@@ -41,10 +58,34 @@ impl<E: Clone> Edge<E> {
         self.style.tip_angle
     }
 
+    /// Returns color of the edge. Uses the color from the edge style if it is set,
+    /// otherwise the color is derived from the egui visuals.
     pub fn color(&self, ctx: &Context) -> Color32 {
-        ctx.style()
-            .visuals
-            .gray_out(ctx.style().visuals.widgets.inactive.fg_stroke.color)
+        let color = match self.style.color {
+            Some(color) => color,
+            None => ctx
+                .style()
+                .visuals
+                .gray_out(ctx.style().visuals.widgets.inactive.fg_stroke.color),
+        };
+
+        match self.style.alpha {
+            Some(alpha) => color.gamma_multiply(alpha.clamp(0., 1.)),
+            None => color,
+        }
+    }
+
+    pub fn style(&self) -> &StyleEdge {
+        &self.style
+    }
+
+    pub fn set_style(&mut self, style: StyleEdge) {
+        self.style = style;
+    }
+
+    pub fn with_style(mut self, style: StyleEdge) -> Self {
+        self.style = style;
+        self
     }
 
     pub fn width(&self) -> f32 {
@@ -61,7 +102,46 @@ impl<E: Clone> Edge<E> {
         self.style.curve_size
     }
 
+    pub fn z_index(&self) -> i32 {
+        self.style.z_index
+    }
+
+    pub fn set_z_index(&mut self, z_index: i32) {
+        self.style.z_index = z_index
+    }
+
     pub fn tip_size(&self) -> f32 {
         self.style.tip_size
     }
+
+    /// Returns markers for the source and target ends of the edge, falling back to the defaults
+    /// for the graph type when they are not set in the style.
+    pub fn markers(&self, directed: bool) -> (EdgeMarker, EdgeMarker) {
+        let default_end = match directed {
+            true => EdgeMarker::Triangle,
+            false => EdgeMarker::None,
+        };
+
+        (
+            self.style.marker_start.unwrap_or_default(),
+            self.style.marker_end.unwrap_or(default_end),
+        )
+    }
+
+    pub fn stroke_pattern(&self) -> &StrokePattern {
+        &self.style.stroke_pattern
+    }
+
+    pub fn flow(&self) -> EdgeFlow {
+        self.style.flow
+    }
+
+    pub fn flow_speed(&self) -> f32 {
+        self.style.flow_speed
+    }
+
+    /// Whether the edge is drawn with a plain solid stroke without animation.
+    pub fn is_plain(&self) -> bool {
+        *self.stroke_pattern() == StrokePattern::Solid && self.flow() == EdgeFlow::None
+    }
 }
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Marker drawn at the end of the edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EdgeMarker {
+    #[default]
+    None,
+    /// Filled triangle. Default end marker for directed graphs.
+    Triangle,
+    /// Arrow drawn with two strokes.
+    OpenArrow,
+    Diamond,
+    Circle,
+    /// Line perpendicular to the edge.
+    Bar,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Pattern used to stroke the edge line. Lengths are given in multiples of the edge width.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum StrokePattern {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+    /// Alternating dash and gap lengths. Odd number of lengths is repeated twice as in SVG `stroke-dasharray`.
+    Custom(Vec<f32>),
+}
+
+impl StrokePattern {
+    /// Returns dash array in multiples of the edge width. Empty for solid and dotted patterns.
+    pub fn dashes(&self) -> Vec<f32> {
+        match self {
+            StrokePattern::Solid | StrokePattern::Dotted => vec![],
+            StrokePattern::Dashed => vec![4., 3.],
+            StrokePattern::Custom(dashes) => {
+                let dashes = dashes.iter().map(|d| d.max(0.)).collect::<Vec<_>>();
+                match dashes.len() % 2 {
+                    0 => dashes,
+                    _ => dashes.repeat(2),
+                }
+            }
+        }
+    }
+}
+
+/// Animation visualizing the flow along the edge direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EdgeFlow {
+    #[default]
+    None,
+    /// Dashes of the stroke pattern move along the edge. Solid edges are drawn dashed.
+    MarchingAnts,
+    /// Particles move along the edge on top of its stroke.
+    Particles,
+}
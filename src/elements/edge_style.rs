@@ -1,11 +1,37 @@
+use egui::Color32;
 use serde::{Deserialize, Serialize};
 
+use super::{EdgeFlow, EdgeMarker, StrokePattern};
+
+/// Style of the edge. Optional colors override the ones derived from the egui visuals.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StyleEdge {
     pub width: f32,
     pub tip_size: f32,
     pub tip_angle: f32,
     pub curve_size: f32,
+    /// Color of the edge and its tip.
+    #[serde(default)]
+    pub color: Option<Color32>,
+    /// Opacity from `0.` to `1.` applied to the edge and its tip.
+    #[serde(default)]
+    pub alpha: Option<f32>,
+    #[serde(default)]
+    pub stroke_pattern: StrokePattern,
+    #[serde(default)]
+    pub flow: EdgeFlow,
+    /// Speed of the flow animation in graph units per second.
+    #[serde(default = "default_flow_speed")]
+    pub flow_speed: f32,
+    /// Marker at the source end of the edge. No marker by default.
+    #[serde(default)]
+    pub marker_start: Option<EdgeMarker>,
+    /// Marker at the target end of the edge. By default it is a triangle for directed graphs and no marker otherwise.
+    #[serde(default)]
+    pub marker_end: Option<EdgeMarker>,
+    /// Edges with higher z-index are drawn on top of the edges with lower one.
+    #[serde(default)]
+    pub z_index: i32,
 }
 
 impl Default for StyleEdge {
@@ -15,6 +41,18 @@ impl Default for StyleEdge {
             tip_size: 15.,
             tip_angle: std::f32::consts::TAU / 30.,
             curve_size: 20.,
+            color: Default::default(),
+            alpha: Default::default(),
+            stroke_pattern: Default::default(),
+            flow: Default::default(),
+            flow_speed: default_flow_speed(),
+            marker_start: Default::default(),
+            marker_end: Default::default(),
+            z_index: Default::default(),
         }
     }
 }
+
+fn default_flow_speed() -> f32 {
+    20.
+}
@@ -1,9 +1,15 @@
 mod edge;
+mod edge_marker;
+mod edge_stroke;
 mod edge_style;
 mod node;
+mod node_shape;
 mod node_style;
 
 pub use self::edge::Edge;
+pub use self::edge_marker::EdgeMarker;
+pub use self::edge_stroke::{EdgeFlow, StrokePattern};
 pub use self::edge_style::StyleEdge;
 pub use self::node::Node;
+pub use self::node_shape::NodeShape;
 pub use self::node_style::StyleNode;
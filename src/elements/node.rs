@@ -1,16 +1,20 @@
-use egui::{Color32, Context, Vec2};
+use egui::{ecolor::tint_color_towards, Color32, Context, Stroke, Vec2};
+use petgraph::stable_graph::{DefaultIx, IndexType, NodeIndex};
 use serde::{Deserialize, Serialize};
 
 use crate::{metadata::Metadata, ComputedNode, SettingsStyle};
 
-use super::StyleNode;
+use super::{NodeShape, StyleNode};
 
 /// Stores properties of a node.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Node<N: Clone> {
+pub struct Node<N: Clone, Ix: IndexType = DefaultIx> {
     /// Client data
     pub data: N,
 
+    #[serde(default)]
+    id: NodeIndex<Ix>,
+
     location: Vec2,
 
     label: String,
@@ -19,22 +23,36 @@ pub struct Node<N: Clone> {
 
     selected: bool,
     dragged: bool,
+    #[serde(default)]
+    pinned: bool,
     computed: ComputedNode,
 }
 
-impl<N: Clone> Node<N> {
+impl<N: Clone, Ix: IndexType> Node<N, Ix> {
     pub fn new(location: Vec2, data: N) -> Self {
         Self {
             location,
             data,
+            id: Default::default(),
             style: Default::default(),
             label: Default::default(),
             selected: Default::default(),
             dragged: Default::default(),
+            pinned: Default::default(),
             computed: Default::default(),
         }
     }
 
+    /// Returns index of the node in the [`crate::Graph`]. It is assigned when the node is added to the graph
+    /// with the crate helpers or when the graph is created and kept up to date while the graph is displayed.
+    pub fn id(&self) -> NodeIndex<Ix> {
+        self.id
+    }
+
+    pub(crate) fn set_id(&mut self, id: NodeIndex<Ix>) {
+        self.id = id;
+    }
+
     /// Returns actual location of the node on the screen. It accounts for the current zoom and pan values.
     pub fn screen_location(&self, m: &Metadata) -> Vec2 {
         self.location * m.zoom + m.pan
@@ -45,10 +63,56 @@ impl<N: Clone> Node<N> {
         (self.radius() + self.num_connections() as f32 * style.edge_radius_weight) * m.zoom
     }
 
+    /// Returns half size of the node on the screen. Half height is equal to [`Node::screen_radius`] and
+    /// half width accounts for the aspect ratio of the node shape.
+    pub fn screen_half_size(&self, m: &Metadata, style: &SettingsStyle) -> Vec2 {
+        self.style
+            .shape
+            .half_size(self.screen_radius(m, style), self.style.aspect_ratio)
+    }
+
+    /// Returns half size of the node in graph coordinates. It accounts for the number of connections
+    /// and the aspect ratio of the node shape.
+    pub fn half_size(&self, style: &SettingsStyle) -> Vec2 {
+        self.style.shape.half_size(
+            self.radius() + self.num_connections() as f32 * style.edge_radius_weight,
+            self.style.aspect_ratio,
+        )
+    }
+
     pub fn radius(&self) -> f32 {
         self.style.radius
     }
 
+    pub fn shape(&self) -> NodeShape {
+        self.style.shape
+    }
+
+    pub fn set_shape(&mut self, shape: NodeShape) {
+        self.style.shape = shape
+    }
+
+    pub fn with_shape(mut self, shape: NodeShape) -> Self {
+        self.style.shape = shape;
+        self
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.style.aspect_ratio
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.style.aspect_ratio = aspect_ratio
+    }
+
+    pub fn z_index(&self) -> i32 {
+        self.style.z_index
+    }
+
+    pub fn set_z_index(&mut self, z_index: i32) {
+        self.style.z_index = z_index
+    }
+
     pub fn num_connections(&self) -> usize {
         self.computed.num_connections
     }
@@ -90,6 +154,21 @@ impl<N: Clone> Node<N> {
         self.dragged
     }
 
+    /// Pinned node keeps its location. Layouts should not move it and it can be locked against dragging
+    /// with [`crate::SettingsInteraction::with_pinned_dragging_locked`].
+    pub fn pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
     pub fn label(&self) -> &str {
         &self.label
     }
@@ -98,12 +177,54 @@ impl<N: Clone> Node<N> {
         self.dragged = dragged;
     }
 
+    pub fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+
     pub fn with_label(mut self, label: String) -> Self {
         self.label = label;
         self
     }
 
+    /// Returns fill color of the node. Uses the color from the node style if it is set,
+    /// otherwise the color is derived from the egui visuals according to the node state.
+    /// Style color of the selected or dragged node is tinted towards the egui highlight color.
     pub fn color(&self, ctx: &Context) -> Color32 {
+        let color = match self.style.fill {
+            Some(fill) => match self.selected || self.dragged {
+                true => tint_color_towards(fill, self.visuals_color(ctx)),
+                false => fill,
+            },
+            None => self.visuals_color(ctx),
+        };
+
+        self.apply_alpha(color)
+    }
+
+    /// Returns stroke of the node outline. Falls back to the fill color and `1.` width.
+    pub fn stroke(&self, ctx: &Context) -> Stroke {
+        let color = match self.style.stroke_color {
+            Some(color) => self.apply_alpha(color),
+            None => self.color(ctx),
+        };
+
+        Stroke::new(self.style.stroke_width.unwrap_or(1.), color)
+    }
+
+    pub fn style(&self) -> &StyleNode {
+        &self.style
+    }
+
+    pub fn set_style(&mut self, style: StyleNode) {
+        self.style = style;
+    }
+
+    pub fn with_style(mut self, style: StyleNode) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn visuals_color(&self, ctx: &Context) -> Color32 {
         if self.dragged {
             return ctx.style().visuals.widgets.active.fg_stroke.color;
         }
@@ -115,8 +236,16 @@ impl<N: Clone> Node<N> {
         ctx.style().visuals.widgets.inactive.fg_stroke.color
     }
 
-    pub fn map_data<NN: Clone, F: Fn(N) -> NN>(self, f: F) -> Node<NN> {
+    fn apply_alpha(&self, color: Color32) -> Color32 {
+        match self.style.alpha {
+            Some(alpha) => color.gamma_multiply(alpha.clamp(0., 1.)),
+            None => color,
+        }
+    }
+
+    pub fn map_data<NN: Clone, F: Fn(N) -> NN>(self, f: F) -> Node<NN, Ix> {
         Node {
+            id: self.id,
             location: self.location,
             data: (f)(self.data),
             label: self.label,
@@ -124,6 +253,60 @@ impl<N: Clone> Node<N> {
             style: self.style,
             selected: self.selected,
             dragged: self.dragged,
+            pinned: self.pinned,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_fallback_to_visuals() {
+        let ctx = Context::default();
+        let n = Node::<_>::new(Vec2::ZERO, ());
+
+        let expected = ctx.style().visuals.widgets.inactive.fg_stroke.color;
+        assert_eq!(n.color(&ctx), expected);
+        assert_eq!(n.stroke(&ctx), Stroke::new(1., expected));
+    }
+
+    #[test]
+    fn test_color_style_override() {
+        let ctx = Context::default();
+        let n = Node::<_>::new(Vec2::ZERO, ()).with_style(StyleNode {
+            fill: Some(Color32::RED),
+            stroke_color: Some(Color32::BLUE),
+            stroke_width: Some(3.),
+            alpha: Some(0.5),
+            ..Default::default()
+        });
+
+        assert_eq!(n.color(&ctx), Color32::RED.gamma_multiply(0.5));
+        assert_eq!(n.stroke(&ctx), Stroke::new(3., Color32::BLUE.gamma_multiply(0.5)));
+    }
+
+    #[test]
+    fn test_color_style_override_highlight() {
+        let ctx = Context::default();
+        let mut n = Node::<_>::new(Vec2::ZERO, ()).with_style(StyleNode {
+            fill: Some(Color32::RED),
+            ..Default::default()
+        });
+
+        n.set_selected(true);
+        let selected = ctx.style().visuals.widgets.hovered.fg_stroke.color;
+        assert_eq!(n.color(&ctx), tint_color_towards(Color32::RED, selected));
+        assert_ne!(n.color(&ctx), Color32::RED);
+        assert_eq!(n.stroke(&ctx).color, n.color(&ctx));
+
+        n.set_dragged(true);
+        let dragged = ctx.style().visuals.widgets.active.fg_stroke.color;
+        assert_eq!(n.color(&ctx), tint_color_towards(Color32::RED, dragged));
+
+        n.set_selected(false);
+        n.set_dragged(false);
+        assert_eq!(n.color(&ctx), Color32::RED);
+    }
+}
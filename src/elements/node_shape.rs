@@ -0,0 +1,282 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use egui::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Number of segments used to approximate curved parts of the shapes.
+const CURVE_SEGMENTS: usize = 32;
+
+/// Number of steps used to find the boundary with bisection.
+const BISECTION_STEPS: usize = 24;
+
+/// Corner radius of [`NodeShape::RoundedRect`] relative to its smaller half side.
+const ROUNDING_SCALE: f32 = 0.4;
+
+/// Shape of the node drawn by [`crate::default_node_draw`].
+///
+/// Every shape is inscribed into the rectangle defined by the half size of the node:
+/// half height is equal to the node radius and half width is the radius multiplied by the aspect ratio.
+/// [`NodeShape::Circle`] ignores the aspect ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NodeShape {
+    #[default]
+    Circle,
+    Rectangle,
+    RoundedRect,
+    Diamond,
+    Triangle,
+    Hexagon,
+    Ellipse,
+}
+
+impl NodeShape {
+    /// Returns half size of the shape bounding rectangle for the given radius and aspect ratio.
+    pub fn half_size(&self, radius: f32, aspect_ratio: f32) -> Vec2 {
+        match self {
+            NodeShape::Circle => Vec2::new(radius, radius),
+            _ => Vec2::new(radius * aspect_ratio, radius),
+        }
+    }
+
+    /// Returns the outline of the shape centered at `center`. All shapes are convex so the outline
+    /// can be used to build a convex polygon. Returns an empty vector for [`NodeShape::Circle`]
+    /// which is drawn with a native circle shape.
+    pub fn outline(&self, center: Pos2, half: Vec2) -> Vec<Pos2> {
+        self.outline_relative(half)
+            .into_iter()
+            .map(|p| center + p)
+            .collect()
+    }
+
+    /// Checks if the point given relative to the shape center is inside the shape.
+    pub fn contains(&self, half: Vec2, p: Vec2) -> bool {
+        match self {
+            NodeShape::Circle => p.length() <= half.x,
+            NodeShape::Ellipse => {
+                (p.x / half.x).powi(2) + (p.y / half.y).powi(2) <= 1.
+            }
+            NodeShape::Rectangle => p.x.abs() <= half.x && p.y.abs() <= half.y,
+            _ => polygon_contains(&self.outline_relative(half), p),
+        }
+    }
+
+    /// Returns the distance from the shape center to its boundary along the `dir` direction.
+    pub fn boundary_distance(&self, half: Vec2, dir: Vec2) -> f32 {
+        let dir = dir.normalized();
+        match self {
+            NodeShape::Circle => half.x,
+            NodeShape::Ellipse => {
+                1. / ((dir.x / half.x).powi(2) + (dir.y / half.y).powi(2)).sqrt()
+            }
+            NodeShape::Rectangle => {
+                let tx = match dir.x == 0. {
+                    true => f32::INFINITY,
+                    false => half.x / dir.x.abs(),
+                };
+                let ty = match dir.y == 0. {
+                    true => f32::INFINITY,
+                    false => half.y / dir.y.abs(),
+                };
+                tx.min(ty)
+            }
+            _ => polygon_ray_distance(&self.outline_relative(half), dir),
+        }
+    }
+
+    /// Returns the distance from the `origin` given relative to the shape center to the shape boundary
+    /// along the `dir` direction. Returns `0.` if the origin is outside of the shape.
+    pub fn boundary_distance_from(&self, half: Vec2, origin: Vec2, dir: Vec2) -> f32 {
+        if origin == Vec2::ZERO {
+            return self.boundary_distance(half, dir);
+        }
+        if !self.contains(half, origin) {
+            return 0.;
+        }
+
+        let dir = dir.normalized();
+        let (mut inside, mut outside) = (0., 2. * half.max_elem() + origin.length());
+        (0..BISECTION_STEPS).for_each(|_| {
+            let middle = (inside + outside) / 2.;
+            match self.contains(half, origin + dir * middle) {
+                true => inside = middle,
+                false => outside = middle,
+            };
+        });
+
+        inside
+    }
+
+    fn outline_relative(&self, half: Vec2) -> Vec<Vec2> {
+        match self {
+            NodeShape::Circle => vec![],
+            NodeShape::Ellipse => (0..CURVE_SEGMENTS)
+                .map(|i| {
+                    let angle = TAU * i as f32 / CURVE_SEGMENTS as f32;
+                    Vec2::new(half.x * angle.cos(), half.y * angle.sin())
+                })
+                .collect(),
+            NodeShape::Rectangle => vec![
+                Vec2::new(-half.x, -half.y),
+                Vec2::new(half.x, -half.y),
+                Vec2::new(half.x, half.y),
+                Vec2::new(-half.x, half.y),
+            ],
+            NodeShape::RoundedRect => {
+                let r = half.x.min(half.y) * ROUNDING_SCALE;
+                let segments = CURVE_SEGMENTS / 4;
+                let corners = [
+                    (Vec2::new(half.x - r, half.y - r), 0.),
+                    (Vec2::new(-half.x + r, half.y - r), FRAC_PI_2),
+                    (Vec2::new(-half.x + r, -half.y + r), PI),
+                    (Vec2::new(half.x - r, -half.y + r), PI + FRAC_PI_2),
+                ];
+                corners
+                    .iter()
+                    .flat_map(|(corner, start)| {
+                        (0..=segments).map(move |i| {
+                            let angle = start + FRAC_PI_2 * i as f32 / segments as f32;
+                            *corner + r * Vec2::angled(angle)
+                        })
+                    })
+                    .collect()
+            }
+            NodeShape::Diamond => vec![
+                Vec2::new(0., -half.y),
+                Vec2::new(half.x, 0.),
+                Vec2::new(0., half.y),
+                Vec2::new(-half.x, 0.),
+            ],
+            NodeShape::Triangle => vec![
+                Vec2::new(0., -half.y),
+                Vec2::new(half.x, half.y),
+                Vec2::new(-half.x, half.y),
+            ],
+            NodeShape::Hexagon => (0..6)
+                .map(|i| {
+                    let angle = TAU * i as f32 / 6.;
+                    Vec2::new(half.x * angle.cos(), half.y * angle.sin())
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Checks if the point is inside the convex polygon given with vertices in any winding order.
+fn polygon_contains(points: &[Vec2], p: Vec2) -> bool {
+    let mut sign = 0.;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let cross = (b - *a).x * (p - *a).y - (b - *a).y * (p - *a).x;
+        if cross == 0. {
+            continue;
+        }
+        if sign == 0. {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Finds the distance from the origin to the convex polygon boundary along the unit `dir` direction.
+/// Origin must be inside the polygon.
+fn polygon_ray_distance(points: &[Vec2], dir: Vec2) -> f32 {
+    let mut closest = f32::INFINITY;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let edge = b - *a;
+        let denom = dir.x * edge.y - dir.y * edge.x;
+        if denom.abs() < f32::EPSILON {
+            continue;
+        }
+
+        // solve t * dir = a + s * edge
+        let t = (a.x * edge.y - a.y * edge.x) / denom;
+        let s = (a.x * dir.y - a.y * dir.x) / denom;
+        if t >= 0. && (-1e-4..=1. + 1e-4).contains(&s) && t < closest {
+            closest = t;
+        }
+    }
+
+    match closest.is_finite() {
+        true => closest,
+        false => 0.,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: f32 = 1e-4;
+
+    #[test]
+    fn test_boundary_distance_circle() {
+        let half = NodeShape::Circle.half_size(5., 2.);
+        let d = NodeShape::Circle.boundary_distance(half, Vec2::new(1., 1.));
+        assert!((d - 5.).abs() < EPS);
+    }
+
+    #[test]
+    fn test_boundary_distance_rectangle() {
+        let half = NodeShape::Rectangle.half_size(5., 2.);
+        assert!((NodeShape::Rectangle.boundary_distance(half, Vec2::X) - 10.).abs() < EPS);
+        assert!((NodeShape::Rectangle.boundary_distance(half, Vec2::Y) - 5.).abs() < EPS);
+    }
+
+    #[test]
+    fn test_boundary_distance_diamond() {
+        let half = Vec2::new(5., 5.);
+        let d = NodeShape::Diamond.boundary_distance(half, Vec2::new(1., 1.));
+        assert!((d - 5. / 2_f32.sqrt()).abs() < EPS);
+        assert!((NodeShape::Diamond.boundary_distance(half, -Vec2::Y) - 5.).abs() < EPS);
+    }
+
+    #[test]
+    fn test_boundary_distance_ellipse() {
+        let half = Vec2::new(10., 5.);
+        assert!((NodeShape::Ellipse.boundary_distance(half, Vec2::X) - 10.).abs() < EPS);
+        assert!((NodeShape::Ellipse.boundary_distance(half, Vec2::Y) - 5.).abs() < EPS);
+    }
+
+    #[test]
+    fn test_boundary_point_is_on_outline() {
+        let half = Vec2::new(8., 5.);
+        let shapes = [
+            NodeShape::RoundedRect,
+            NodeShape::Diamond,
+            NodeShape::Triangle,
+            NodeShape::Hexagon,
+        ];
+        for shape in shapes {
+            let dir = Vec2::new(0.3, -0.7).normalized();
+            let d = shape.boundary_distance(half, dir);
+            assert!(shape.contains(half, dir * (d - 0.01)), "{:?}", shape);
+            assert!(!shape.contains(half, dir * (d + 0.01)), "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn test_boundary_distance_from() {
+        let half = Vec2::new(5., 5.);
+        let d = NodeShape::Circle.boundary_distance_from(half, Vec2::new(0., 3.), Vec2::X);
+        assert!((d - 4.).abs() < 1e-3);
+
+        let d = NodeShape::Rectangle.boundary_distance_from(half, Vec2::new(0., 3.), Vec2::X);
+        assert!((d - 5.).abs() < 1e-3);
+
+        let d = NodeShape::Circle.boundary_distance_from(half, Vec2::new(0., 6.), Vec2::X);
+        assert_eq!(d, 0.);
+    }
+
+    #[test]
+    fn test_contains() {
+        let half = Vec2::new(10., 5.);
+        assert!(NodeShape::Rectangle.contains(half, Vec2::new(9., 4.)));
+        assert!(!NodeShape::Diamond.contains(half, Vec2::new(9., 4.)));
+        assert!(NodeShape::Hexagon.contains(half, Vec2::ZERO));
+        assert!(!NodeShape::Triangle.contains(half, Vec2::new(-9., -4.)));
+    }
+}
@@ -1,12 +1,49 @@
+use egui::Color32;
 use serde::{Serialize, Deserialize};
 
+use super::NodeShape;
+
+/// Style of the node. Optional colors override the ones derived from the egui visuals.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StyleNode {
     pub radius: f32,
+    #[serde(default)]
+    pub shape: NodeShape,
+    /// Ratio of the node width to its height. Ignored by [`NodeShape::Circle`].
+    #[serde(default = "default_aspect_ratio")]
+    pub aspect_ratio: f32,
+    /// Fill color of the node.
+    #[serde(default)]
+    pub fill: Option<Color32>,
+    /// Color of the node outline. Defaults to the fill color.
+    #[serde(default)]
+    pub stroke_color: Option<Color32>,
+    /// Width of the node outline in screen pixels.
+    #[serde(default)]
+    pub stroke_width: Option<f32>,
+    /// Opacity from `0.` to `1.` applied to both fill and outline.
+    #[serde(default)]
+    pub alpha: Option<f32>,
+    /// Nodes with higher z-index are drawn on top of the nodes with lower one.
+    #[serde(default)]
+    pub z_index: i32,
 }
 
 impl Default for StyleNode {
     fn default() -> Self {
-        Self { radius: 5. }
+        Self {
+            radius: 5.,
+            shape: Default::default(),
+            aspect_ratio: default_aspect_ratio(),
+            fill: Default::default(),
+            stroke_color: Default::default(),
+            stroke_width: Default::default(),
+            alpha: Default::default(),
+            z_index: Default::default(),
+        }
     }
 }
+
+fn default_aspect_ratio() -> f32 {
+    1.
+}
@@ -38,6 +38,16 @@ pub struct PayloadNodeDeselect {
     pub id: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PayloadNodePin {
+    pub id: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PayloadNodeUnpin {
+    pub id: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PayloadNodeClick {
     pub id: usize,
@@ -57,6 +67,8 @@ pub enum Event {
     NodeDragEnd(PayloadNodeDragEnd),
     NodeSelect(PayloadNodeSelect),
     NodeDeselect(PayloadNodeDeselect),
+    NodePin(PayloadNodePin),
+    NodeUnpin(PayloadNodeUnpin),
     NodeClick(PayloadNodeClick),
     NodeDoubleClick(PayloadNodeDoubleClick),
 }
@@ -2,5 +2,6 @@ mod event;
 
 pub use self::event::{
     Event, PayloadNodeClick, PayloadNodeDeselect, PayloadNodeDoubleClick, PayloadNodeDragEnd,
-    PayloadNodeDragStart, PayloadNodeMove, PayloadNodeSelect, PayloadPan, PyaloadZoom,
+    PayloadNodeDragStart, PayloadNodeMove, PayloadNodePin, PayloadNodeSelect, PayloadNodeUnpin,
+    PayloadPan, PyaloadZoom,
 };
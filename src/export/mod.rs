@@ -0,0 +1,375 @@
+#[cfg(feature = "png")]
+mod png;
+mod svg;
+
+use egui::{
+    Color32, Context, Id, LayerId, Painter, Pos2, RawInput, Rect, Shape, Vec2, Visuals,
+};
+#[cfg(feature = "png")]
+use egui::{
+    epaint::{FontImage, ImageData},
+    TextureId,
+};
+use petgraph::{
+    stable_graph::{DefaultIx, EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
+
+use crate::{
+    computed::ComputedState,
+    default_edges_draw, default_node_draw,
+    draw::{CanvasDraw, DrawHooks, Drawer, EdgeDrawer, Layers, NodeDrawer, WidgetState},
+    Edge, Graph, Metadata, Node, SettingsStyle,
+};
+
+/// Part of the graph which is exported.
+#[derive(Clone)]
+pub enum ExportArea {
+    /// The part of the graph visible in the widget. `rect` is the widget rectangle from the
+    /// response of the widget and `meta` is its metadata obtained with [`Metadata::get`].
+    Viewport { rect: Rect, meta: Metadata },
+    /// The whole graph scaled with `zoom` and surrounded with `padding` in pixels.
+    Graph { zoom: f32, padding: f32 },
+}
+
+impl Default for ExportArea {
+    fn default() -> Self {
+        Self::Graph {
+            zoom: 1.,
+            padding: 20.,
+        }
+    }
+}
+
+/// Error of the raster export.
+#[cfg(feature = "png")]
+#[derive(Debug)]
+pub enum ExportError {
+    /// The image with the given size can not be created, e.g. the exported area is empty.
+    Size { width: u32, height: u32 },
+    /// The image can not be encoded.
+    Encoding(String),
+}
+
+#[cfg(feature = "png")]
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Size { width, height } => {
+                write!(f, "invalid image size {}x{}", width, height)
+            }
+            ExportError::Encoding(message) => write!(f, "image encoding error: {}", message),
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+impl std::error::Error for ExportError {}
+
+/// Shapes of a single exported frame.
+pub(crate) struct Frame {
+    /// Exported area in screen coordinates.
+    pub rect: Rect,
+    pub background: Option<Color32>,
+    /// All shapes in the drawing order.
+    pub shapes: Vec<Shape>,
+    /// Font atlas rasterized with the frame scale, text shapes refer to it with texel coordinates.
+    #[cfg(feature = "png")]
+    pub font_image: Option<FontImage>,
+}
+
+/// Renders the graph outside of the egui window, e.g. to produce images for reports.
+///
+/// The exporter runs the same drawing pipeline as the [`crate::GraphView`] widget with a headless
+/// egui context, so the result matches the interactive view including custom draw functions.
+/// It does not need a GPU or a running application.
+pub struct GraphExport<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    settings_style: SettingsStyle,
+    area: ExportArea,
+    visuals: Visuals,
+    background: Option<Color32>,
+    g: &'a mut Graph<N, E, Ty, Ix>,
+
+    edge_drawer: Box<dyn EdgeDrawer<N, E, Ty, Ix> + 'a>,
+    node_drawer: Box<dyn NodeDrawer<N, E, Ty, Ix> + 'a>,
+    background_draw: Option<Box<CanvasDraw<'a, N, E, Ty, Ix>>>,
+    foreground_draw: Option<Box<CanvasDraw<'a, N, E, Ty, Ix>>>,
+}
+
+impl<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> GraphExport<'a, N, E, Ty, Ix> {
+    /// Creates a new exporter of the whole graph with the default style and dark visuals.
+    pub fn new(g: &'a mut Graph<N, E, Ty, Ix>) -> Self {
+        let visuals = Visuals::dark();
+        Self {
+            g,
+
+            settings_style: Default::default(),
+            area: Default::default(),
+            background: Some(visuals.panel_fill),
+            visuals,
+
+            node_drawer: Box::new(default_node_draw),
+            edge_drawer: Box::new(default_edges_draw),
+            background_draw: Default::default(),
+            foreground_draw: Default::default(),
+        }
+    }
+
+    /// Modifies default style settings.
+    pub fn with_styles(mut self, settings_style: &SettingsStyle) -> Self {
+        self.settings_style = settings_style.clone();
+        self
+    }
+
+    /// Sets the exported part of the graph.
+    pub fn with_area(mut self, area: ExportArea) -> Self {
+        self.area = area;
+        self
+    }
+
+    /// Sets the visuals used by the draw functions, e.g. for the labels color.
+    /// The background color is reset to the panel color of the visuals.
+    pub fn with_visuals(mut self, visuals: Visuals) -> Self {
+        self.background = Some(visuals.panel_fill);
+        self.visuals = visuals;
+        self
+    }
+
+    /// Sets the background color. `None` leaves the background transparent.
+    pub fn with_background(mut self, background: Option<Color32>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Changes the function that will be called by the drawer for every node.
+    /// The function can be a closure capturing application state.
+    pub fn with_custom_node_draw(
+        self,
+        func: impl Fn(
+                &Context,
+                (NodeIndex<Ix>, &Node<N, Ix>),
+                &WidgetState<N, E, Ty, Ix>,
+                &mut Layers,
+            ) + 'a,
+    ) -> Self {
+        self.with_node_drawer(func)
+    }
+
+    /// Changes the drawer that will be called for every node.
+    pub fn with_node_drawer(mut self, drawer: impl NodeDrawer<N, E, Ty, Ix> + 'a) -> Self {
+        self.node_drawer = Box::new(drawer);
+        self
+    }
+
+    /// Changes the function that will be called by the drawer for every pair of nodes connected with edges.
+    /// The function can be a closure capturing application state.
+    pub fn with_custom_edge_draw(
+        self,
+        func: impl Fn(
+                &Context,
+                (NodeIndex<Ix>, NodeIndex<Ix>),
+                Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+                &WidgetState<N, E, Ty, Ix>,
+                &mut Layers,
+            ) + 'a,
+    ) -> Self {
+        self.with_edge_drawer(func)
+    }
+
+    /// Changes the drawer that will be called for every pair of nodes connected with edges.
+    pub fn with_edge_drawer(mut self, drawer: impl EdgeDrawer<N, E, Ty, Ix> + 'a) -> Self {
+        self.edge_drawer = Box::new(drawer);
+        self
+    }
+
+    /// Sets the function painting application shapes under the graph.
+    /// See [`crate::GraphView::with_custom_background_draw`].
+    pub fn with_custom_background_draw(
+        mut self,
+        func: impl Fn(&Context, &WidgetState<N, E, Ty, Ix>, &mut Layers) + 'a,
+    ) -> Self {
+        self.background_draw = Some(Box::new(func));
+        self
+    }
+
+    /// Sets the function painting application shapes over the graph.
+    /// See [`crate::GraphView::with_custom_foreground_draw`].
+    pub fn with_custom_foreground_draw(
+        mut self,
+        func: impl Fn(&Context, &WidgetState<N, E, Ty, Ix>, &mut Layers) + 'a,
+    ) -> Self {
+        self.foreground_draw = Some(Box::new(func));
+        self
+    }
+
+    /// Renders the graph into the standalone SVG document.
+    pub fn to_svg(&mut self) -> String {
+        svg::frame_to_svg(&self.frame(1.))
+    }
+
+    /// Renders the graph into the PNG image with the software rasterizer. The image size is the size
+    /// of the exported area multiplied by `pixels_per_point`.
+    #[cfg(feature = "png")]
+    pub fn to_png(&mut self, pixels_per_point: f32) -> Result<Vec<u8>, ExportError> {
+        png::frame_to_png(&self.frame(pixels_per_point), pixels_per_point)
+    }
+
+    /// Runs the drawing pipeline with a headless context and collects the shapes.
+    /// Text is laid out for the given scale.
+    pub(crate) fn frame(&mut self, pixels_per_point: f32) -> Frame {
+        let comp = ComputedState::compute(self.g, &self.settings_style);
+        let (rect, meta) = self.area_transform(&comp);
+
+        let ctx = Context::default();
+        ctx.set_visuals(self.visuals.clone());
+        ctx.begin_frame(RawInput {
+            screen_rect: Some(rect),
+            pixels_per_point: Some(pixels_per_point),
+            ..Default::default()
+        });
+
+        let shapes = Drawer::new(
+            Painter::new(ctx.clone(), LayerId::background(), rect),
+            Id::null(),
+            self.g,
+            &self.settings_style,
+            &meta,
+            &comp,
+            DrawHooks {
+                node: self.node_drawer.as_ref(),
+                edge: self.edge_drawer.as_ref(),
+                background: self.background_draw.as_deref(),
+                foreground: self.foreground_draw.as_deref(),
+            },
+        )
+        .into_shapes();
+
+        #[cfg_attr(not(feature = "png"), allow(unused_variables))]
+        let output = ctx.end_frame();
+
+        Frame {
+            rect,
+            background: self.background,
+            shapes,
+            #[cfg(feature = "png")]
+            font_image: output
+                .textures_delta
+                .set
+                .into_iter()
+                .find_map(|(id, delta)| match (id, delta.image, delta.pos) {
+                    (TextureId::Managed(0), ImageData::Font(image), None) => Some(image),
+                    _ => None,
+                }),
+        }
+    }
+
+    /// Returns the exported rectangle in screen coordinates and the metadata mapping the graph into it.
+    fn area_transform(&self, comp: &ComputedState<Ix>) -> (Rect, Metadata) {
+        match &self.area {
+            ExportArea::Viewport { rect, meta } => (*rect, meta.clone()),
+            ExportArea::Graph { zoom, padding } => {
+                let bounds = match self.g.g.node_count() {
+                    0 => Rect::from_min_max(Pos2::ZERO, Pos2::ZERO),
+                    _ => comp.graph_bounds(),
+                };
+                let padding = Vec2::splat(*padding);
+                let size = bounds.size() * *zoom + 2. * padding;
+                let meta = Metadata {
+                    first_frame: false,
+                    zoom: *zoom,
+                    pan: padding - bounds.min.to_vec2() * *zoom,
+                    ..Default::default()
+                };
+
+                (Rect::from_min_size(Pos2::ZERO, size), meta)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{stable_graph::StableGraph, Directed};
+
+    use crate::to_graph;
+
+    use super::*;
+
+    fn graph() -> Graph<(), (), Directed> {
+        let mut g = StableGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        g.add_edge(a, b, ());
+
+        let mut g = to_graph(&g);
+        g.node_mut(a).unwrap().set_location(Vec2::new(0., 0.));
+        g.node_mut(b).unwrap().set_location(Vec2::new(100., 50.));
+        g
+    }
+
+    #[test]
+    fn test_graph_area() {
+        let mut g = graph();
+        let frame = GraphExport::new(&mut g)
+            .with_area(ExportArea::Graph {
+                zoom: 2.,
+                padding: 10.,
+            })
+            .frame(1.);
+
+        let rad = g.node(NodeIndex::new(0)).unwrap().half_size(&Default::default()).x;
+        let expected = Vec2::new(100. + 2. * rad, 50. + 2. * rad) * 2. + Vec2::splat(20.);
+        assert_eq!(frame.rect.min, Pos2::ZERO);
+        assert!((frame.rect.size() - expected).length() < 1e-3);
+        assert!(!frame.shapes.is_empty());
+    }
+
+    #[test]
+    fn test_viewport_area() {
+        let mut g = graph();
+        let rect = Rect::from_min_size(Pos2::new(5., 5.), Vec2::new(300., 200.));
+        let frame = GraphExport::new(&mut g)
+            .with_area(ExportArea::Viewport {
+                rect,
+                meta: Metadata::default(),
+            })
+            .frame(1.);
+
+        assert_eq!(frame.rect, rect);
+    }
+
+    #[test]
+    fn test_custom_draw() {
+        let mut g = graph();
+        let frame = GraphExport::new(&mut g)
+            .with_custom_node_draw(|_, _, _, _| {})
+            .with_custom_edge_draw(|_, _, _, _, _| {})
+            .with_custom_foreground_draw(|_, _, l| {
+                l.add(Shape::circle_filled(Pos2::ZERO, 1., Color32::RED))
+            })
+            .frame(1.);
+
+        assert_eq!(frame.shapes.len(), 1);
+    }
+
+    #[test]
+    fn test_edge_order_is_stable() {
+        let mut g = StableGraph::<(), (), Directed>::new();
+        let idxs = (0..8).map(|_| g.add_node(())).collect::<Vec<_>>();
+        idxs.iter().rev().zip(idxs.iter()).for_each(|(a, b)| {
+            g.add_edge(*a, *b, ());
+        });
+        let mut g = to_graph(&g);
+
+        let drawn = std::cell::RefCell::new(vec![]);
+        GraphExport::new(&mut g)
+            .with_custom_edge_draw(|_, bounds, _, _, _| drawn.borrow_mut().push(bounds))
+            .frame(1.);
+
+        let drawn = drawn.into_inner();
+        let mut sorted = drawn.clone();
+        sorted.sort();
+        assert_eq!(drawn.len(), 8);
+        assert_eq!(drawn, sorted);
+    }
+}
@@ -0,0 +1,342 @@
+use egui::{
+    epaint::{CubicBezierShape, FontImage, PathShape, QuadraticBezierShape, TextShape},
+    Color32, Mesh, Pos2, Shape, Stroke,
+};
+use tiny_skia::{
+    FillRule, FilterQuality, Paint, Path, PathBuilder, Pixmap, PixmapPaint, Transform,
+};
+
+use super::{ExportError, Frame};
+
+/// Rasterizes the exported frame and encodes it into the PNG image.
+pub(super) fn frame_to_png(frame: &Frame, pixels_per_point: f32) -> Result<Vec<u8>, ExportError> {
+    let rect = frame.rect;
+    let width = (rect.width() * pixels_per_point).ceil().max(0.) as u32;
+    let height = (rect.height() * pixels_per_point).ceil().max(0.) as u32;
+    let mut pixmap = Pixmap::new(width, height).ok_or(ExportError::Size { width, height })?;
+
+    if let Some(background) = frame.background {
+        pixmap.fill(color(background));
+    }
+
+    let mut r = Rasterizer {
+        pixmap: &mut pixmap,
+        font_image: frame.font_image.as_ref(),
+        transform: Transform::from_scale(pixels_per_point, pixels_per_point)
+            .pre_translate(-rect.min.x, -rect.min.y),
+    };
+    frame.shapes.iter().for_each(|shape| r.draw_shape(shape));
+
+    pixmap
+        .encode_png()
+        .map_err(|err| ExportError::Encoding(err.to_string()))
+}
+
+struct Rasterizer<'a> {
+    pixmap: &'a mut Pixmap,
+    font_image: Option<&'a FontImage>,
+    /// Maps screen coordinates to the image pixels.
+    transform: Transform,
+}
+
+impl Rasterizer<'_> {
+    fn draw_shape(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Noop | Shape::Callback(_) => {}
+            Shape::Vec(shapes) => shapes.iter().for_each(|shape| self.draw_shape(shape)),
+            Shape::Circle(circle) => {
+                let path = PathBuilder::from_circle(circle.center.x, circle.center.y, circle.radius);
+                self.draw_path(path, circle.fill, circle.stroke);
+            }
+            Shape::LineSegment { points, stroke } => {
+                self.draw_path(polyline(points, false), Color32::TRANSPARENT, *stroke);
+            }
+            Shape::Path(PathShape {
+                points,
+                closed,
+                fill,
+                stroke,
+            }) => {
+                let fill = match closed {
+                    true => *fill,
+                    false => Color32::TRANSPARENT,
+                };
+                self.draw_path(polyline(points, *closed), fill, *stroke);
+            }
+            Shape::Rect(rect) => {
+                let path = tiny_skia::Rect::from_ltrb(
+                    rect.rect.min.x,
+                    rect.rect.min.y,
+                    rect.rect.max.x,
+                    rect.rect.max.y,
+                )
+                .map(PathBuilder::from_rect);
+                self.draw_path(path, rect.fill, rect.stroke);
+            }
+            Shape::Text(text) => self.draw_text(text),
+            Shape::Mesh(mesh) => self.draw_mesh(mesh),
+            Shape::QuadraticBezier(QuadraticBezierShape {
+                points,
+                closed,
+                fill,
+                stroke,
+            }) => {
+                let mut pb = PathBuilder::new();
+                pb.move_to(points[0].x, points[0].y);
+                pb.quad_to(points[1].x, points[1].y, points[2].x, points[2].y);
+                self.draw_curve(pb, *closed, *fill, *stroke);
+            }
+            Shape::CubicBezier(CubicBezierShape {
+                points,
+                closed,
+                fill,
+                stroke,
+            }) => {
+                let mut pb = PathBuilder::new();
+                pb.move_to(points[0].x, points[0].y);
+                pb.cubic_to(
+                    points[1].x,
+                    points[1].y,
+                    points[2].x,
+                    points[2].y,
+                    points[3].x,
+                    points[3].y,
+                );
+                self.draw_curve(pb, *closed, *fill, *stroke);
+            }
+        }
+    }
+
+    fn draw_curve(&mut self, mut pb: PathBuilder, closed: bool, fill: Color32, stroke: Stroke) {
+        let fill = match closed {
+            true => {
+                pb.close();
+                fill
+            }
+            false => Color32::TRANSPARENT,
+        };
+        self.draw_path(pb.finish(), fill, stroke);
+    }
+
+    fn draw_path(&mut self, path: Option<Path>, fill: Color32, stroke: Stroke) {
+        let path = match path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if fill != Color32::TRANSPARENT {
+            let paint = paint(fill);
+            self.pixmap
+                .fill_path(&path, &paint, FillRule::Winding, self.transform, None);
+        }
+
+        if !stroke.is_empty() {
+            let paint = paint(stroke.color);
+            let stroke = tiny_skia::Stroke {
+                width: stroke.width,
+                ..Default::default()
+            };
+            self.pixmap
+                .stroke_path(&path, &paint, &stroke, self.transform, None);
+        }
+    }
+
+    /// Draws every triangle of the untextured mesh filled with the color of its first vertex.
+    fn draw_mesh(&mut self, mesh: &Mesh) {
+        if mesh.texture_id != Default::default() {
+            return;
+        }
+
+        mesh.indices.chunks_exact(3).for_each(|triangle| {
+            let vertices = triangle
+                .iter()
+                .map(|i| mesh.vertices[*i as usize])
+                .collect::<Vec<_>>();
+            let points = vertices.iter().map(|v| v.pos).collect::<Vec<_>>();
+            self.draw_path(polyline(&points, true), vertices[0].color, Stroke::NONE);
+        });
+    }
+
+    /// Draws the glyphs of the text from the font atlas. Every glyph is a rectangle of the row mesh
+    /// with texel coordinates of the atlas region.
+    fn draw_text(&mut self, text: &TextShape) {
+        let font_image = match self.font_image {
+            Some(font_image) => font_image,
+            None => return,
+        };
+
+        let transform = self
+            .transform
+            .pre_concat(Transform::from_rotate_at(
+                text.angle.to_degrees(),
+                text.pos.x,
+                text.pos.y,
+            ))
+            .pre_translate(text.pos.x, text.pos.y);
+
+        text.galley.rows.iter().for_each(|row| {
+            let mesh = &row.visuals.mesh;
+            mesh.indices.chunks_exact(6).for_each(|indices| {
+                // glyphs are added as rectangles, other parts of the row mesh are skipped
+                let first = indices[0];
+                if indices != [first, first + 1, first + 2, first + 2, first + 1, first + 3] {
+                    return;
+                }
+
+                let (min, max) = (mesh.vertices[first as usize], mesh.vertices[first as usize + 3]);
+                let color = text.override_text_color.unwrap_or(min.color);
+                let glyph = match glyph_pixmap(font_image, min.uv, max.uv, color) {
+                    Some(glyph) => glyph,
+                    None => return,
+                };
+
+                let scale_x = (max.pos.x - min.pos.x) / glyph.width() as f32;
+                let scale_y = (max.pos.y - min.pos.y) / glyph.height() as f32;
+                let paint = PixmapPaint {
+                    quality: FilterQuality::Bilinear,
+                    ..Default::default()
+                };
+                self.pixmap.draw_pixmap(
+                    0,
+                    0,
+                    glyph.as_ref(),
+                    &paint,
+                    transform
+                        .pre_translate(min.pos.x, min.pos.y)
+                        .pre_scale(scale_x, scale_y),
+                    None,
+                );
+            });
+        });
+    }
+}
+
+/// Creates the pixmap of the font atlas region between the texel coordinates colored with the text color.
+fn glyph_pixmap(font_image: &FontImage, min: Pos2, max: Pos2, color: Color32) -> Option<Pixmap> {
+    let (x0, y0) = (min.x.floor() as usize, min.y.floor() as usize);
+    let (x1, y1) = (
+        (max.x.ceil() as usize).min(font_image.width()),
+        (max.y.ceil() as usize).min(font_image.height()),
+    );
+    let mut pixmap = Pixmap::new(x1.checked_sub(x0)? as u32, y1.checked_sub(y0)? as u32)?;
+
+    let pixels = (y0..y1).flat_map(|y| (x0..x1).map(move |x| (x, y)));
+    pixmap
+        .data_mut()
+        .chunks_exact_mut(4)
+        .zip(pixels)
+        .for_each(|(pixel, (x, y))| {
+            let coverage = font_image.pixels[y * font_image.width() + x];
+            pixel.copy_from_slice(&color.to_array().map(|c| (c as f32 * coverage).round() as u8));
+        });
+
+    Some(pixmap)
+}
+
+fn polyline(points: &[Pos2], closed: bool) -> Option<Path> {
+    let (first, rest) = points.split_first()?;
+    let mut pb = PathBuilder::new();
+    pb.move_to(first.x, first.y);
+    rest.iter().for_each(|p| pb.line_to(p.x, p.y));
+    if closed {
+        pb.close();
+    }
+    pb.finish()
+}
+
+fn color(color: Color32) -> tiny_skia::Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    tiny_skia::Color::from_rgba8(r, g, b, a)
+}
+
+fn paint(c: Color32) -> Paint<'static> {
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    paint.set_color(color(c));
+    paint
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Pos2, Rect, Vec2};
+    use petgraph::stable_graph::StableGraph;
+
+    use crate::{to_graph, ExportArea, GraphExport, SettingsStyle};
+
+    use super::*;
+
+    fn decode(png: &[u8]) -> Pixmap {
+        Pixmap::decode_png(png).unwrap()
+    }
+
+    fn pixel(pixmap: &Pixmap, x: u32, y: u32) -> [u8; 4] {
+        let p = pixmap.pixel(x, y).unwrap().demultiply();
+        [p.red(), p.green(), p.blue(), p.alpha()]
+    }
+
+    #[test]
+    fn test_to_png() {
+        let mut g = StableGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        g.add_edge(a, b, ());
+        let mut g = to_graph(&g);
+        g.node_mut(a).unwrap().set_location(Vec2::new(20., 20.));
+        g.node_mut(b).unwrap().set_location(Vec2::new(80., 20.));
+
+        let png = GraphExport::new(&mut g)
+            .with_styles(&SettingsStyle::default().with_labels_always(true))
+            .with_area(ExportArea::Viewport {
+                rect: Rect::from_min_size(Pos2::ZERO, Vec2::new(100., 40.)),
+                meta: Default::default(),
+            })
+            .with_background(Some(Color32::BLACK))
+            .to_png(2.)
+            .unwrap();
+
+        let pixmap = decode(&png);
+        assert_eq!((pixmap.width(), pixmap.height()), (200, 80));
+        assert_eq!(pixel(&pixmap, 0, 0), [0, 0, 0, 255]);
+
+        // node centers are filled with the node color
+        let node = Color32::from_gray(180).to_array();
+        assert_eq!(pixel(&pixmap, 40, 40), node);
+        assert_eq!(pixel(&pixmap, 160, 40), node);
+
+        // labels are drawn above the nodes
+        let label = (0..80)
+            .flat_map(|x| (0..26).map(move |y| (x, y)))
+            .any(|(x, y)| pixel(&pixmap, x, y) != [0, 0, 0, 255]);
+        assert!(label);
+    }
+
+    #[test]
+    fn test_transparent_background() {
+        let mut g = to_graph::<(), (), _, _>(&StableGraph::new());
+
+        let png = GraphExport::new(&mut g)
+            .with_background(None)
+            .to_png(1.)
+            .unwrap();
+
+        let pixmap = decode(&png);
+        assert_eq!((pixmap.width(), pixmap.height()), (40, 40));
+        assert_eq!(pixel(&pixmap, 20, 20)[3], 0);
+    }
+
+    #[test]
+    fn test_empty_area() {
+        let mut g = to_graph::<(), (), _, _>(&StableGraph::new());
+
+        let res = GraphExport::new(&mut g)
+            .with_area(ExportArea::Graph {
+                zoom: 1.,
+                padding: 0.,
+            })
+            .to_png(1.);
+
+        assert!(matches!(res, Err(ExportError::Size { width: 0, height: 0 })));
+    }
+}
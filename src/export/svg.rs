@@ -0,0 +1,332 @@
+use std::fmt::Write;
+
+use egui::{
+    epaint::{CubicBezierShape, PathShape, QuadraticBezierShape, RectShape, TextShape},
+    Color32, FontFamily, Mesh, Pos2, Shape, Stroke,
+};
+
+use crate::io::format_hex_color;
+
+use super::Frame;
+
+/// Converts the exported frame into the SVG document. Coordinates of the document are the screen
+/// coordinates of the frame.
+pub(super) fn frame_to_svg(frame: &Frame) -> String {
+    let rect = frame.rect;
+    let mut svg = String::new();
+    svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">",
+        num(rect.width()),
+        num(rect.height()),
+        num(rect.min.x),
+        num(rect.min.y),
+        num(rect.width()),
+        num(rect.height()),
+    );
+
+    if let Some(background) = frame.background {
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}/>",
+            num(rect.min.x),
+            num(rect.min.y),
+            num(rect.width()),
+            num(rect.height()),
+            paint("fill", background),
+        );
+    }
+
+    frame.shapes.iter().for_each(|shape| write_shape(&mut svg, shape));
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &Shape) {
+    let _ = match shape {
+        Shape::Noop | Shape::Callback(_) => Ok(()),
+        Shape::Vec(shapes) => {
+            shapes.iter().for_each(|shape| write_shape(svg, shape));
+            Ok(())
+        }
+        Shape::Circle(circle) => writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"{}{}/>",
+            num(circle.center.x),
+            num(circle.center.y),
+            num(circle.radius),
+            paint("fill", circle.fill),
+            stroke(circle.stroke),
+        ),
+        Shape::LineSegment { points, stroke: s } => writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"{}/>",
+            num(points[0].x),
+            num(points[0].y),
+            num(points[1].x),
+            num(points[1].y),
+            stroke(*s),
+        ),
+        Shape::Path(path) => write_path(svg, path),
+        Shape::Rect(rect) => write_rect(svg, rect),
+        Shape::Text(text) => write_text(svg, text),
+        Shape::Mesh(mesh) => write_mesh(svg, mesh),
+        Shape::QuadraticBezier(QuadraticBezierShape {
+            points,
+            closed,
+            fill,
+            stroke: s,
+        }) => write_curve(svg, 'Q', points, *closed, *fill, *s),
+        Shape::CubicBezier(CubicBezierShape {
+            points,
+            closed,
+            fill,
+            stroke: s,
+        }) => write_curve(svg, 'C', points, *closed, *fill, *s),
+    };
+}
+
+fn write_path(svg: &mut String, path: &PathShape) -> std::fmt::Result {
+    if path.points.len() < 2 {
+        return Ok(());
+    }
+
+    let (element, fill) = match path.closed {
+        true => ("polygon", paint("fill", path.fill)),
+        false => ("polyline", " fill=\"none\"".to_string()),
+    };
+    writeln!(
+        svg,
+        "<{} points=\"{}\"{}{}/>",
+        element,
+        points(&path.points),
+        fill,
+        stroke(path.stroke),
+    )
+}
+
+fn write_rect(svg: &mut String, rect: &RectShape) -> std::fmt::Result {
+    writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"{}{}/>",
+        num(rect.rect.min.x),
+        num(rect.rect.min.y),
+        num(rect.rect.width()),
+        num(rect.rect.height()),
+        num(rect.rounding.nw),
+        paint("fill", rect.fill),
+        stroke(rect.stroke),
+    )
+}
+
+fn write_curve(
+    svg: &mut String,
+    command: char,
+    curve: &[Pos2],
+    closed: bool,
+    fill: Color32,
+    s: Stroke,
+) -> std::fmt::Result {
+    let fill = match closed {
+        true => paint("fill", fill),
+        false => " fill=\"none\"".to_string(),
+    };
+    writeln!(
+        svg,
+        "<path d=\"M {} {} {} {}{}\"{}{}/>",
+        num(curve[0].x),
+        num(curve[0].y),
+        command,
+        points(&curve[1..]),
+        match closed {
+            true => " Z",
+            false => "",
+        },
+        fill,
+        stroke(s),
+    )
+}
+
+/// Writes every row of the galley as a separate text element placed at the row baseline.
+fn write_text(svg: &mut String, text: &TextShape) -> std::fmt::Result {
+    let transform = match text.angle == 0. {
+        true => String::new(),
+        false => format!(
+            " transform=\"rotate({} {} {})\"",
+            num(text.angle.to_degrees()),
+            num(text.pos.x),
+            num(text.pos.y)
+        ),
+    };
+
+    for row in text.galley.rows.iter() {
+        let first = match row.glyphs.first() {
+            Some(first) => first,
+            None => continue,
+        };
+        let format = &text.galley.job.sections[first.section_index as usize].format;
+        let color = text.override_text_color.unwrap_or(format.color);
+        let family = match format.font_id.family {
+            FontFamily::Monospace => "monospace",
+            _ => "sans-serif",
+        };
+        let content = row.glyphs.iter().map(|g| g.chr).collect::<String>();
+
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\"{}{} xml:space=\"preserve\">{}</text>",
+            num(text.pos.x + first.pos.x),
+            num(text.pos.y + first.pos.y),
+            family,
+            num(format.font_id.size),
+            paint("fill", color),
+            transform,
+            escape(&content),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes every triangle of the mesh filled with the color of its first vertex.
+fn write_mesh(svg: &mut String, mesh: &Mesh) -> std::fmt::Result {
+    for triangle in mesh.indices.chunks_exact(3) {
+        let vertices = triangle
+            .iter()
+            .map(|i| mesh.vertices[*i as usize])
+            .collect::<Vec<_>>();
+        writeln!(
+            svg,
+            "<polygon points=\"{}\"{}/>",
+            points(&vertices.iter().map(|v| v.pos).collect::<Vec<_>>()),
+            paint("fill", vertices[0].color),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Formats the paint attribute with the opacity for translucent colors.
+fn paint(attribute: &str, color: Color32) -> String {
+    let [_, _, _, a] = color.to_srgba_unmultiplied();
+    match a {
+        0 => format!(" {}=\"none\"", attribute),
+        255 => format!(" {}=\"{}\"", attribute, format_hex_color(color)),
+        _ => format!(
+            " {}=\"{}\" {}-opacity=\"{}\"",
+            attribute,
+            format_hex_color(color.to_opaque()),
+            attribute,
+            num(a as f32 / 255.)
+        ),
+    }
+}
+
+fn stroke(stroke: Stroke) -> String {
+    match stroke.is_empty() {
+        true => String::new(),
+        false => format!("{} stroke-width=\"{}\"", paint("stroke", stroke.color), num(stroke.width)),
+    }
+}
+
+fn points(points: &[Pos2]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", num(p.x), num(p.y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats the number with at most two decimal places.
+fn num(value: f32) -> String {
+    let rounded = (value * 100.).round() / 100.;
+    match rounded == 0. {
+        true => "0".to_string(),
+        false => rounded.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Rect, Vec2};
+    use petgraph::stable_graph::StableGraph;
+
+    use crate::{to_graph, ExportArea, GraphExport, SettingsStyle};
+
+    use super::*;
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(1.), "1");
+        assert_eq!(num(1.234), "1.23");
+        assert_eq!(num(-0.001), "0");
+    }
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(paint("fill", Color32::RED), " fill=\"#ff0000\"");
+        assert_eq!(paint("fill", Color32::TRANSPARENT), " fill=\"none\"");
+        assert_eq!(
+            paint("stroke", Color32::from_rgba_unmultiplied(0, 0, 255, 51)),
+            " stroke=\"#0000ff\" stroke-opacity=\"0.2\""
+        );
+    }
+
+    #[test]
+    fn test_shapes() {
+        let mut svg = String::new();
+        write_shape(
+            &mut svg,
+            &Shape::Vec(vec![
+                Shape::circle_filled(Pos2::new(1., 2.), 3., Color32::WHITE),
+                Shape::line_segment([Pos2::ZERO, Pos2::new(4., 4.)], Stroke::new(1., Color32::BLACK)),
+                Shape::line(vec![Pos2::ZERO, Pos2::new(1., 0.), Pos2::new(1., 1.)], Stroke::new(2., Color32::BLACK)),
+                Shape::Noop,
+            ]),
+        );
+
+        assert_eq!(
+            svg,
+            "<circle cx=\"1\" cy=\"2\" r=\"3\" fill=\"#ffffff\"/>\n\
+             <line x1=\"0\" y1=\"0\" x2=\"4\" y2=\"4\" stroke=\"#000000\" stroke-width=\"1\"/>\n\
+             <polyline points=\"0,0 1,0 1,1\" fill=\"none\" stroke=\"#000000\" stroke-width=\"2\"/>\n"
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let mut g = StableGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        g.add_edge(a, b, ());
+        g.add_edge(b, b, ());
+        let mut g = to_graph(&g);
+        g.node_mut(a).unwrap().set_location(Vec2::new(0., 0.));
+        g.node_mut(b).unwrap().set_location(Vec2::new(100., 0.));
+        g.node_mut(a).unwrap().set_label("a<b".to_string());
+
+        let svg = GraphExport::new(&mut g)
+            .with_styles(&SettingsStyle::default().with_labels_always(true))
+            .with_area(ExportArea::Viewport {
+                rect: Rect::from_min_size(Pos2::ZERO, Vec2::new(200., 100.)),
+                meta: Default::default(),
+            })
+            .with_background(None)
+            .to_svg();
+
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("viewBox=\"0 0 200 100\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(">a&lt;b</text>"));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert!(!svg.contains("<rect"));
+    }
+}
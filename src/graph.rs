@@ -1,61 +1,124 @@
+use std::collections::HashMap;
+
 use petgraph::{
-    stable_graph::{EdgeIndex, EdgeReference, NodeIndex, StableGraph},
+    stable_graph::{DefaultIx, EdgeIndex, EdgeReference, IndexType, NodeIndex, StableGraph},
     visit::{EdgeRef, IntoEdgeReferences, IntoNodeReferences},
     Direction, EdgeType,
 };
 
-use crate::{transform, Edge, Node};
+use crate::{transform, ComputedNode, Edge, Node};
 
 /// Graph type compatible with [`super::GraphView`].
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct Graph<N: Clone, E: Clone, Ty: EdgeType> {
-    pub g: StableGraph<Node<N>, Edge<E>, Ty>,
+// edge type markers are not serializable, the graph is serializable for any edge type
+#[serde(bound(
+    serialize = "N: serde::Serialize, E: serde::Serialize, Ix: serde::Serialize",
+    deserialize = "N: serde::Deserialize<'de>, E: serde::Deserialize<'de>, \
+                   Ix: serde::Deserialize<'de>"
+))]
+pub struct Graph<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    /// Underlying graph. It is modified only with the methods of [`Graph`] and through [`Graph::g`],
+    /// so node and edge ids and node connections stay in sync with the graph.
+    pub(crate) g: StableGraph<Node<N, Ix>, Edge<E, Ix>, Ty, Ix>,
+    /// Whether the graph was borrowed mutably with [`Graph::g`] and ids can be outdated.
+    #[serde(skip, default = "ids_outdated")]
+    ids_outdated: bool,
+}
+
+/// Ids are not trusted after deserialization, since they are missing in older documents.
+fn ids_outdated() -> bool {
+    true
 }
 
-impl<N: Clone, E: Clone, Ty: EdgeType> From<&StableGraph<N, E, Ty>> for Graph<N, E, Ty> {
-    fn from(value: &StableGraph<N, E, Ty>) -> Self {
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> From<&StableGraph<N, E, Ty, Ix>>
+    for Graph<N, E, Ty, Ix>
+{
+    fn from(value: &StableGraph<N, E, Ty, Ix>) -> Self {
         transform::to_graph(value)
     }
 }
 
-impl<'a, N: Clone, E: Clone, Ty: EdgeType> Graph<N, E, Ty> {
-    pub fn new(g: StableGraph<Node<N>, Edge<E>, Ty>) -> Self {
-        Self { g }
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Graph<N, E, Ty, Ix> {
+    pub fn new(g: StableGraph<Node<N, Ix>, Edge<E, Ix>, Ty, Ix>) -> Self {
+        let mut g = Self {
+            g,
+            ids_outdated: false,
+        };
+        g.sync_ids();
+        g
     }
 
+    /// Stores actual indices in the nodes and edges if the graph was borrowed mutably with [`Graph::g`]
+    /// since the last sync.
+    pub(crate) fn sync_outdated_ids(&mut self) {
+        if self.ids_outdated {
+            self.sync_ids();
+            self.ids_outdated = false;
+        }
+    }
 
-    pub fn g(&mut self) -> &mut StableGraph<Node<N>, Edge<E>, Ty> {
+    /// Stores actual indices in all nodes and edges.
+    fn sync_ids(&mut self) {
+        self.g
+            .node_indices()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .for_each(|idx| self.g.node_weight_mut(idx).unwrap().set_id(idx));
+        self.g
+            .edge_indices()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .for_each(|idx| self.g.edge_weight_mut(idx).unwrap().set_id(idx));
+    }
+
+    /// Recomputes the number of connections of the node which is used for the node size,
+    /// so the node is drawn correctly before the next frame computes the state of the graph.
+    fn sync_connections(&mut self, idx: NodeIndex<Ix>) {
+        let num_connections = self.edges_num(idx);
+        if let Some(n) = self.g.node_weight_mut(idx) {
+            n.set_computed(ComputedNode { num_connections });
+        }
+    }
+
+    /// Provides mutable access to the underlying graph. Prefer the mutation methods of [`Graph`],
+    /// changes made directly are synced only when the graph is displayed next time.
+    pub fn g(&mut self) -> &mut StableGraph<Node<N, Ix>, Edge<E, Ix>, Ty, Ix> {
+        self.ids_outdated = true;
         &mut self.g
     }
 
+    /// Provides read only access to the underlying graph, e.g. to run petgraph algorithms on it.
+    pub fn stable_graph(&self) -> &StableGraph<Node<N, Ix>, Edge<E, Ix>, Ty, Ix> {
+        &self.g
+    }
+
     ///Provides iterator over all nodes and their indices.
-    pub fn nodes_iter(&'a self) -> impl Iterator<Item = (NodeIndex, &Node<N>)> {
+    pub fn nodes_iter(&self) -> impl Iterator<Item = (NodeIndex<Ix>, &Node<N, Ix>)> {
         self.g.node_references()
     }
 
-
     /// Provides iterator over all edges and their indices.
-    pub fn edges_iter(&'a self) -> impl Iterator<Item = (EdgeIndex, &Edge<E>)> {
+    pub fn edges_iter(&self) -> impl Iterator<Item = (EdgeIndex<Ix>, &Edge<E, Ix>)> {
         self.g.edge_references().map(|e| (e.id(), e.weight()))
     }
 
-    pub fn node(&self, i: NodeIndex) -> Option<&Node<N>> {
+    pub fn node(&self, i: NodeIndex<Ix>) -> Option<&Node<N, Ix>> {
         self.g.node_weight(i)
     }
 
-    pub fn edge(&self, i: EdgeIndex) -> Option<&Edge<E>> {
+    pub fn edge(&self, i: EdgeIndex<Ix>) -> Option<&Edge<E, Ix>> {
         self.g.edge_weight(i)
     }
 
-    pub fn edge_endpoints(&self, i: EdgeIndex) -> Option<(NodeIndex, NodeIndex)> {
+    pub fn edge_endpoints(&self, i: EdgeIndex<Ix>) -> Option<(NodeIndex<Ix>, NodeIndex<Ix>)> {
         self.g.edge_endpoints(i)
     }
 
-    pub fn node_mut(&mut self, i: NodeIndex) -> Option<&mut Node<N>> {
+    pub fn node_mut(&mut self, i: NodeIndex<Ix>) -> Option<&mut Node<N, Ix>> {
         self.g.node_weight_mut(i)
     }
 
-    pub fn edge_mut(&mut self, i: EdgeIndex) -> Option<&mut Edge<E>> {
+    pub fn edge_mut(&mut self, i: EdgeIndex<Ix>) -> Option<&mut Edge<E, Ix>> {
         self.g.edge_weight_mut(i)
     }
 
@@ -63,15 +126,285 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> Graph<N, E, Ty> {
         self.g.is_directed()
     }
 
-    pub fn edges_num(&self, idx: NodeIndex) -> usize {
+    pub fn edges_num(&self, idx: NodeIndex<Ix>) -> usize {
         self.g.edges(idx).count()
     }
 
+    /// Provides iterator over all edges going from `start` to `end`. For undirected graphs
+    /// edges in both directions are returned.
+    pub fn edges_connecting(
+        &self,
+        start: NodeIndex<Ix>,
+        end: NodeIndex<Ix>,
+    ) -> impl Iterator<Item = EdgeReference<'_, Edge<E, Ix>, Ix>> {
+        self.g.edges_connecting(start, end)
+    }
+
     pub fn edges_directed(
         &self,
-        idx: NodeIndex,
+        idx: NodeIndex<Ix>,
         dir: Direction,
-    ) -> impl Iterator<Item = EdgeReference<Edge<E>>> {
+    ) -> impl Iterator<Item = EdgeReference<'_, Edge<E, Ix>, Ix>> {
         self.g.edges_directed(idx, dir)
     }
+
+    pub fn node_count(&self) -> usize {
+        self.g.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.g.edge_count()
+    }
+
+    /// Adds the node to the graph and returns its index.
+    pub fn add_node(&mut self, node: Node<N, Ix>) -> NodeIndex<Ix> {
+        let idx = self.g.add_node(node);
+        self.g.node_weight_mut(idx).unwrap().set_id(idx);
+        self.sync_connections(idx);
+        idx
+    }
+
+    /// Adds the edge between `start` and `end` and returns its index. Number of connections
+    /// of both nodes is updated.
+    ///
+    /// Panics if any of the nodes does not exist.
+    pub fn add_edge(
+        &mut self,
+        start: NodeIndex<Ix>,
+        end: NodeIndex<Ix>,
+        edge: Edge<E, Ix>,
+    ) -> EdgeIndex<Ix> {
+        let idx = self.g.add_edge(start, end, edge);
+        self.g.edge_weight_mut(idx).unwrap().set_id(idx);
+        self.sync_connections(start);
+        self.sync_connections(end);
+        idx
+    }
+
+    /// Removes the node together with all its edges and returns it. Returns `None` if the node
+    /// does not exist.
+    ///
+    /// Indices of other nodes and edges stay the same, the index of the removed node can be
+    /// reused by the next added node. Selection and dragging state is stored in the nodes,
+    /// so it is removed together with the node.
+    pub fn remove_node(&mut self, idx: NodeIndex<Ix>) -> Option<Node<N, Ix>> {
+        let neighbors = self.g.neighbors_undirected(idx).collect::<Vec<_>>();
+        let node = self.g.remove_node(idx)?;
+        neighbors
+            .into_iter()
+            .for_each(|neighbor| self.sync_connections(neighbor));
+        Some(node)
+    }
+
+    /// Removes the edge and returns it. Returns `None` if the edge does not exist.
+    pub fn remove_edge(&mut self, idx: EdgeIndex<Ix>) -> Option<Edge<E, Ix>> {
+        let (start, end) = self.g.edge_endpoints(idx)?;
+        let edge = self.g.remove_edge(idx)?;
+        self.sync_connections(start);
+        self.sync_connections(end);
+        Some(edge)
+    }
+
+    /// Removes all edges going from `start` to `end` and returns them. For undirected graphs
+    /// edges in both directions are removed.
+    pub fn remove_edges_between(
+        &mut self,
+        start: NodeIndex<Ix>,
+        end: NodeIndex<Ix>,
+    ) -> Vec<Edge<E, Ix>> {
+        let idxs = self
+            .edges_connecting(start, end)
+            .map(|e| e.id())
+            .collect::<Vec<_>>();
+        idxs.into_iter()
+            .filter_map(|idx| self.remove_edge(idx))
+            .collect()
+    }
+
+    /// Keeps only the nodes for which `f` returns `true`. Edges of the removed nodes are removed too.
+    pub fn retain_nodes(&mut self, mut f: impl FnMut(NodeIndex<Ix>, &Node<N, Ix>) -> bool) {
+        let removed = self
+            .nodes_iter()
+            .filter(|(idx, n)| !f(*idx, n))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        removed.into_iter().for_each(|idx| {
+            self.remove_node(idx);
+        });
+    }
+
+    /// Keeps only the edges for which `f` returns `true`.
+    pub fn retain_edges(&mut self, mut f: impl FnMut(EdgeIndex<Ix>, &Edge<E, Ix>) -> bool) {
+        let removed = self
+            .edges_iter()
+            .filter(|(idx, e)| !f(*idx, e))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        removed.into_iter().for_each(|idx| {
+            self.remove_edge(idx);
+        });
+    }
+
+    /// Removes vacant indices left by removed nodes and edges. Nodes and edges keep their order,
+    /// the returned [`IndexRemap`] maps their old indices to the new ones.
+    ///
+    /// Indices no longer match the graph the [`Graph`] was converted from, so the remap has to be
+    /// applied to the indices stored outside of the graph.
+    pub fn compact(&mut self) -> IndexRemap<Ix> {
+        let mut remap = IndexRemap::default();
+        let mut g = StableGraph::with_capacity(self.g.node_count(), self.g.edge_count());
+
+        self.g.node_indices().for_each(|idx| {
+            let new_idx = g.add_node(self.g[idx].clone());
+            remap.nodes.insert(idx, new_idx);
+        });
+        self.g.edge_references().for_each(|e| {
+            let new_idx = g.add_edge(
+                remap.nodes[&e.source()],
+                remap.nodes[&e.target()],
+                e.weight().clone(),
+            );
+            remap.edges.insert(e.id(), new_idx);
+        });
+
+        self.g = g;
+        self.sync_ids();
+        remap
+    }
+}
+
+/// Old and new indices of nodes and edges changed by [`Graph::compact`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexRemap<Ix: IndexType = DefaultIx> {
+    pub nodes: HashMap<NodeIndex<Ix>, NodeIndex<Ix>>,
+    pub edges: HashMap<EdgeIndex<Ix>, EdgeIndex<Ix>>,
+}
+
+impl<Ix: IndexType> Default for IndexRemap<Ix> {
+    fn default() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Vec2;
+    use petgraph::{Directed, Undirected};
+
+    use super::*;
+
+    fn graph<Ty: EdgeType>() -> (Graph<&'static str, u8, Ty>, [NodeIndex; 3]) {
+        let mut g = Graph::new(StableGraph::default());
+        let idxs = ["a", "b", "c"].map(|data| g.add_node(Node::new(Vec2::ZERO, data)));
+        g.add_edge(idxs[0], idxs[1], Edge::new(1));
+        g.add_edge(idxs[1], idxs[0], Edge::new(2));
+        g.add_edge(idxs[1], idxs[2], Edge::new(3));
+        (g, idxs)
+    }
+
+    #[test]
+    fn test_add() {
+        let (g, [a, b, c]) = graph::<Directed>();
+
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.node(c).unwrap().id(), c);
+        g.edges_iter().for_each(|(idx, e)| assert_eq!(e.id(), idx));
+
+        // number of connections is updated without computing the state of the graph
+        assert_eq!(g.node(a).unwrap().num_connections(), 1);
+        assert_eq!(g.node(b).unwrap().num_connections(), 2);
+    }
+
+    #[test]
+    fn test_ids_outdated_by_direct_access() {
+        let (mut g, [a, ..]) = graph::<Directed>();
+        assert!(!g.ids_outdated);
+
+        g.g().remove_node(a);
+        let d = g.g().add_node(Node::new(Vec2::ZERO, "d"));
+        assert_eq!(d, a);
+        assert!(g.ids_outdated);
+
+        g.node_mut(d).unwrap().set_id(NodeIndex::new(7));
+        g.sync_outdated_ids();
+        assert_eq!(g.node(d).unwrap().id(), d);
+        assert!(!g.ids_outdated);
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let (mut g, [a, b, c]) = graph::<Undirected>();
+        g.node_mut(b).unwrap().set_selected(true);
+
+        let removed = g.remove_node(b).unwrap();
+        assert!(removed.selected());
+        assert!(g.remove_node(b).is_none());
+        assert_eq!(g.edge_count(), 0);
+        assert_eq!(g.node(a).unwrap().num_connections(), 0);
+        assert_eq!(g.node(c).unwrap().num_connections(), 0);
+        assert!(g.nodes_iter().all(|(_, n)| !n.selected()));
+
+        // indices of other nodes stay the same
+        assert_eq!(g.node(c).unwrap().data, "c");
+    }
+
+    #[test]
+    fn test_remove_edges() {
+        let (mut g, [a, b, c]) = graph::<Directed>();
+
+        let removed = g.remove_edges_between(a, b);
+        assert_eq!(removed.iter().map(|e| e.data).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(g.edge_count(), 2);
+        assert_eq!(g.node(a).unwrap().num_connections(), 0);
+
+        let e = g.edges_connecting(b, c).next().unwrap().id();
+        assert_eq!(g.remove_edge(e).unwrap().data, 3);
+        assert!(g.remove_edge(e).is_none());
+        assert_eq!(g.node(b).unwrap().num_connections(), 1);
+
+        let (mut g, [a, b, _]) = graph::<Undirected>();
+        assert_eq!(g.remove_edges_between(b, a).len(), 2);
+        assert_eq!(g.edge_count(), 1);
+    }
+
+    #[test]
+    fn test_retain() {
+        let (mut g, [a, b, c]) = graph::<Directed>();
+
+        g.retain_edges(|_, e| e.data != 2);
+        assert_eq!(g.edge_count(), 2);
+        assert_eq!(g.node(b).unwrap().num_connections(), 1);
+
+        g.retain_nodes(|_, n| n.data != "a");
+        assert!(g.node(a).is_none());
+        assert_eq!(g.node_count(), 2);
+        assert_eq!(g.edge_count(), 1);
+        assert!(g.edges_connecting(b, c).next().is_some());
+    }
+
+    #[test]
+    fn test_compact() {
+        let (mut g, [a, b, c]) = graph::<Directed>();
+        let e = g.edges_connecting(b, c).next().unwrap().id();
+        g.remove_node(a);
+
+        let remap = g.compact();
+        assert_eq!(remap.nodes[&b], NodeIndex::new(0));
+        assert_eq!(remap.nodes[&c], NodeIndex::new(1));
+        assert_eq!(remap.edges[&e], EdgeIndex::new(0));
+
+        let new_c = remap.nodes[&c];
+        assert_eq!(g.node(new_c).unwrap().data, "c");
+        assert_eq!(g.node(new_c).unwrap().id(), new_c);
+        assert_eq!(g.edge(EdgeIndex::new(0)).unwrap().id(), EdgeIndex::new(0));
+        assert_eq!(g.g.node_indices().next_back(), Some(NodeIndex::new(1)));
+        assert_eq!(
+            g.edge_endpoints(EdgeIndex::new(0)),
+            Some((remap.nodes[&b], new_c))
+        );
+    }
 }
@@ -1,23 +1,32 @@
 #[cfg(feature = "events")]
 use crate::events::{
     Event, PayloadNodeClick, PayloadNodeDeselect, PayloadNodeDoubleClick, PayloadNodeDragEnd,
-    PayloadNodeDragStart, PayloadNodeMove, PayloadNodeSelect, PayloadPan, PyaloadZoom,
+    PayloadNodeDragStart, PayloadNodeMove, PayloadNodePin, PayloadNodeSelect, PayloadNodeUnpin,
+    PayloadPan, PyaloadZoom,
 };
+#[cfg(feature = "egui_persistence")]
+use crate::metadata::PersistedGraphState;
+#[cfg(feature = "egui_persistence")]
+use std::collections::HashMap;
 use crate::{
+    alignment::{align_to_nodes, snap_to_grid},
     computed::ComputedState,
-    draw::{Drawer, FnEdgeDraw, FnNodeDraw},
+    draw::{
+        default_node_detect, CanvasDraw, DrawHooks, Drawer, EdgeDrawer, Layers, NodeDetector,
+        NodeDrawer, WidgetState,
+    },
     metadata::Metadata,
     settings::SettingsNavigation,
     settings::{SettingsInteraction, SettingsStyle},
-    Graph, Node, default_node_draw, default_edges_draw,
+    Edge, Graph, Node, default_node_draw, default_edges_draw,
 };
 #[cfg(feature = "events")]
 use crossbeam::channel::Sender;
-use egui::{Pos2, Rect, Response, Sense, Ui, Vec2, Widget};
-use petgraph::{stable_graph::NodeIndex, EdgeType};
-
-pub type FnNodeDetect<N> =
-    fn(&Metadata, &Node<N>, Vec2, &SettingsStyle) -> bool;
+use egui::{Context, Id, Pos2, Rect, Response, Sense, Ui, Vec2, Widget};
+use petgraph::{
+    stable_graph::{DefaultIx, EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
 
 /// Widget for visualizing and interacting with graphs.
 ///
@@ -34,28 +43,38 @@ pub type FnNodeDetect<N> =
 /// When the user performs navigation actions (zoom & pan or fit to screen), they do not
 /// produce changes. This is because these actions are performed on the global coordinates and do not change any
 /// properties of the nodes or edges.
-pub struct GraphView<'a, N: Clone, E: Clone, Ty: EdgeType> {
+pub struct GraphView<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
     settings_interaction: SettingsInteraction,
     settings_navigation: SettingsNavigation,
     settings_style: SettingsStyle,
-    g: &'a mut Graph<N, E, Ty>,
+    g: &'a mut Graph<N, E, Ty, Ix>,
+    id: Id,
 
-    edge_draw_fn: FnEdgeDraw<N, E, Ty>,
-    node_draw_fn: FnNodeDraw<N, E, Ty>,
-    node_detect_fn: FnNodeDetect<N>,
+    edge_drawer: Box<dyn EdgeDrawer<N, E, Ty, Ix> + 'a>,
+    node_drawer: Box<dyn NodeDrawer<N, E, Ty, Ix> + 'a>,
+    node_detector: Box<dyn NodeDetector<N, Ix> + 'a>,
+    background_draw: Option<Box<CanvasDraw<'a, N, E, Ty, Ix>>>,
+    foreground_draw: Option<Box<CanvasDraw<'a, N, E, Ty, Ix>>>,
 
     #[cfg(feature = "events")]
     events_publisher: Option<&'a Sender<Event>>,
+
+    #[cfg(feature = "egui_persistence")]
+    persisted_positions: bool,
 }
 
-impl<'a, N: Clone, E: Clone, Ty: EdgeType> Widget for &mut GraphView<'a, N, E, Ty> {
+impl<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Widget
+    for &mut GraphView<'a, N, E, Ty, Ix>
+{
     fn ui(self, ui: &mut Ui) -> Response {
         let (resp, p) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
 
-        let mut meta = Metadata::get(ui);
+        let mut meta = Metadata::get_with_id(ui, self.id);
+        #[cfg(feature = "egui_persistence")]
+        self.restore_graph_state(&mut meta);
         let mut computed = self.compute_state();
 
-        self.handle_fit_to_screen(&resp, &mut meta, &computed);
+        self.handle_fit_to_screen(ui, &resp, &mut meta, &computed);
         self.handle_navigation(ui, &resp, &mut meta, &computed);
 
         self.handle_node_drag(&resp, &mut computed, &mut meta);
@@ -63,56 +82,131 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> Widget for &mut GraphView<'a, N, E, T
 
         Drawer::new(
             p,
+            self.id,
             self.g,
             &self.settings_style,
             &meta,
-            self.node_draw_fn,
-            self.edge_draw_fn,
+            &computed,
+            DrawHooks {
+                node: self.node_drawer.as_ref(),
+                edge: self.edge_drawer.as_ref(),
+                background: self.background_draw.as_deref(),
+                foreground: self.foreground_draw.as_deref(),
+            },
         )
         .draw();
 
-        meta.store_into_ui(ui);
+        #[cfg(feature = "egui_persistence")]
+        self.store_graph_state(&mut meta);
+        meta.store_into_ui_with_id(ui, self.id);
         ui.ctx().request_repaint();
 
         resp
     }
 }
 
-impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
+impl<'a, N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> GraphView<'a, N, E, Ty, Ix> {
     /// Creates a new `GraphView` widget with default navigation and interactions settings.
     /// To customize navigation and interactions use `with_interactions` and `with_navigations` methods.
-    pub fn new(g: &'a mut Graph<N, E, Ty>) -> Self {
+    pub fn new(g: &'a mut Graph<N, E, Ty, Ix>) -> Self {
         Self {
             g,
+            id: Id::null(),
 
             settings_style: Default::default(),
             settings_interaction: Default::default(),
             settings_navigation: Default::default(),
 
-            node_draw_fn: default_node_draw,
-            edge_draw_fn: default_edges_draw,
-            node_detect_fn: default_node_detect,
+            node_drawer: Box::new(default_node_draw),
+            edge_drawer: Box::new(default_edges_draw),
+            node_detector: Box::new(default_node_detect),
+            background_draw: Default::default(),
+            foreground_draw: Default::default(),
 
             #[cfg(feature = "events")]
             events_publisher: Default::default(),
+
+            #[cfg(feature = "egui_persistence")]
+            persisted_positions: Default::default(),
         }
     }
 
-    /// Changes the fucntion that will be called by the drawer for every node.
-    pub fn with_custom_node_draw(mut self, func: FnNodeDraw<N, E, Ty>) -> Self {
-        self.node_draw_fn = func;
+    /// Changes the function that will be called by the drawer for every node.
+    /// The function can be a closure capturing application state.
+    pub fn with_custom_node_draw(
+        self,
+        func: impl Fn(
+                &Context,
+                (NodeIndex<Ix>, &Node<N, Ix>),
+                &WidgetState<N, E, Ty, Ix>,
+                &mut Layers,
+            ) + 'a,
+    ) -> Self {
+        self.with_node_drawer(func)
+    }
+
+    /// Changes the drawer that will be called for every node.
+    pub fn with_node_drawer(mut self, drawer: impl NodeDrawer<N, E, Ty, Ix> + 'a) -> Self {
+        self.node_drawer = Box::new(drawer);
         self
     }
 
     /// Changes the function that will be called by the drawer for every pair of nodes connected with edges.
-    pub fn with_custom_edge_draw(mut self, func: FnEdgeDraw<N, E, Ty>) -> Self {
-        self.edge_draw_fn = func;
+    /// The function can be a closure capturing application state.
+    pub fn with_custom_edge_draw(
+        self,
+        func: impl Fn(
+                &Context,
+                (NodeIndex<Ix>, NodeIndex<Ix>),
+                Vec<(EdgeIndex<Ix>, &Edge<E, Ix>)>,
+                &WidgetState<N, E, Ty, Ix>,
+                &mut Layers,
+            ) + 'a,
+    ) -> Self {
+        self.with_edge_drawer(func)
+    }
+
+    /// Changes the drawer that will be called for every pair of nodes connected with edges.
+    /// Clicks on self loops are checked with [`EdgeDrawer::loops_contain`] of the drawer.
+    pub fn with_edge_drawer(mut self, drawer: impl EdgeDrawer<N, E, Ty, Ix> + 'a) -> Self {
+        self.edge_drawer = Box::new(drawer);
         self
     }
 
     /// Changes the function that will be called for detecting whether a given node has been clicked.
-    pub fn with_custom_node_detect(mut self, func: FnNodeDetect<N>) -> Self {
-        self.node_detect_fn = func;
+    /// The function can be a closure capturing application state.
+    pub fn with_custom_node_detect(
+        self,
+        func: impl Fn(&Metadata, &Node<N, Ix>, Vec2, &SettingsStyle) -> bool + 'a,
+    ) -> Self {
+        self.with_node_detector(func)
+    }
+
+    /// Changes the detector that will be called for detecting whether a given node has been clicked.
+    pub fn with_node_detector(mut self, detector: impl NodeDetector<N, Ix> + 'a) -> Self {
+        self.node_detector = Box::new(detector);
+        self
+    }
+
+    /// Sets the function painting application shapes under the graph, e.g. a map or a floor plan.
+    /// Shapes added with [`Layers::add`] go to the [`crate::Layer::Background`] layer.
+    /// Use [`Metadata::graph_to_screen`] to place shapes in graph coordinates.
+    pub fn with_custom_background_draw(
+        mut self,
+        func: impl Fn(&Context, &WidgetState<N, E, Ty, Ix>, &mut Layers) + 'a,
+    ) -> Self {
+        self.background_draw = Some(Box::new(func));
+        self
+    }
+
+    /// Sets the function painting application shapes over the graph, e.g. annotations or a legend.
+    /// Shapes added with [`Layers::add`] go to the [`crate::Layer::Foreground`] layer.
+    /// Use [`Metadata::graph_to_screen`] to place shapes in graph coordinates.
+    pub fn with_custom_foreground_draw(
+        mut self,
+        func: impl Fn(&Context, &WidgetState<N, E, Ty, Ix>, &mut Layers) + 'a,
+    ) -> Self {
+        self.foreground_draw = Some(Box::new(func));
         self
     }
 
@@ -134,9 +228,45 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self
     }
 
-    /// Resets navigation metadata
+    /// Sets the id the widget metadata is stored with. Every widget shown at the same time needs
+    /// its own id, e.g. created with [`Ui::make_persistent_id`]. Use [`Metadata::get_with_id`] to
+    /// access metadata of the widget. Default: [`Id::null`].
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Whether node positions are persisted together with the camera and selection, so the layout
+    /// survives application restarts. Positions are restored for the nodes with the same indices.
+    ///
+    /// Default: `false`
+    #[cfg(feature = "egui_persistence")]
+    pub fn with_persisted_positions(mut self, persisted: bool) -> Self {
+        self.persisted_positions = persisted;
+        self
+    }
+
+    /// Resets navigation metadata of the widget with the default id.
     pub fn reset_metadata(ui: &mut Ui) {
-        Metadata::default().store_into_ui(ui);
+        Self::reset_metadata_with_id(ui, Id::null());
+    }
+
+    /// Resets navigation metadata of the widget with the given id, see [`GraphView::with_id`].
+    pub fn reset_metadata_with_id(ui: &mut Ui, id: Id) {
+        Metadata::default().store_into_ui_with_id(ui, id);
+    }
+
+    /// Requests the graph to be fitted to the screen on the next frame regardless of the [`FitToScreen`] mode.
+    /// Applies to the widget with the default id.
+    pub fn request_fit_to_screen(ui: &mut Ui) {
+        Self::request_fit_to_screen_with_id(ui, Id::null());
+    }
+
+    /// The same as [`GraphView::request_fit_to_screen`] for the widget with the given id.
+    pub fn request_fit_to_screen_with_id(ui: &mut Ui, id: Id) {
+        let mut meta = Metadata::get_with_id(ui, id);
+        meta.fit_requested = true;
+        meta.store_into_ui_with_id(ui, id);
     }
 
     #[cfg(feature = "events")]
@@ -145,41 +275,99 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self
     }
 
-    fn compute_state(&mut self) -> ComputedState {
-        let mut computed = ComputedState::default();
+    /// Applies the selection and node positions loaded from the storage to the graph.
+    #[cfg(feature = "egui_persistence")]
+    fn restore_graph_state(&mut self, meta: &mut Metadata) {
+        if !meta.restore_pending {
+            return;
+        }
+        meta.restore_pending = false;
+
+        let state = match &meta.graph_state {
+            Some(state) => state,
+            None => return,
+        };
 
+        let positions = state.positions.iter().copied().collect::<HashMap<_, _>>();
         let n_idxs = self.g.g.node_indices().collect::<Vec<_>>();
-        n_idxs.iter().for_each(|idx| {
-            let comp = computed.compute_for_node(self.g, *idx);
+        n_idxs.into_iter().for_each(|idx| {
+            let n = self.g.node_mut(idx).unwrap();
+            n.set_selected(state.selected.contains(&idx.index()));
+            if let Some(loc) = positions.get(&idx.index()) {
+                n.set_location(*loc);
+            }
+        });
+    }
 
-            let n = self.g.node_mut(*idx).unwrap();
-            n.set_computed(comp);
+    /// Stores the selection and node positions which are persisted with the metadata.
+    #[cfg(feature = "egui_persistence")]
+    fn store_graph_state(&self, meta: &mut Metadata) {
+        let selected = self
+            .g
+            .nodes_iter()
+            .filter(|(_, n)| n.selected())
+            .map(|(idx, _)| idx.index())
+            .collect();
+        let positions = match self.persisted_positions {
+            true => self
+                .g
+                .nodes_iter()
+                .map(|(idx, n)| (idx.index(), n.location()))
+                .collect(),
+            false => Vec::new(),
+        };
 
-            computed.comp_iter_bounds(n, &self.settings_style);
+        meta.graph_state = Some(PersistedGraphState {
+            selected,
+            positions,
         });
+    }
 
-        computed
+    fn compute_state(&mut self) -> ComputedState<Ix> {
+        ComputedState::compute(self.g, &self.settings_style)
     }
 
-    /// Fits the graph to the screen if it is the first frame or
-    /// fit to screen setting is enabled;
-    fn handle_fit_to_screen(&self, r: &Response, meta: &mut Metadata, comp: &ComputedState) {
-        if !meta.first_frame && !self.settings_navigation.fit_to_screen_enabled {
-            return;
+    /// Fits the graph to the screen if it is the first frame, fit is requested or
+    /// fit to screen mode requires it, and moves the animated fit towards its target.
+    fn handle_fit_to_screen(
+        &self,
+        ui: &Ui,
+        r: &Response,
+        meta: &mut Metadata,
+        comp: &ComputedState<Ix>,
+    ) {
+        let dragging = comp.dragged.is_some();
+        let camera = meta
+            .fit_to_screen(
+                &self.settings_navigation,
+                &r.rect,
+                comp.graph_bounds(),
+                dragging,
+            )
+            .or_else(|| meta.animate_fit_to_screen(ui.input(|i| i.stable_dt)));
+
+        if let Some((zoom, pan)) = camera {
+            self.set_zoom(zoom, meta);
+            self.set_pan(pan, meta);
         }
-
-        self.fit_to_screen(&r.rect, meta, comp);
-        meta.first_frame = false;
     }
 
-    fn handle_click(&mut self, resp: &Response, meta: &mut Metadata, comp: &ComputedState) {
+    fn handle_click(&mut self, resp: &Response, meta: &mut Metadata, comp: &ComputedState<Ix>) {
         if !resp.clicked() && !resp.double_clicked() {
             return;
         }
 
+        if resp.double_clicked()
+            && self.settings_navigation.fit_to_screen_on_double_click
+            && self.node_by_screen_pos(meta, resp.hover_pos().unwrap()).is_none()
+        {
+            meta.fit_requested = true;
+        }
+
         let clickable = self.settings_interaction.clicking_enabled
             || self.settings_interaction.selection_enabled
-            || self.settings_interaction.selection_multi_enabled;
+            || self.settings_interaction.selection_multi_enabled
+            || self.settings_interaction.pinning_enabled;
 
         if !(clickable) {
             return;
@@ -209,17 +397,20 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self.handle_node_click(node_idx, comp);
     }
 
-    fn handle_node_double_click(&mut self, idx: NodeIndex) {
+    fn handle_node_double_click(&mut self, idx: NodeIndex<Ix>) {
+        if self.settings_interaction.pinning_enabled {
+            let pinned = self.g.node(idx).unwrap().pinned();
+            self.set_node_pinned(idx, !pinned);
+        }
+
         if !self.settings_interaction.clicking_enabled {
             return;
         }
 
-        if self.settings_interaction.clicking_enabled {
-            self.set_node_double_clicked(idx);
-        }
+        self.set_node_double_clicked(idx);
     }
 
-    fn handle_node_click(&mut self, idx: NodeIndex, comp: &ComputedState) {
+    fn handle_node_click(&mut self, idx: NodeIndex<Ix>, comp: &ComputedState<Ix>) {
         if !self.settings_interaction.clicking_enabled
             && !self.settings_interaction.selection_enabled
         {
@@ -247,81 +438,121 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self.select_node(idx);
     }
 
-    fn handle_node_drag(&mut self, resp: &Response, comp: &mut ComputedState, meta: &mut Metadata) {
+    fn handle_node_drag(
+        &mut self,
+        resp: &Response,
+        comp: &mut ComputedState<Ix>,
+        meta: &mut Metadata,
+    ) {
         if !self.settings_interaction.dragging_enabled {
             return;
         }
 
         if resp.drag_started() {
-            if let Some((idx, _)) =
+            if let Some((idx, n)) =
                 self
                     .node_by_screen_pos(meta, resp.hover_pos().unwrap())
             {
-                self.set_drag_start(idx);
+                let locked = n.pinned() && self.settings_interaction.pinned_dragging_locked;
+                if !locked {
+                    meta.drag_location = Some(n.location());
+                    self.set_drag_start(idx);
+                }
             }
         }
 
-        if resp.dragged()
-            && comp.dragged.is_some()
-            && (resp.drag_delta().x.abs() > 0. || resp.drag_delta().y.abs() > 0.)
-        {
-            let n_idx_dragged = comp.dragged.unwrap();
-            let delta_in_graph_coords = resp.drag_delta() / meta.zoom;
-            self.move_node(n_idx_dragged, delta_in_graph_coords);
-        }
+        if let Some(n_idx_dragged) = comp.dragged {
+            if resp.dragged()
+                && (resp.drag_delta().x.abs() > 0. || resp.drag_delta().y.abs() > 0.)
+            {
+                let delta_in_graph_coords = match self.snapping_enabled() {
+                    true => self.snapped_drag_delta(resp, n_idx_dragged, meta),
+                    false => resp.drag_delta() / meta.zoom,
+                };
+                if delta_in_graph_coords != Vec2::ZERO {
+                    self.move_node(n_idx_dragged, delta_in_graph_coords);
+                }
+            }
 
-        if resp.drag_released() && comp.dragged.is_some() {
-            let n_idx = comp.dragged.unwrap();
-            self.set_drag_end(n_idx);
+            if self.settings_interaction.alignment_guides_enabled && !resp.drag_released() {
+                let loc = self.g.node(n_idx_dragged).unwrap().location();
+                let (_, guides) = align_to_nodes(
+                    loc,
+                    self.other_node_locations(n_idx_dragged),
+                    f32::EPSILON,
+                );
+                comp.alignment_guides = guides;
+            }
+
+            if resp.drag_released() {
+                meta.drag_location = None;
+                self.set_drag_end(n_idx_dragged);
+                if self.settings_interaction.pinning_enabled {
+                    self.set_node_pinned(n_idx_dragged, true);
+                }
+            }
         }
     }
 
-    /// Finds node by position. Can be optimized by using a spatial index like quad-tree if needed.
-    pub fn node_by_screen_pos(
-        &self,
-        meta: &'a Metadata,
-        screen_pos: Pos2,
-    ) -> Option<(NodeIndex, &Node<N>)> {
-        let pos_in_graph = (screen_pos.to_vec2() - meta.pan) / meta.zoom;
-        self.g.nodes_iter().find(|(_, n)| {
-            (self.node_detect_fn)(meta, n, pos_in_graph, &self.settings_style)
-        })
+    fn snapping_enabled(&self) -> bool {
+        self.settings_interaction.snap_to_grid_enabled
+            || self.settings_interaction.alignment_guides_enabled
     }
 
-    fn fit_to_screen(&self, rect: &Rect, meta: &mut Metadata, comp: &ComputedState) {
-        // calculate graph dimensions with decorative padding
-        let bounds = comp.graph_bounds();
-        let mut diag = bounds.max - bounds.min;
-
-        // if the graph is empty or consists from one node, use a default size
-        if diag == Vec2::ZERO {
-            diag = Vec2::new(1., 100.);
+    /// Computes the delta which moves dragged node with the pointer, snapped to the grid
+    /// and aligned with other nodes according to the interaction settings. The location before
+    /// snapping is accumulated in the metadata, so the node keeps its offset from the pointer.
+    fn snapped_drag_delta(
+        &self,
+        resp: &Response,
+        idx: NodeIndex<Ix>,
+        meta: &mut Metadata,
+    ) -> Vec2 {
+        let location = self.g.node(idx).unwrap().location();
+        let free = meta.drag_location.unwrap_or(location) + resp.drag_delta() / meta.zoom;
+        meta.drag_location = Some(free);
+
+        let mut target = free;
+        if self.settings_interaction.snap_to_grid_enabled {
+            target = snap_to_grid(target, self.settings_style.grid_spacing);
+        }
+        if self.settings_interaction.alignment_guides_enabled {
+            let threshold = self.settings_interaction.alignment_guides_threshold / meta.zoom;
+            (target, _) = align_to_nodes(target, self.other_node_locations(idx), threshold);
         }
 
-        let graph_size = diag * (1. + self.settings_navigation.screen_padding);
-        let (width, height) = (graph_size.x, graph_size.y);
-
-        // calculate canvas dimensions
-        let canvas_size = rect.size();
-        let (canvas_width, canvas_height) = (canvas_size.x, canvas_size.y);
-
-        // calculate zoom factors for x and y to fit the graph inside the canvas
-        let zoom_x = canvas_width / width;
-        let zoom_y = canvas_height / height;
-
-        // choose the minimum of the two zoom factors to avoid distortion
-        let new_zoom = zoom_x.min(zoom_y);
-
-        // calculate the zoom delta and call handle_zoom to adjust the zoom factor
-        let zoom_delta = new_zoom / meta.zoom - 1.0;
-        self.zoom(rect, zoom_delta, None, meta);
+        target - location
+    }
 
-        // calculate the center of the graph and the canvas
-        let graph_center = (bounds.min.to_vec2() + bounds.max.to_vec2()) / 2.0;
+    fn other_node_locations(&self, idx: NodeIndex<Ix>) -> impl Iterator<Item = Vec2> + '_ {
+        self.g
+            .nodes_iter()
+            .filter(move |(other_idx, _)| *other_idx != idx)
+            .map(|(_, n)| n.location())
+    }
 
-        // adjust the pan value to align the centers of the graph and the canvas
-        let new_pan = rect.center().to_vec2() - graph_center * new_zoom;
-        self.set_pan(new_pan, meta);
+    /// Finds node by position. Looped edges are considered a part of their node.
+    /// Can be optimized by using a spatial index like quad-tree if needed.
+    pub fn node_by_screen_pos(
+        &self,
+        meta: &'a Metadata,
+        screen_pos: Pos2,
+    ) -> Option<(NodeIndex<Ix>, &Node<N, Ix>)> {
+        let pos_in_graph = (screen_pos.to_vec2() - meta.pan) / meta.zoom;
+        self.g
+            .nodes_iter()
+            .find(|(_, n)| self.node_detector.detect(meta, n, pos_in_graph, &self.settings_style))
+            .or_else(|| {
+                self.g.nodes_iter().find(|(idx, _)| {
+                    self.edge_drawer.loops_contain(
+                        self.g,
+                        &self.settings_style,
+                        meta,
+                        *idx,
+                        screen_pos,
+                    )
+                })
+            })
     }
 
     fn handle_navigation(
@@ -329,7 +560,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         ui: &Ui,
         resp: &Response,
         meta: &mut Metadata,
-        comp: &ComputedState,
+        comp: &ComputedState<Ix>,
     ) {
         self.handle_zoom(ui, resp, meta);
         self.handle_pan(resp, meta, comp);
@@ -347,11 +578,12 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
             }
 
             let step = self.settings_navigation.zoom_speed * (delta-1.).signum();
+            meta.fit_target = None;
             self.zoom(&resp.rect, step, i.pointer.hover_pos(), meta);
         });
     }
 
-    fn handle_pan(&self, resp: &Response, meta: &mut Metadata, comp: &ComputedState) {
+    fn handle_pan(&self, resp: &Response, meta: &mut Metadata, comp: &ComputedState<Ix>) {
         if !self.settings_navigation.zoom_and_pan_enabled {
             return;
         }
@@ -361,6 +593,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
             && (resp.drag_delta().x.abs() > 0. || resp.drag_delta().y.abs() > 0.)
         {
             let new_pan = meta.pan + resp.drag_delta();
+            meta.fit_target = None;
             self.set_pan(new_pan, meta);
         }
     }
@@ -382,7 +615,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self.set_zoom(new_zoom, meta);
     }
 
-    fn select_node(&mut self, idx: NodeIndex) {
+    fn select_node(&mut self, idx: NodeIndex<Ix>) {
         let n = self.g.node_mut(idx).unwrap();
         n.set_selected(true);
 
@@ -390,7 +623,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self.publish_event(Event::NodeSelect(PayloadNodeSelect { id: idx.index() }));
     }
 
-    fn deselect_node(&mut self, idx: NodeIndex) {
+    fn deselect_node(&mut self, idx: NodeIndex<Ix>) {
         let n = self.g.node_mut(idx).unwrap();
         n.set_selected(false);
 
@@ -398,25 +631,41 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         self.publish_event(Event::NodeDeselect(PayloadNodeDeselect { id: idx.index() }));
     }
 
-    fn set_node_clicked(&mut self, idx: NodeIndex) {
+    fn set_node_pinned(&mut self, idx: NodeIndex<Ix>, pinned: bool) {
+        let n = self.g.node_mut(idx).unwrap();
+        if n.pinned() == pinned {
+            return;
+        }
+        n.set_pinned(pinned);
+
+        #[cfg(feature = "events")]
+        match pinned {
+            true => self.publish_event(Event::NodePin(PayloadNodePin { id: idx.index() })),
+            false => self.publish_event(Event::NodeUnpin(PayloadNodeUnpin { id: idx.index() })),
+        };
+    }
+
+    #[allow(unused_variables)]
+    fn set_node_clicked(&mut self, idx: NodeIndex<Ix>) {
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeClick(PayloadNodeClick { id: idx.index() }));
     }
 
-    fn set_node_double_clicked(&mut self, idx: NodeIndex) {
+    #[allow(unused_variables)]
+    fn set_node_double_clicked(&mut self, idx: NodeIndex<Ix>) {
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeDoubleClick(PayloadNodeDoubleClick {
             id: idx.index(),
         }));
     }
 
-    fn deselect_all(&mut self, comp: &ComputedState) {
+    fn deselect_all(&mut self, comp: &ComputedState<Ix>) {
         comp.selected.iter().for_each(|idx| {
             self.deselect_node(*idx);
         });
     }
 
-    fn move_node(&mut self, idx: NodeIndex, delta: Vec2) {
+    fn move_node(&mut self, idx: NodeIndex<Ix>, delta: Vec2) {
         let n = self.g.node_mut(idx).unwrap();
         n.set_location(n.location() + delta);
 
@@ -427,7 +676,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         }));
     }
 
-    fn set_drag_start(&mut self, idx: NodeIndex) {
+    fn set_drag_start(&mut self, idx: NodeIndex<Ix>) {
         let n = self.g.node_mut(idx).unwrap();
         n.set_dragged(true);
 
@@ -437,7 +686,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
         }));
     }
 
-    fn set_drag_end(&mut self, idx: NodeIndex) {
+    fn set_drag_end(&mut self, idx: NodeIndex<Ix>) {
         let n = self.g.node_mut(idx).unwrap();
         n.set_dragged(false);
 
@@ -446,6 +695,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
     }
 
     fn set_pan(&self, new_pan: Vec2, meta: &mut Metadata) {
+        #[cfg(feature = "events")]
         let diff = new_pan - meta.pan;
         meta.pan = new_pan;
 
@@ -454,6 +704,7 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
     }
 
     fn set_zoom(&self, new_zoom: f32, meta: &mut Metadata) {
+        #[cfg(feature = "events")]
         let diff = new_zoom - meta.zoom;
         meta.zoom = new_zoom;
 
@@ -469,7 +720,304 @@ impl<'a, N: Clone, E: Clone, Ty: EdgeType> GraphView<'a, N, E, Ty> {
     }
 }
 
-fn default_node_detect<N: Clone>(meta: &Metadata, n: &Node<N>, pos_in_graph: Vec2, settings_style: &SettingsStyle) -> bool {
-    let dist_to_node = (n.location() - pos_in_graph).length();
-    dist_to_node <= n.screen_radius(meta, &settings_style) / meta.zoom
+#[cfg(test)]
+mod tests {
+    use egui::{CentralPanel, Event, Modifiers, PointerButton, RawInput};
+    use petgraph::{stable_graph::StableGraph, Directed};
+
+    use crate::{draw::loops_contain, to_graph, Camera, FitToScreen};
+
+    use super::*;
+
+    const GRID_SPACING: f32 = 10.;
+
+    fn graph() -> Graph<(), (), Directed> {
+        let mut g = StableGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        g.add_edge(a, b, ());
+        let mut g = to_graph(&g);
+        g.node_mut(a).unwrap().set_location(Vec2::new(0., 0.));
+        g.node_mut(b).unwrap().set_location(Vec2::new(100., 100.));
+        g
+    }
+
+    /// Shows the widget for one frame with the input events and returns its metadata.
+    fn frame(
+        ctx: &Context,
+        g: &mut Graph<(), (), Directed>,
+        interaction: &SettingsInteraction,
+        events: Vec<Event>,
+    ) -> Metadata {
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::splat(400.))),
+            events,
+            ..Default::default()
+        };
+        let navigation = SettingsNavigation::new()
+            .with_fit_to_screen(FitToScreen::Once)
+            .with_fit_to_screen_on_double_click(true);
+        let style = SettingsStyle::new().with_grid_spacing(GRID_SPACING);
+
+        let mut meta = None;
+        let _ = ctx.run(input, |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                ui.add(
+                    &mut GraphView::new(g)
+                        .with_interactions(interaction)
+                        .with_navigations(&navigation)
+                        .with_styles(&style),
+                );
+                meta = Some(Metadata::get(ui));
+            });
+        });
+        meta.unwrap()
+    }
+
+    fn button(pos: Pos2, pressed: bool) -> Event {
+        Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    /// Drags the pointer from `from` to `to` in graph coordinates with a frame for every step.
+    fn drag(
+        ctx: &Context,
+        g: &mut Graph<(), (), Directed>,
+        interaction: &SettingsInteraction,
+        from: Vec2,
+        steps: &[Vec2],
+    ) {
+        let meta = frame(ctx, g, interaction, vec![]);
+        let screen = |loc: Vec2| meta.graph_to_screen(loc.to_pos2());
+
+        let start = screen(from);
+        frame(
+            ctx,
+            g,
+            interaction,
+            vec![Event::PointerMoved(start), button(start, true)],
+        );
+        let mut pos = from;
+        steps.iter().for_each(|step| {
+            pos += *step;
+            frame(ctx, g, interaction, vec![Event::PointerMoved(screen(pos))]);
+        });
+        frame(ctx, g, interaction, vec![button(screen(pos), false)]);
+    }
+
+    /// Double clicks at the screen position with a frame for every pointer event.
+    fn double_click(
+        ctx: &Context,
+        g: &mut Graph<(), (), Directed>,
+        interaction: &SettingsInteraction,
+        pos: Pos2,
+    ) {
+        frame(ctx, g, interaction, vec![Event::PointerMoved(pos)]);
+        [true, false, true, false].into_iter().for_each(|pressed| {
+            frame(ctx, g, interaction, vec![button(pos, pressed)]);
+        });
+
+        // frames take 1/60 s, wait until egui does not count the next click as a double or triple one
+        (0..40).for_each(|_| {
+            frame(ctx, g, interaction, vec![]);
+        });
+    }
+
+    #[test]
+    fn test_drag_moves_node() {
+        let ctx = Context::default();
+        let mut g = graph();
+        let a = NodeIndex::new(0);
+        let interaction = SettingsInteraction::new().with_dragging_enabled(true);
+
+        drag(&ctx, &mut g, &interaction, Vec2::new(1., 1.), &[Vec2::new(10., 0.)]);
+
+        let loc = g.node(a).unwrap().location();
+        assert!((loc - Vec2::new(10., 0.)).length() < 0.1, "{:?}", loc);
+        assert!(!g.node(a).unwrap().dragged());
+    }
+
+    #[test]
+    fn test_snapped_drag_keeps_grab_offset() {
+        let ctx = Context::default();
+        let mut g = graph();
+        let a = NodeIndex::new(0);
+        let interaction = SettingsInteraction::new()
+            .with_dragging_enabled(true)
+            .with_snap_to_grid_enabled(true);
+
+        // the node is grabbed off its center, it does not jump to the pointer
+        drag(&ctx, &mut g, &interaction, Vec2::new(4., 4.), &[Vec2::new(3., 0.)]);
+        assert_eq!(g.node(a).unwrap().location(), Vec2::ZERO);
+
+        // small moves are accumulated until the next grid line is reached
+        let steps = [Vec2::new(4., 0.); 3];
+        drag(&ctx, &mut g, &interaction, Vec2::new(4., 4.), &steps);
+        assert_eq!(g.node(a).unwrap().location(), Vec2::new(GRID_SPACING, 0.));
+    }
+
+    #[test]
+    fn test_pinned_node_is_not_dragged() {
+        let ctx = Context::default();
+        let mut g = graph();
+        let a = NodeIndex::new(0);
+        g.node_mut(a).unwrap().set_pinned(true);
+
+        let locked = SettingsInteraction::new()
+            .with_dragging_enabled(true)
+            .with_pinned_dragging_locked(true);
+        drag(&ctx, &mut g, &locked, Vec2::new(1., 1.), &[Vec2::new(10., 0.)]);
+        assert_eq!(g.node(a).unwrap().location(), Vec2::ZERO);
+        assert!(g.node(a).unwrap().pinned());
+
+        // without the lock pinned nodes are dragged as usual
+        let unlocked = SettingsInteraction::new().with_dragging_enabled(true);
+        drag(&ctx, &mut g, &unlocked, Vec2::new(1., 1.), &[Vec2::new(10., 0.)]);
+        assert!(g.node(a).unwrap().location().x > 9.);
+    }
+
+    #[test]
+    fn test_drag_pins_node() {
+        let ctx = Context::default();
+        let mut g = graph();
+        let a = NodeIndex::new(0);
+        let interaction = SettingsInteraction::new()
+            .with_dragging_enabled(true)
+            .with_pinning_enabled(true);
+
+        drag(&ctx, &mut g, &interaction, Vec2::new(1., 1.), &[Vec2::new(10., 0.)]);
+        assert!(g.node(a).unwrap().pinned());
+        assert!(!g.node(NodeIndex::new(1)).unwrap().pinned());
+    }
+
+    #[test]
+    fn test_double_click_toggles_pin() {
+        let ctx = Context::default();
+        let mut g = graph();
+        let a = NodeIndex::new(0);
+        let interaction = SettingsInteraction::new().with_pinning_enabled(true);
+
+        let meta = frame(&ctx, &mut g, &interaction, vec![]);
+        let pos = meta.graph_to_screen(Pos2::ZERO);
+
+        double_click(&ctx, &mut g, &interaction, pos);
+        assert!(g.node(a).unwrap().pinned());
+
+        double_click(&ctx, &mut g, &interaction, pos);
+        assert!(!g.node(a).unwrap().pinned());
+
+        // pinning is disabled
+        double_click(&ctx, &mut g, &SettingsInteraction::new(), pos);
+        assert!(!g.node(a).unwrap().pinned());
+    }
+
+    #[test]
+    fn test_double_click_fits_to_screen() {
+        let ctx = Context::default();
+        let mut g = graph();
+        let interaction = SettingsInteraction::new();
+
+        let fitted = frame(&ctx, &mut g, &interaction, vec![]);
+        g.node_mut(NodeIndex::new(1))
+            .unwrap()
+            .set_location(Vec2::new(300., 300.));
+        assert_eq!(frame(&ctx, &mut g, &interaction, vec![]).zoom, fitted.zoom);
+
+        // double click on the empty canvas
+        double_click(&ctx, &mut g, &interaction, Pos2::new(380., 20.));
+        let meta = frame(&ctx, &mut g, &interaction, vec![]);
+
+        assert!(meta.zoom < fitted.zoom);
+        assert!(!meta.fit_requested);
+        assert_eq!(
+            meta.graph_to_screen(Pos2::new(150., 150.)),
+            Pos2::new(200., 200.)
+        );
+    }
+
+    /// Edge drawer which draws self loops nowhere.
+    struct WithoutLoops;
+
+    impl EdgeDrawer<(), (), Directed> for WithoutLoops {
+        fn draw(
+            &self,
+            _: &Context,
+            _: (NodeIndex, NodeIndex),
+            _: Vec<(EdgeIndex, &Edge<()>)>,
+            _: &WidgetState<(), (), Directed>,
+            _: &mut Layers,
+        ) {
+        }
+
+        fn loops_contain(
+            &self,
+            _: &Graph<(), (), Directed>,
+            _: &SettingsStyle,
+            _: &Metadata,
+            _: NodeIndex,
+            _: Pos2,
+        ) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_loops_are_detected_by_edge_drawer() {
+        let mut g = graph();
+        let a = NodeIndex::new(0);
+        g.add_edge(a, a, Edge::new(()));
+        let meta = Metadata::default();
+        let style = SettingsStyle::new();
+
+        // find a point on the loop outside of the node
+        let on_loop = (-100..100)
+            .flat_map(|x| (-100..100).map(move |y| Pos2::new(x as f32, y as f32)))
+            .find(|pos| {
+                loops_contain(&g, &style, &meta, a, *pos)
+                    && !default_node_detect(&meta, g.node(a).unwrap(), pos.to_vec2(), &style)
+            })
+            .unwrap();
+
+        let found = |view: GraphView<(), (), Directed>| {
+            view.node_by_screen_pos(&meta, on_loop).map(|(idx, _)| idx)
+        };
+        assert_eq!(found(GraphView::new(&mut g)), Some(a));
+        assert_eq!(
+            found(GraphView::new(&mut g).with_edge_drawer(default_edges_draw)),
+            Some(a)
+        );
+        assert_eq!(
+            found(GraphView::new(&mut g).with_edge_drawer(WithoutLoops)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_metadata_helpers_with_id() {
+        let ctx = Context::default();
+        let id = Id::new("graph");
+
+        let _ = ctx.run(RawInput::default(), |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                GraphView::<(), (), Directed>::request_fit_to_screen_with_id(ui, id);
+                assert!(Metadata::get_with_id(ui, id).fit_requested);
+                assert!(!Metadata::get(ui).fit_requested);
+
+                let camera = Camera {
+                    zoom: 2.,
+                    pan: Vec2::new(1., 2.),
+                };
+                camera.store_into_ui_with_id(ui, id);
+                assert_eq!(Camera::get_with_id(ui, id), camera);
+                assert_eq!(Camera::get(ui), Camera::default());
+
+                GraphView::<(), (), Directed>::reset_metadata_with_id(ui, id);
+                assert_eq!(Camera::get_with_id(ui, id), Camera::default());
+            });
+        });
+    }
 }
@@ -0,0 +1,45 @@
+use egui::Color32;
+
+/// Parses color in the `#rrggbb` or `#rrggbbaa` notation.
+pub(crate) fn parse_hex_color(value: &str) -> Option<Color32> {
+    let hex = value.strip_prefix('#')?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match hex.len() {
+        6 => Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?)),
+        8 => Some(Color32::from_rgba_unmultiplied(
+            channel(0)?,
+            channel(2)?,
+            channel(4)?,
+            channel(6)?,
+        )),
+        _ => None,
+    }
+}
+
+/// Formats color in the `#rrggbb` notation or `#rrggbbaa` for translucent colors.
+pub(crate) fn format_hex_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    match a {
+        255 => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_color() {
+        assert_eq!(parse_hex_color("#0a0B0c"), Some(Color32::from_rgb(10, 11, 12)));
+        assert_eq!(format_hex_color(Color32::from_rgb(10, 11, 12)), "#0a0b0c");
+
+        let translucent = Color32::from_rgba_unmultiplied(255, 0, 0, 128);
+        assert_eq!(format_hex_color(translucent), "#ff000080");
+        assert_eq!(parse_hex_color(&format_hex_color(translucent)), Some(translucent));
+
+        assert_eq!(parse_hex_color("0a0b0c"), None);
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("#gg0000"), None);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use egui::Vec2;
+use petgraph::{
+    stable_graph::{NodeIndex, StableGraph},
+    EdgeType,
+};
+
+use crate::{
+    transform::{random_location, DEFAULT_SPAWN_SIZE},
+    Edge, Graph, Node,
+};
+
+use super::{
+    reader::{parse_number, Table},
+    CsvError, CsvOptions, CsvRecord,
+};
+
+/// Edge of the CSV edge list passed to the edge data function.
+#[derive(Debug, Clone, Copy)]
+pub struct CsvEdge<'a> {
+    /// Value of the weight column, `None` if the column is not set or the value is empty.
+    pub weight: Option<f64>,
+    /// Value of the label column.
+    pub label: Option<&'a str>,
+    /// All values of the row.
+    pub record: &'a CsvRecord,
+}
+
+/// Result of the CSV import.
+#[derive(Debug, Clone)]
+pub struct CsvImport<N: Clone, E: Clone, Ty: EdgeType> {
+    pub graph: Graph<N, E, Ty>,
+    /// Node indices by their ids.
+    pub nodes: HashMap<String, NodeIndex>,
+}
+
+/// Builds the graph from the CSV edge list and the optional node table. Node and edge data are
+/// created with [`Default`], use [`from_csv_custom`] to build them from the CSV values.
+pub fn from_csv<N: Clone + Default, E: Clone + Default, Ty: EdgeType>(
+    edges: &str,
+    nodes: Option<&str>,
+    options: &CsvOptions,
+) -> Result<CsvImport<N, E, Ty>, CsvError> {
+    from_csv_custom(edges, nodes, options, |_, _| N::default(), |_| E::default())
+}
+
+/// Builds the graph from the CSV edge list and the optional node table building node and edge data
+/// with the provided functions. `node_data` receives node id and its row of the node table if any,
+/// `edge_data` receives the edge row.
+///
+/// Every row of the edge list is an edge between the nodes in the source and target columns.
+/// Nodes are created in the order of the node table and then in the order of their first appearance
+/// in the edge list. The node table provides node labels and locations, see [`CsvOptions`] for the columns.
+/// Node ids of the node table must be unique.
+///
+/// # Example
+/// ```
+/// use egui_graphs::{from_csv, CsvOptions};
+/// use petgraph::Directed;
+///
+/// let edges = "from;to;weight\na;b;0.5\nb;c;2\n";
+/// let nodes = "id;name\na;start\n";
+/// let options = CsvOptions::new()
+///     .with_delimiter(';')
+///     .with_weight_column("weight")
+///     .with_node_label_column("name");
+/// let import = from_csv::<(), (), Directed>(edges, Some(nodes), &options).unwrap();
+///
+/// assert_eq!(import.graph.node_count(), 3);
+/// assert_eq!(import.graph.edge_count(), 2);
+/// assert_eq!(import.graph.node(import.nodes["a"]).unwrap().label(), "start");
+/// ```
+pub fn from_csv_custom<N: Clone, E: Clone, Ty: EdgeType>(
+    edges: &str,
+    nodes: Option<&str>,
+    options: &CsvOptions,
+    node_data: impl Fn(&str, Option<&CsvRecord>) -> N,
+    edge_data: impl Fn(&CsvEdge) -> E,
+) -> Result<CsvImport<N, E, Ty>, CsvError> {
+    let mut g = StableGraph::<Node<N>, Edge<E>, Ty>::default();
+    let mut indices = HashMap::new();
+
+    if let Some(nodes) = nodes {
+        let table = Table::parse(nodes, options)?;
+        let id = table.index(&options.node_id)?;
+        let label = options
+            .node_label
+            .as_ref()
+            .map(|column| table.index(column).map(|index| (index, column)))
+            .transpose()?;
+        let position = options
+            .node_position
+            .as_ref()
+            .map(|(x, y)| Ok::<_, CsvError>(((table.index(x)?, x), (table.index(y)?, y))))
+            .transpose()?;
+
+        table.rows.iter().try_for_each(|row| {
+            let node_id = table.value(row, id, &options.node_id)?;
+            if indices.contains_key(node_id) {
+                return Err(CsvError::DuplicateNode {
+                    line: row.line,
+                    id: node_id.to_string(),
+                });
+            }
+            let record = table.record(row);
+
+            let location = match position {
+                Some(((x, x_column), (y, y_column))) => Vec2::new(
+                    parse_number(table.value(row, x, x_column)?, row.line, x_column)? as f32,
+                    parse_number(table.value(row, y, y_column)?, row.line, y_column)? as f32,
+                ),
+                None => random_location(DEFAULT_SPAWN_SIZE),
+            };
+            let mut node = Node::new(location, node_data(node_id, Some(&record)));
+            node.set_label(match label {
+                Some((index, column)) => table.value(row, index, column)?.to_string(),
+                None => node_id.to_string(),
+            });
+
+            indices.insert(node_id.to_string(), g.add_node(node));
+            Ok::<(), CsvError>(())
+        })?;
+    }
+
+    let table = Table::parse(edges, options)?;
+    let source = table.index(&options.source)?;
+    let target = table.index(&options.target)?;
+    let weight = options
+        .weight
+        .as_ref()
+        .map(|column| table.index(column).map(|index| (index, column)))
+        .transpose()?;
+    let label = options
+        .label
+        .as_ref()
+        .map(|column| table.index(column).map(|index| (index, column)))
+        .transpose()?;
+
+    table.rows.iter().try_for_each(|row| {
+        let ends = [
+            table.value(row, source, &options.source)?,
+            table.value(row, target, &options.target)?,
+        ]
+        .map(|id| {
+            *indices.entry(id.to_string()).or_insert_with(|| {
+                let mut node = Node::new(random_location(DEFAULT_SPAWN_SIZE), node_data(id, None));
+                node.set_label(id.to_string());
+                g.add_node(node)
+            })
+        });
+
+        let record = table.record(row);
+        let edge = CsvEdge {
+            weight: match weight {
+                Some((index, column)) => table.number(row, index, column)?,
+                None => None,
+            },
+            label: match label {
+                Some((index, column)) => Some(table.value(row, index, column)?),
+                None => None,
+            },
+            record: &record,
+        };
+        g.add_edge(ends[0], ends[1], Edge::new(edge_data(&edge)));
+        Ok::<(), CsvError>(())
+    })?;
+
+    Ok(CsvImport {
+        graph: Graph::new(g),
+        nodes: indices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{Directed, Undirected};
+
+    use crate::io::csv::CsvColumn;
+
+    use super::*;
+
+    #[test]
+    fn test_from_csv_custom() {
+        let edges = "source,target,weight,kind\na,b,1.5,road\nb,c,,rail\nc,a,3,\n";
+        let nodes = "id,label,x,y\nc,third,10,-20\nd,alone,0,0\n";
+        let options = CsvOptions::new()
+            .with_weight_column("weight")
+            .with_label_column(3)
+            .with_node_label_column("label")
+            .with_node_position_columns("x", "y");
+
+        let import = from_csv_custom::<String, (Option<f64>, String), Directed>(
+            edges,
+            Some(nodes),
+            &options,
+            |id, record| format!("{}:{}", id, record.is_some()),
+            |edge| (edge.weight, edge.label.unwrap_or_default().to_string()),
+        )
+        .unwrap();
+
+        let g = &import.graph;
+        assert_eq!(g.g.node_count(), 4);
+        assert_eq!(g.g.edge_count(), 3);
+
+        // nodes of the node table come first
+        assert_eq!(import.nodes["c"], NodeIndex::new(0));
+        assert_eq!(import.nodes["a"], NodeIndex::new(2));
+
+        let c = g.node(import.nodes["c"]).unwrap();
+        assert_eq!(c.label(), "third");
+        assert_eq!(c.location(), Vec2::new(10., -20.));
+        assert_eq!(c.data, "c:true");
+        let a = g.node(import.nodes["a"]).unwrap();
+        assert_eq!(a.label(), "a");
+        assert_eq!(a.data, "a:false");
+
+        let data = g
+            .edges_iter()
+            .map(|(_, e)| e.data.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            data,
+            vec![
+                (Some(1.5), "road".to_string()),
+                (None, "rail".to_string()),
+                (Some(3.), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_csv_without_header() {
+        let options = CsvOptions::new().with_header(false).with_delimiter('\t');
+        let import = from_csv::<(), (), Undirected>("1\t2\n2\t3\n", None, &options).unwrap();
+
+        assert_eq!(import.graph.g.node_count(), 3);
+        assert!(import
+            .graph
+            .g
+            .find_edge(import.nodes["3"], import.nodes["2"])
+            .is_some());
+    }
+
+    #[test]
+    fn test_from_csv_errors() {
+        let options = CsvOptions::new().with_weight_column("weight");
+        assert_eq!(
+            from_csv::<(), (), Directed>("a,b\n1,2\n", None, &options).err(),
+            Some(CsvError::Column {
+                line: 1,
+                column: "weight".to_string()
+            })
+        );
+
+        let options = CsvOptions::new().with_weight_column(2);
+        assert_eq!(
+            from_csv::<(), (), Directed>("a,b,w\n1,2,x\n", None, &options).err(),
+            Some(CsvError::Value {
+                line: 2,
+                column: "2".to_string(),
+                value: "x".to_string()
+            })
+        );
+
+        let options = CsvOptions::new().with_header(false);
+        assert_eq!(
+            from_csv::<(), (), Directed>("1,2\n3\n", None, &options).err(),
+            Some(CsvError::Column {
+                line: 2,
+                column: CsvColumn::Index(1).to_string()
+            })
+        );
+
+        assert_eq!(
+            from_csv::<(), (), Directed>("a,b\n1,2\n", Some("id\n1\n2\n1\n"), &CsvOptions::new())
+                .err(),
+            Some(CsvError::DuplicateNode {
+                line: 4,
+                id: "1".to_string()
+            })
+        );
+    }
+}
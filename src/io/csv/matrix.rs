@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+
+use petgraph::{
+    stable_graph::{NodeIndex, StableGraph},
+    EdgeType,
+};
+
+use crate::{
+    transform::{random_location, DEFAULT_SPAWN_SIZE},
+    Edge, Graph, Node,
+};
+
+use super::{
+    reader::{Row, Table},
+    CsvColumn, CsvError, CsvOptions,
+};
+
+/// Adjacency matrix of the graph. Node `i` of the matrix becomes the node with index `i` of the graph.
+/// Entries with zero weight are not edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdjacencyMatrix {
+    size: usize,
+    /// Non-zero entries as `(row, column, weight)`.
+    entries: Vec<(usize, usize, f64)>,
+    labels: Vec<String>,
+}
+
+impl AdjacencyMatrix {
+    /// Creates the matrix from its rows. Returns an error if the matrix is not square.
+    pub fn dense(rows: &[Vec<f64>]) -> Result<Self, CsvError> {
+        let size = rows.len();
+        if let Some(i) = rows.iter().position(|row| row.len() != size) {
+            return Err(CsvError::Matrix(format!(
+                "row {} has {} values, expected {}",
+                i,
+                rows[i].len(),
+                size
+            )));
+        }
+
+        let entries = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().enumerate().map(move |(j, w)| (i, j, *w)))
+            .collect();
+        Self::sparse(size, entries)
+    }
+
+    /// Creates the matrix of the given size from its entries as `(row, column, weight)`.
+    /// Returns an error if an entry is out of the matrix.
+    pub fn sparse(size: usize, entries: Vec<(usize, usize, f64)>) -> Result<Self, CsvError> {
+        if let Some((i, j, _)) = entries.iter().find(|(i, j, _)| *i >= size || *j >= size) {
+            return Err(CsvError::Matrix(format!(
+                "entry ({}, {}) is out of the matrix of size {}",
+                i, j, size
+            )));
+        }
+
+        Ok(Self {
+            size,
+            entries: entries.into_iter().filter(|(_, _, w)| *w != 0.).collect(),
+            labels: (0..size).map(|i| i.to_string()).collect(),
+        })
+    }
+
+    /// Reads the dense matrix with a row of values per node. If the file has header, it provides
+    /// node labels. If the first cell of the header is empty, the first column holds the row labels
+    /// and is skipped. Empty values are zero.
+    ///
+    /// ```
+    /// use egui_graphs::{AdjacencyMatrix, CsvOptions};
+    /// use petgraph::Undirected;
+    ///
+    /// let csv = ",a,b,c\na,0,1,0\nb,1,0,2\nc,0,2,0\n";
+    /// let matrix = AdjacencyMatrix::from_csv(csv, &CsvOptions::new()).unwrap();
+    /// let g = matrix.to_graph::<(), (), Undirected>();
+    ///
+    /// assert_eq!(g.node_count(), 3);
+    /// assert_eq!(g.edge_count(), 2);
+    /// ```
+    pub fn from_csv(text: &str, options: &CsvOptions) -> Result<Self, CsvError> {
+        let table = Table::parse(text, options)?;
+        let row_labels = table
+            .header
+            .as_ref()
+            .and_then(|header| header.first())
+            .is_some_and(|first| first.is_empty());
+        let skip = match row_labels {
+            true => 1,
+            false => 0,
+        };
+
+        let rows = table
+            .rows
+            .iter()
+            .map(|row| {
+                row.values
+                    .iter()
+                    .enumerate()
+                    .skip(skip)
+                    .map(|(i, _)| {
+                        let column = i.into();
+                        table.number(row, i, &column).map(|w| w.unwrap_or_default())
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let matrix = Self::dense(&rows)?;
+        match table.header {
+            Some(header) => matrix.with_labels(header.into_iter().skip(skip).collect()),
+            None => Ok(matrix),
+        }
+    }
+
+    /// Reads the sparse matrix with an entry per row. Entries are read from the source, target and
+    /// weight columns of the options, node indices start from `0`. Entries without weight column or
+    /// with empty weight have weight `1`. The matrix size is set by [`CsvOptions::with_matrix_size`],
+    /// otherwise it is the largest node index plus one and a single wrong index can allocate a huge
+    /// number of nodes, so set the size for untrusted files.
+    pub fn from_csv_sparse(text: &str, options: &CsvOptions) -> Result<Self, CsvError> {
+        let table = Table::parse(text, options)?;
+        let source = table.index(&options.source)?;
+        let target = table.index(&options.target)?;
+        let weight = options
+            .weight
+            .as_ref()
+            .map(|column| table.index(column).map(|index| (index, column)))
+            .transpose()?;
+
+        let index = |row: &Row, index: usize, column: &CsvColumn| {
+            let value = table.number(row, index, column)?.unwrap_or(-1.);
+            match value >= 0. && value.fract() == 0. {
+                true => Ok(value as usize),
+                false => Err(CsvError::Value {
+                    line: row.line,
+                    column: column.to_string(),
+                    value: table.value(row, index, column)?.to_string(),
+                }),
+            }
+        };
+
+        let entries = table
+            .rows
+            .iter()
+            .map(|row| {
+                let weight = match weight {
+                    Some((index, column)) => table.number(row, index, column)?.unwrap_or(1.),
+                    None => 1.,
+                };
+                Ok((
+                    index(row, source, &options.source)?,
+                    index(row, target, &options.target)?,
+                    weight,
+                ))
+            })
+            .collect::<Result<Vec<_>, CsvError>>()?;
+
+        let size = options.matrix_size.unwrap_or_else(|| {
+            entries
+                .iter()
+                .map(|(i, j, _)| i.max(j) + 1)
+                .max()
+                .unwrap_or_default()
+        });
+        Self::sparse(size, entries)
+    }
+
+    /// Sets the node labels. Returns an error if the number of labels differs from the matrix size.
+    ///
+    /// Default: node indices
+    pub fn with_labels(mut self, labels: Vec<String>) -> Result<Self, CsvError> {
+        if labels.len() != self.size {
+            return Err(CsvError::Matrix(format!(
+                "{} labels for the matrix of size {}",
+                labels.len(),
+                self.size
+            )));
+        }
+
+        self.labels = labels;
+        Ok(self)
+    }
+
+    /// Number of nodes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Builds the graph with [`Default`] node and edge data.
+    pub fn to_graph<N: Clone + Default, E: Clone + Default, Ty: EdgeType>(
+        &self,
+    ) -> Graph<N, E, Ty> {
+        self.to_graph_custom(|_, _| N::default(), |_| E::default())
+    }
+
+    /// Builds the graph creating node data from the node index and label and edge data from the weight.
+    ///
+    /// For undirected graphs the entries `(i, j)` and `(j, i)` are the same edge, so a symmetric
+    /// matrix gives one edge per pair of nodes.
+    pub fn to_graph_custom<N: Clone, E: Clone, Ty: EdgeType>(
+        &self,
+        node_data: impl Fn(usize, &str) -> N,
+        edge_data: impl Fn(f64) -> E,
+    ) -> Graph<N, E, Ty> {
+        let mut g = StableGraph::<Node<N>, Edge<E>, Ty>::default();
+        self.labels.iter().enumerate().for_each(|(i, label)| {
+            let mut node = Node::new(random_location(DEFAULT_SPAWN_SIZE), node_data(i, label));
+            node.set_label(label.clone());
+            g.add_node(node);
+        });
+
+        let positions = self
+            .entries
+            .iter()
+            .map(|(i, j, _)| (*i, *j))
+            .collect::<HashSet<_>>();
+        self.entries.iter().for_each(|(i, j, w)| {
+            let mirrored = !Ty::is_directed() && i > j && positions.contains(&(*j, *i));
+            if !mirrored {
+                g.add_edge(
+                    NodeIndex::new(*i),
+                    NodeIndex::new(*j),
+                    Edge::new(edge_data(*w)),
+                );
+            }
+        });
+
+        Graph::new(g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{Directed, Undirected};
+
+    use super::*;
+
+    fn weights<Ty: EdgeType>(g: &Graph<(), f64, Ty>) -> Vec<(usize, usize, f64)> {
+        g.edges_iter()
+            .map(|(e, edge)| {
+                let (a, b) = g.g.edge_endpoints(e).unwrap();
+                (a.index(), b.index(), edge.data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dense() {
+        let matrix =
+            AdjacencyMatrix::dense(&[vec![0., 1., 0.], vec![1., 0., 2.], vec![0., 0., 1.]])
+                .unwrap();
+
+        let directed = matrix.to_graph_custom::<(), f64, Directed>(|_, _| (), |w| w);
+        assert_eq!(
+            weights(&directed),
+            vec![(0, 1, 1.), (1, 0, 1.), (1, 2, 2.), (2, 2, 1.)]
+        );
+
+        let undirected = matrix.to_graph_custom::<(), f64, Undirected>(|_, _| (), |w| w);
+        assert_eq!(
+            weights(&undirected),
+            vec![(0, 1, 1.), (1, 2, 2.), (2, 2, 1.)]
+        );
+        assert_eq!(undirected.node(NodeIndex::new(2)).unwrap().label(), "2");
+
+        assert!(matches!(
+            AdjacencyMatrix::dense(&[vec![0., 1.], vec![1.]]),
+            Err(CsvError::Matrix(_))
+        ));
+    }
+
+    #[test]
+    fn test_sparse() {
+        let matrix = AdjacencyMatrix::sparse(3, vec![(0, 2, 0.5), (2, 1, 0.)]).unwrap();
+        assert_eq!(matrix.entries, vec![(0, 2, 0.5)]);
+
+        assert!(matches!(
+            AdjacencyMatrix::sparse(2, vec![(0, 2, 1.)]),
+            Err(CsvError::Matrix(_))
+        ));
+        assert!(matches!(
+            matrix.with_labels(vec!["a".to_string()]),
+            Err(CsvError::Matrix(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let labeled =
+            AdjacencyMatrix::from_csv(",a,b\na,0,1.5\nb,,0\n", &CsvOptions::new()).unwrap();
+        assert_eq!(labeled.size(), 2);
+        assert_eq!(labeled.entries, vec![(0, 1, 1.5)]);
+        assert_eq!(labeled.labels, vec!["a".to_string(), "b".to_string()]);
+
+        let header =
+            AdjacencyMatrix::from_csv("x;y\n0;1\n1;0\n", &CsvOptions::new().with_delimiter(';'))
+                .unwrap();
+        assert_eq!(header.labels, vec!["x".to_string(), "y".to_string()]);
+
+        let plain = AdjacencyMatrix::from_csv(
+            "0 1\n0 0\n",
+            &CsvOptions::new().with_header(false).with_delimiter(' '),
+        )
+        .unwrap();
+        assert_eq!(plain.entries, vec![(0, 1, 1.)]);
+        assert_eq!(plain.labels, vec!["0".to_string(), "1".to_string()]);
+
+        assert!(matches!(
+            AdjacencyMatrix::from_csv("0,1\n0,x\n", &CsvOptions::new().with_header(false)),
+            Err(CsvError::Value { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_csv_sparse() {
+        let options = CsvOptions::new()
+            .with_source_column("row")
+            .with_target_column("col")
+            .with_weight_column("w");
+        let matrix =
+            AdjacencyMatrix::from_csv_sparse("row,col,w\n0,3,2\n1,0,\n", &options).unwrap();
+
+        assert_eq!(matrix.size(), 4);
+        assert_eq!(matrix.entries, vec![(0, 3, 2.), (1, 0, 1.)]);
+
+        assert_eq!(
+            AdjacencyMatrix::from_csv_sparse("row,col,w\n0,1.5,1\n", &options).err(),
+            Some(CsvError::Value {
+                line: 2,
+                column: "col".to_string(),
+                value: "1.5".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_csv_sparse_size() {
+        let options = CsvOptions::new().with_header(false).with_matrix_size(3);
+
+        let matrix = AdjacencyMatrix::from_csv_sparse("0,1\n", &options).unwrap();
+        assert_eq!(matrix.size(), 3);
+
+        assert!(matches!(
+            AdjacencyMatrix::from_csv_sparse("0,1\n1000000000,0\n", &options),
+            Err(CsvError::Matrix(_))
+        ));
+    }
+}
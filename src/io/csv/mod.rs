@@ -0,0 +1,224 @@
+//! Import of graphs from CSV edge lists and adjacency matrices.
+
+mod edges;
+mod matrix;
+mod reader;
+
+use std::{collections::HashMap, fmt};
+
+pub use self::edges::{from_csv, from_csv_custom, CsvEdge, CsvImport};
+pub use self::matrix::AdjacencyMatrix;
+
+/// Values of the CSV row by the column names. Columns of the files without header are named
+/// by their indices starting from `0`.
+pub type CsvRecord = HashMap<String, String>;
+
+/// Column of the CSV file given by its index starting from `0` or by its name in the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl fmt::Display for CsvColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvColumn::Index(index) => write!(f, "{}", index),
+            CsvColumn::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Settings of the CSV import.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub(crate) delimiter: char,
+    pub(crate) has_header: bool,
+
+    pub(crate) source: CsvColumn,
+    pub(crate) target: CsvColumn,
+    pub(crate) weight: Option<CsvColumn>,
+    pub(crate) label: Option<CsvColumn>,
+
+    pub(crate) node_id: CsvColumn,
+    pub(crate) node_label: Option<CsvColumn>,
+    pub(crate) node_position: Option<(CsvColumn, CsvColumn)>,
+
+    pub(crate) matrix_size: Option<usize>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: true,
+
+            source: CsvColumn::Index(0),
+            target: CsvColumn::Index(1),
+            weight: Default::default(),
+            label: Default::default(),
+
+            node_id: CsvColumn::Index(0),
+            node_label: Default::default(),
+            node_position: Default::default(),
+
+            matrix_size: Default::default(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Creates new [`CsvOptions`] with default values.
+    /// ```
+    /// use egui_graphs::CsvOptions;
+    /// let options = CsvOptions::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Values delimiter, e.g. `;` or `\t`.
+    ///
+    /// Default: `,`
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Whether the first row of the files is the header with the column names.
+    /// Columns can be selected by the names only if the files have header.
+    ///
+    /// Default: `true`
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Column of the edge list with the edge source node id.
+    ///
+    /// Default: `0`
+    pub fn with_source_column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.source = column.into();
+        self
+    }
+
+    /// Column of the edge list with the edge target node id.
+    ///
+    /// Default: `1`
+    pub fn with_target_column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.target = column.into();
+        self
+    }
+
+    /// Column of the edge list with the numeric edge weight. Empty values are allowed.
+    ///
+    /// Default: `None`
+    pub fn with_weight_column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.weight = Some(column.into());
+        self
+    }
+
+    /// Column of the edge list with the edge label.
+    ///
+    /// Default: `None`
+    pub fn with_label_column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.label = Some(column.into());
+        self
+    }
+
+    /// Column of the node table with the node id used in the edge list.
+    ///
+    /// Default: `0`
+    pub fn with_node_id_column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.node_id = column.into();
+        self
+    }
+
+    /// Column of the node table with the node label. Nodes without label are labeled with their ids.
+    ///
+    /// Default: `None`
+    pub fn with_node_label_column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.node_label = Some(column.into());
+        self
+    }
+
+    /// Columns of the node table with the node location. Nodes without location are placed randomly.
+    ///
+    /// Default: `None`
+    pub fn with_node_position_columns(
+        mut self,
+        x: impl Into<CsvColumn>,
+        y: impl Into<CsvColumn>,
+    ) -> Self {
+        self.node_position = Some((x.into(), y.into()));
+        self
+    }
+
+    /// Size of the sparse adjacency matrix. Entries out of the matrix are errors. Without the size
+    /// it is taken from the largest node index, so a single wrong index can create a huge graph.
+    ///
+    /// Default: `None`
+    pub fn with_matrix_size(mut self, size: usize) -> Self {
+        self.matrix_size = Some(size);
+        self
+    }
+}
+
+/// Error returned when CSV data can not be imported. Lines start from `1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// Source is not valid CSV, e.g. a quoted value is not terminated.
+    Syntax { line: usize, message: String },
+    /// Column is missing in the header or in the row.
+    Column { line: usize, column: String },
+    /// Value is not a number.
+    Value {
+        line: usize,
+        column: String,
+        value: String,
+    },
+    /// Node table has several rows with the same node id.
+    DuplicateNode { line: usize, id: String },
+    /// Adjacency matrix is not square or refers to a node out of its size.
+    Matrix(String),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Syntax { line, message } => {
+                write!(f, "CSV syntax error at line {}: {}", line, message)
+            }
+            CsvError::Column { line, column } => {
+                write!(f, "missing column `{}` at line {}", column, line)
+            }
+            CsvError::Value {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "invalid number `{}` in column `{}` at line {}",
+                value, column, line
+            ),
+            CsvError::DuplicateNode { line, id } => {
+                write!(f, "duplicate node id `{}` at line {}", id, line)
+            }
+            CsvError::Matrix(message) => write!(f, "invalid adjacency matrix: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
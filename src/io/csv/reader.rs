@@ -0,0 +1,247 @@
+use super::{CsvColumn, CsvError, CsvOptions, CsvRecord};
+
+/// Row of the CSV file with its line number.
+pub(super) struct Row {
+    pub line: usize,
+    pub values: Vec<String>,
+}
+
+/// Parsed CSV file.
+pub(super) struct Table {
+    pub header: Option<Vec<String>>,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    pub fn parse(source: &str, options: &CsvOptions) -> Result<Self, CsvError> {
+        let mut rows = read_rows(source, options.delimiter)?;
+        let header = match options.has_header && !rows.is_empty() {
+            true => Some(rows.remove(0).values),
+            false => None,
+        };
+
+        Ok(Self { header, rows })
+    }
+
+    /// Returns the index of the column checking that it exists in the header.
+    pub fn index(&self, column: &CsvColumn) -> Result<usize, CsvError> {
+        let missing = || CsvError::Column {
+            line: 1,
+            column: column.to_string(),
+        };
+        match (column, &self.header) {
+            (CsvColumn::Index(index), Some(header)) if *index >= header.len() => Err(missing()),
+            (CsvColumn::Index(index), _) => Ok(*index),
+            (CsvColumn::Name(name), Some(header)) => {
+                header.iter().position(|h| h == name).ok_or_else(missing)
+            }
+            (CsvColumn::Name(_), None) => Err(missing()),
+        }
+    }
+
+    /// Returns the value of the column in the row.
+    pub fn value<'a>(
+        &self,
+        row: &'a Row,
+        index: usize,
+        column: &CsvColumn,
+    ) -> Result<&'a str, CsvError> {
+        row.values
+            .get(index)
+            .map(|v| v.as_str())
+            .ok_or_else(|| CsvError::Column {
+                line: row.line,
+                column: column.to_string(),
+            })
+    }
+
+    /// Returns the number in the column of the row. Empty values are `None`.
+    pub fn number(
+        &self,
+        row: &Row,
+        index: usize,
+        column: &CsvColumn,
+    ) -> Result<Option<f64>, CsvError> {
+        let value = self.value(row, index, column)?;
+        match value.is_empty() {
+            true => Ok(None),
+            false => parse_number(value, row.line, column).map(Some),
+        }
+    }
+
+    /// Returns all values of the row by the column names.
+    pub fn record(&self, row: &Row) -> CsvRecord {
+        row.values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let name = self
+                    .header
+                    .as_ref()
+                    .and_then(|header| header.get(i).cloned())
+                    .unwrap_or_else(|| i.to_string());
+                (name, value.clone())
+            })
+            .collect()
+    }
+}
+
+pub(super) fn parse_number(value: &str, line: usize, column: &CsvColumn) -> Result<f64, CsvError> {
+    value.parse::<f64>().map_err(|_| CsvError::Value {
+        line,
+        column: column.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Splits the source into rows of values. Values can be quoted with `"`, quotes inside of quoted
+/// values are doubled. Unquoted values are trimmed and empty lines are skipped.
+fn read_rows(source: &str, delimiter: char) -> Result<Vec<Row>, CsvError> {
+    let mut rows = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start = line;
+        let mut values = Vec::new();
+        let mut value = String::new();
+        let mut quoted = false;
+
+        loop {
+            match chars.next() {
+                None => {
+                    values.push(finish(value, quoted));
+                    break;
+                }
+                Some('\n') => {
+                    line += 1;
+                    values.push(finish(value, quoted));
+                    break;
+                }
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some('"') if value.trim().is_empty() && !quoted => {
+                    value.clear();
+                    quoted = true;
+                    loop {
+                        match chars.next() {
+                            None => {
+                                return Err(CsvError::Syntax {
+                                    line: start,
+                                    message: "unterminated quoted value".to_string(),
+                                })
+                            }
+                            Some('"') if chars.peek() == Some(&'"') => {
+                                chars.next();
+                                value.push('"');
+                            }
+                            Some('"') => break,
+                            Some(c) => {
+                                if c == '\n' {
+                                    line += 1;
+                                }
+                                value.push(c);
+                            }
+                        }
+                    }
+                }
+                Some(c) if c == delimiter => {
+                    values.push(finish(std::mem::take(&mut value), quoted));
+                    quoted = false;
+                }
+                Some(c) => {
+                    if quoted && !c.is_whitespace() {
+                        return Err(CsvError::Syntax {
+                            line,
+                            message: format!("unexpected `{}` after quoted value", c),
+                        });
+                    }
+                    value.push(c);
+                }
+            }
+        }
+
+        let empty = values.len() == 1 && values[0].is_empty();
+        if !empty {
+            rows.push(Row {
+                line: start,
+                values,
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+fn finish(value: String, quoted: bool) -> String {
+    match quoted {
+        true => value,
+        false => value.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(source: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+        read_rows(source, delimiter)
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.line, row.values))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_rows() {
+        let rows = values("a, b ,c\r\n\n\"x, \"\"y\"\"\",\"multi\nline\"\n1;2\n", ',');
+
+        assert_eq!(
+            rows,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+                (3, vec!["x, \"y\"".to_string(), "multi\nline".to_string()]),
+                (5, vec!["1;2".to_string()]),
+            ]
+        );
+        assert_eq!(
+            values("1\t2", '\t'),
+            vec![(1, vec!["1".to_string(), "2".to_string()])]
+        );
+    }
+
+    #[test]
+    fn test_read_rows_errors() {
+        assert_eq!(
+            read_rows("a,b\n\"c", ',').err(),
+            Some(CsvError::Syntax {
+                line: 2,
+                message: "unterminated quoted value".to_string()
+            })
+        );
+        assert!(matches!(
+            read_rows("\"a\"b", ','),
+            Err(CsvError::Syntax { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_table_columns() {
+        let options = CsvOptions::new();
+        let table = Table::parse("from,to,w\na,b,1.5\nc,d,\n", &options).unwrap();
+
+        assert_eq!(table.index(&"to".into()).unwrap(), 1);
+        assert_eq!(
+            table.index(&"weight".into()).err(),
+            Some(CsvError::Column {
+                line: 1,
+                column: "weight".to_string()
+            })
+        );
+        assert_eq!(
+            table.number(&table.rows[0], 2, &"w".into()).unwrap(),
+            Some(1.5)
+        );
+        assert_eq!(table.number(&table.rows[1], 2, &"w".into()).unwrap(), None);
+        assert_eq!(table.record(&table.rows[0])["from"], "a");
+    }
+}
@@ -0,0 +1,384 @@
+use egui::{ecolor::Hsva, Color32, Vec2};
+
+use crate::{
+    io::color::parse_hex_color, Edge, EdgeMarker, Node, NodeShape, StrokePattern,
+};
+
+use super::{lexer::keyword, DotAttributes, DotError};
+
+/// Number of graph units, which are the same as DOT points, in one inch.
+pub(super) const POINTS_PER_INCH: f32 = 72.;
+
+/// Named colors supported in addition to the `#rrggbb`, `#rrggbbaa` and HSV notations.
+const NAMED_COLORS: &[(&str, Color32)] = &[
+    ("black", Color32::BLACK),
+    ("white", Color32::WHITE),
+    ("transparent", Color32::TRANSPARENT),
+    ("none", Color32::TRANSPARENT),
+    ("red", Color32::from_rgb(255, 0, 0)),
+    ("green", Color32::from_rgb(0, 255, 0)),
+    ("blue", Color32::from_rgb(0, 0, 255)),
+    ("yellow", Color32::from_rgb(255, 255, 0)),
+    ("cyan", Color32::from_rgb(0, 255, 255)),
+    ("magenta", Color32::from_rgb(255, 0, 255)),
+    ("orange", Color32::from_rgb(255, 165, 0)),
+    ("purple", Color32::from_rgb(160, 32, 240)),
+    ("brown", Color32::from_rgb(165, 42, 42)),
+    ("pink", Color32::from_rgb(255, 192, 203)),
+    ("gold", Color32::from_rgb(255, 215, 0)),
+    ("gray", Color32::from_rgb(192, 192, 192)),
+    ("grey", Color32::from_rgb(192, 192, 192)),
+    ("lightgray", Color32::from_rgb(211, 211, 211)),
+    ("lightgrey", Color32::from_rgb(211, 211, 211)),
+    ("darkgray", Color32::from_rgb(169, 169, 169)),
+    ("darkgrey", Color32::from_rgb(169, 169, 169)),
+    ("lightblue", Color32::from_rgb(173, 216, 230)),
+    ("darkblue", Color32::from_rgb(0, 0, 139)),
+    ("navy", Color32::from_rgb(0, 0, 128)),
+    ("lightgreen", Color32::from_rgb(144, 238, 144)),
+    ("darkgreen", Color32::from_rgb(0, 100, 0)),
+    ("darkred", Color32::from_rgb(139, 0, 0)),
+    ("violet", Color32::from_rgb(238, 130, 238)),
+    ("turquoise", Color32::from_rgb(64, 224, 208)),
+    ("salmon", Color32::from_rgb(250, 128, 114)),
+];
+
+/// Parses DOT color. Returns `None` for color names missing in the supported set.
+pub(super) fn parse_color(value: &str) -> Result<Option<Color32>, ()> {
+    // only the first color of the color list is used
+    let value = value.split(':').next().unwrap_or_default();
+    let value = value.split(';').next().unwrap_or_default().trim();
+
+    if value.starts_with('#') {
+        return parse_hex_color(&value.replace(' ', "")).map(Some).ok_or(());
+    }
+
+    if value.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        let hsv = value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f32>().map_err(|_| ()))
+            .collect::<Result<Vec<_>, _>>()?;
+        return match hsv.len() {
+            3 => Ok(Some(Hsva::new(hsv[0], hsv[1], hsv[2], 1.).into())),
+            4 => Ok(Some(Hsva::new(hsv[0], hsv[1], hsv[2], hsv[3]).into())),
+            _ => Err(()),
+        };
+    }
+
+    // color scheme prefix like `/x11/` is ignored
+    let name = value.rsplit('/').next().unwrap_or_default().to_lowercase();
+    Ok(NAMED_COLORS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, color)| *color))
+}
+
+/// Parses node position given in points with the y axis pointing up. Returns location in graph
+/// coordinates and whether the position is fixed with the trailing `!`.
+pub(super) fn parse_pos(value: &str) -> Result<(Vec2, bool), ()> {
+    let (value, pinned) = match value.trim().strip_suffix('!') {
+        Some(value) => (value, true),
+        None => (value.trim(), false),
+    };
+    let coords = value
+        .split(',')
+        .map(|s| s.trim().parse::<f32>().map_err(|_| ()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match coords.len() {
+        // the third coordinate is used by 3D layouts and is ignored
+        2 | 3 => Ok((Vec2::new(coords[0], -coords[1]), pinned)),
+        _ => Err(()),
+    }
+}
+
+/// Formats location in graph coordinates as the DOT position.
+pub(super) fn format_pos(location: Vec2, pinned: bool) -> String {
+    let pin = match pinned {
+        true => "!",
+        false => "",
+    };
+    format!("{},{}{}", location.x, -location.y, pin)
+}
+
+/// Maps DOT shape to the closest [`NodeShape`]. Returns `None` for unsupported shapes.
+pub(super) fn parse_shape(value: &str, rounded: bool) -> Option<NodeShape> {
+    let shape = match value.to_lowercase().as_str() {
+        "circle" | "doublecircle" | "point" => NodeShape::Circle,
+        "ellipse" | "oval" => NodeShape::Ellipse,
+        "box" | "rect" | "rectangle" | "square" | "record" => NodeShape::Rectangle,
+        "mrecord" => NodeShape::RoundedRect,
+        "diamond" | "mdiamond" => NodeShape::Diamond,
+        "triangle" => NodeShape::Triangle,
+        "hexagon" => NodeShape::Hexagon,
+        _ => return None,
+    };
+
+    match (shape, rounded) {
+        (NodeShape::Rectangle, true) => Some(NodeShape::RoundedRect),
+        _ => Some(shape),
+    }
+}
+
+/// Returns DOT shape for the [`NodeShape`] and whether it should be drawn with the `rounded` style.
+pub(super) fn format_shape(shape: NodeShape) -> (&'static str, bool) {
+    match shape {
+        NodeShape::Circle => ("circle", false),
+        NodeShape::Ellipse => ("ellipse", false),
+        NodeShape::Rectangle => ("box", false),
+        NodeShape::RoundedRect => ("box", true),
+        NodeShape::Diamond => ("diamond", false),
+        NodeShape::Triangle => ("triangle", false),
+        NodeShape::Hexagon => ("hexagon", false),
+    }
+}
+
+fn parse_marker(value: &str) -> Option<EdgeMarker> {
+    // modifiers like `o` for open or `l` for the left half are ignored
+    let value = value.trim_start_matches(['o', 'l', 'r']);
+    match value {
+        "normal" | "inv" | "" => Some(EdgeMarker::Triangle),
+        "vee" | "open" => Some(EdgeMarker::OpenArrow),
+        "diamond" | "ediamond" => Some(EdgeMarker::Diamond),
+        "dot" | "odot" => Some(EdgeMarker::Circle),
+        "tee" => Some(EdgeMarker::Bar),
+        "none" => Some(EdgeMarker::None),
+        _ => None,
+    }
+}
+
+pub(super) fn format_marker(marker: EdgeMarker) -> &'static str {
+    match marker {
+        EdgeMarker::None => "none",
+        EdgeMarker::Triangle => "normal",
+        EdgeMarker::OpenArrow => "vee",
+        EdgeMarker::Diamond => "diamond",
+        EdgeMarker::Circle => "dot",
+        EdgeMarker::Bar => "tee",
+    }
+}
+
+/// Replaces DOT escape sequences in the label.
+pub(super) fn unescape_label(label: &str, node_id: &str, graph_id: &str) -> String {
+    let mut res = String::new();
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('N') => res.push_str(node_id),
+            Some('G') => res.push_str(graph_id),
+            Some('n') | Some('l') | Some('r') => res.push('\n'),
+            Some(c) => res.push(c),
+            None => res.push('\\'),
+        };
+    }
+
+    res
+}
+
+/// Escapes the label so that [`unescape_label`] restores it.
+pub(super) fn escape_label(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Quotes the identifier if it is not a plain alphanumeric one or a numeral.
+pub(super) fn quote(id: &str) -> String {
+    let alphanumeric = id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && keyword(id).is_none();
+    let digits = id.strip_prefix('-').unwrap_or(id);
+    let numeral = digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    match alphanumeric || numeral {
+        true => id.to_string(),
+        false => format!("\"{}\"", id.replace('"', "\\\"")),
+    }
+}
+
+fn number(element: &str, attrs: &DotAttributes, name: &str) -> Result<Option<f32>, DotError> {
+    attrs
+        .get(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.)
+                .ok_or_else(|| invalid(element, name, value))
+        })
+        .transpose()
+}
+
+fn color(element: &str, attrs: &DotAttributes, name: &str) -> Result<Option<Color32>, DotError> {
+    match attrs.get(name) {
+        Some(value) => parse_color(value).map_err(|_| invalid(element, name, value)),
+        None => Ok(None),
+    }
+}
+
+fn invalid(element: &str, name: &str, value: &str) -> DotError {
+    DotError::Attribute {
+        element: element.to_string(),
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Applies node attributes to the node style, label and location. Location is not changed
+/// if there is no `pos` attribute.
+pub(super) fn apply_node_attributes<N: Clone>(
+    node: &mut Node<N>,
+    id: &str,
+    graph_id: &str,
+    attrs: &DotAttributes,
+) -> Result<(), DotError> {
+    let element = format!("node `{}`", id);
+    let mut style = node.style().clone();
+
+    let styles = attrs.get("style").map(|s| s.to_lowercase()).unwrap_or_default();
+    if let Some(shape) = attrs.get("shape") {
+        style.shape = parse_shape(shape, styles.contains("rounded")).unwrap_or(style.shape);
+    }
+
+    let width = number(&element, attrs, "width")?;
+    let height = number(&element, attrs, "height")?;
+    match (width, height) {
+        (Some(w), Some(h)) if h > 0. => {
+            style.radius = h * POINTS_PER_INCH / 2.;
+            style.aspect_ratio = w / h;
+        }
+        (Some(size), _) | (None, Some(size)) => style.radius = size * POINTS_PER_INCH / 2.,
+        _ => {}
+    };
+
+    let stroke = color(&element, attrs, "color")?;
+    style.stroke_color = stroke.or(style.stroke_color);
+    style.fill = color(&element, attrs, "fillcolor")?.or(stroke).or(style.fill);
+    style.stroke_width = number(&element, attrs, "penwidth")?.or(style.stroke_width);
+    node.set_style(style);
+
+    let label = attrs.get("label").map(|l| l.as_str()).unwrap_or("\\N");
+    node.set_label(unescape_label(label, id, graph_id));
+
+    if let Some(pos) = attrs.get("pos") {
+        let (location, pinned) = parse_pos(pos).map_err(|_| invalid(&element, "pos", pos))?;
+        node.set_location(location);
+        node.set_pinned(pinned);
+    }
+    if let Some(pin) = attrs.get("pin") {
+        node.set_pinned(pin.to_lowercase() == "true");
+    }
+
+    Ok(())
+}
+
+/// Applies edge attributes to the edge style.
+pub(super) fn apply_edge_attributes<E: Clone>(
+    edge: &mut Edge<E>,
+    element: &str,
+    directed: bool,
+    attrs: &DotAttributes,
+) -> Result<(), DotError> {
+    let mut style = edge.style().clone();
+
+    style.color = color(element, attrs, "color")?.or(style.color);
+    let width = number(element, attrs, "penwidth")?.or(number(element, attrs, "width")?);
+    style.width = width.unwrap_or(style.width);
+
+    let styles = attrs.get("style").map(|s| s.to_lowercase()).unwrap_or_default();
+    style.stroke_pattern = match styles {
+        s if s.contains("dashed") => StrokePattern::Dashed,
+        s if s.contains("dotted") => StrokePattern::Dotted,
+        _ => style.stroke_pattern,
+    };
+
+    let dir = attrs.get("dir").map(|d| d.as_str()).unwrap_or(match directed {
+        true => "forward",
+        false => "none",
+    });
+    let head = attrs.get("arrowhead").and_then(|m| parse_marker(m));
+    let tail = attrs.get("arrowtail").and_then(|m| parse_marker(m));
+    let (start, end) = match dir {
+        "forward" => (EdgeMarker::None, head.unwrap_or(EdgeMarker::Triangle)),
+        "back" => (tail.unwrap_or(EdgeMarker::Triangle), EdgeMarker::None),
+        "both" => (
+            tail.unwrap_or(EdgeMarker::Triangle),
+            head.unwrap_or(EdgeMarker::Triangle),
+        ),
+        _ => (EdgeMarker::None, EdgeMarker::None),
+    };
+    if attrs.contains_key("dir") || head.is_some() || tail.is_some() {
+        style.marker_start = Some(start);
+        style.marker_end = Some(end);
+    }
+
+    edge.set_style(style);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("red"), Ok(Some(Color32::from_rgb(255, 0, 0))));
+        assert_eq!(parse_color("/x11/Blue"), Ok(Some(Color32::from_rgb(0, 0, 255))));
+        assert_eq!(parse_color("#0a0B0c"), Ok(Some(Color32::from_rgb(10, 11, 12))));
+        assert_eq!(
+            parse_color("#ff000080:blue"),
+            Ok(Some(Color32::from_rgba_unmultiplied(255, 0, 0, 128)))
+        );
+        assert_eq!(parse_color("0 0 1"), Ok(Some(Color32::from_rgb(255, 255, 255))));
+        assert_eq!(parse_color("papayawhip"), Ok(None));
+        assert_eq!(parse_color("#12345"), Err(()));
+        assert_eq!(parse_color("#gg0000"), Err(()));
+    }
+
+    #[test]
+    fn test_format_shape() {
+        let shapes = [
+            NodeShape::Circle,
+            NodeShape::Ellipse,
+            NodeShape::Rectangle,
+            NodeShape::RoundedRect,
+            NodeShape::Diamond,
+            NodeShape::Triangle,
+            NodeShape::Hexagon,
+        ];
+        shapes.into_iter().for_each(|shape| {
+            let (name, rounded) = format_shape(shape);
+            assert_eq!(parse_shape(name, rounded), Some(shape));
+        });
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("a_1"), "a_1");
+        assert_eq!(quote("-1.5"), "-1.5");
+        assert_eq!(quote("1a"), "\"1a\"");
+        assert_eq!(quote("node"), "\"node\"");
+        assert_eq!(quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn test_pos() {
+        assert_eq!(parse_pos("1.5,2"), Ok((Vec2::new(1.5, -2.), false)));
+        assert_eq!(parse_pos(" 1,2,3! "), Ok((Vec2::new(1., -2.), true)));
+        assert_eq!(parse_pos("1"), Err(()));
+        assert_eq!(parse_pos("a,b"), Err(()));
+        assert_eq!(parse_pos(&format_pos(Vec2::new(3., 4.), true)), Ok((Vec2::new(3., 4.), true)));
+    }
+
+    #[test]
+    fn test_unescape_label() {
+        assert_eq!(unescape_label("\\N in \\G\\nend\\\\", "a", "g"), "a in g\nend\\");
+        assert_eq!(unescape_label(&escape_label("a\\n\nb"), "", ""), "a\\n\nb");
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt::Write;
+
+use petgraph::{
+    stable_graph::{EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
+
+use crate::{io::color::format_hex_color, Edge, Graph, Node, NodeShape, StrokePattern};
+
+use super::attributes::{
+    escape_label, format_marker, format_pos, format_shape, quote, POINTS_PER_INCH,
+};
+
+/// Writes the graph in the Graphviz DOT language.
+///
+/// Nodes are named by their indices and written with their labels, locations as `pos` in points with
+/// the `y` axis pointing up, shapes, sizes and colors set in the style. Pinned nodes get `!` after
+/// the position. Selected nodes are drawn bold and marked with the `selected=true` attribute.
+/// Edges are written with their colors, widths, stroke patterns and markers set in the style.
+///
+/// Graphviz layout engines recompute positions by default. Use `neato -n` to render the graph
+/// with the exported layout.
+///
+/// # Example
+/// ```
+/// use egui_graphs::{to_dot, to_graph};
+/// use petgraph::stable_graph::StableGraph;
+///
+/// let mut g: StableGraph<(), ()> = StableGraph::new();
+/// let a = g.add_node(());
+/// let b = g.add_node(());
+/// g.add_edge(a, b, ());
+///
+/// let dot = to_dot(&to_graph(&g));
+/// assert!(dot.starts_with("digraph {"));
+/// assert!(dot.contains("0 -> 1"));
+/// ```
+pub fn to_dot<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(g: &Graph<N, E, Ty, Ix>) -> String {
+    to_dot_custom(g, |_, _| vec![], |_, _| vec![])
+}
+
+/// The same as [`to_dot`], but allows to add attributes for nodes and edges, e.g. edge labels from
+/// the edge data. Added attributes override the ones derived from the elements.
+pub fn to_dot_custom<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &Graph<N, E, Ty, Ix>,
+    node_attributes: impl Fn(NodeIndex<Ix>, &Node<N, Ix>) -> Vec<(String, String)>,
+    edge_attributes: impl Fn(EdgeIndex<Ix>, &Edge<E, Ix>) -> Vec<(String, String)>,
+) -> String {
+    let (kind, op) = match g.is_directed() {
+        true => ("digraph", "->"),
+        false => ("graph", "--"),
+    };
+
+    let mut res = format!("{} {{\n", kind);
+    g.nodes_iter().for_each(|(idx, n)| {
+        let attrs = merge(node_dot_attributes(n), node_attributes(idx, n));
+        writeln!(res, "    {}{};", idx.index(), format_attributes(&attrs)).unwrap();
+    });
+    g.edges_iter().for_each(|(idx, e)| {
+        let (start, end) = g.edge_endpoints(idx).unwrap();
+        let attrs = merge(edge_dot_attributes(e, g.is_directed()), edge_attributes(idx, e));
+        writeln!(
+            res,
+            "    {} {} {}{};",
+            start.index(),
+            op,
+            end.index(),
+            format_attributes(&attrs)
+        )
+        .unwrap();
+    });
+    res.push_str("}\n");
+
+    res
+}
+
+fn node_dot_attributes<N: Clone, Ix: IndexType>(n: &Node<N, Ix>) -> Vec<(String, String)> {
+    let style = n.style();
+    let (shape, rounded) = format_shape(style.shape);
+    let height = 2. * style.radius / POINTS_PER_INCH;
+    let width = match style.shape {
+        NodeShape::Circle => height,
+        _ => height * style.aspect_ratio,
+    };
+
+    let mut styles = vec![];
+    if rounded {
+        styles.push("rounded");
+    }
+    if style.fill.is_some() {
+        styles.push("filled");
+    }
+    if n.selected() {
+        styles.push("bold");
+    }
+
+    let mut attrs = vec![
+        ("label", escape_label(n.label())),
+        ("pos", format_pos(n.location(), n.pinned())),
+        ("shape", shape.to_string()),
+        ("width", width.to_string()),
+        ("height", height.to_string()),
+    ];
+    if let Some(color) = style.stroke_color {
+        attrs.push(("color", format_hex_color(color)));
+    }
+    if let Some(fill) = style.fill {
+        attrs.push(("fillcolor", format_hex_color(fill)));
+    }
+    if let Some(width) = style.stroke_width {
+        attrs.push(("penwidth", width.to_string()));
+    }
+    if !styles.is_empty() {
+        attrs.push(("style", styles.join(",")));
+    }
+    if n.selected() {
+        attrs.push(("selected", "true".to_string()));
+    }
+
+    attrs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+fn edge_dot_attributes<E: Clone, Ix: IndexType>(
+    e: &Edge<E, Ix>,
+    directed: bool,
+) -> Vec<(String, String)> {
+    let style = e.style();
+    let mut attrs = vec![("penwidth", style.width.to_string())];
+    if let Some(color) = style.color {
+        attrs.push(("color", format_hex_color(color)));
+    }
+    match style.stroke_pattern {
+        StrokePattern::Dashed | StrokePattern::Custom(_) => {
+            attrs.push(("style", "dashed".to_string()))
+        }
+        StrokePattern::Dotted => attrs.push(("style", "dotted".to_string())),
+        StrokePattern::Solid => {}
+    };
+    if style.marker_start.is_some() || style.marker_end.is_some() {
+        let (start, end) = e.markers(directed);
+        attrs.push(("dir", "both".to_string()));
+        attrs.push(("arrowtail", format_marker(start).to_string()));
+        attrs.push(("arrowhead", format_marker(end).to_string()));
+    }
+
+    attrs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+/// Appends custom attributes replacing the ones with the same names.
+fn merge(mut attrs: Vec<(String, String)>, custom: Vec<(String, String)>) -> Vec<(String, String)> {
+    custom.into_iter().for_each(|(k, v)| {
+        match attrs.iter_mut().find(|(key, _)| *key == k) {
+            Some(attr) => attr.1 = v,
+            None => attrs.push((k, v)),
+        };
+    });
+
+    attrs
+}
+
+fn format_attributes(attrs: &[(String, String)]) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+
+    let list = attrs
+        .iter()
+        .map(|(k, v)| format!("{}={}", quote(k), quote(v)))
+        .collect::<Vec<_>>();
+    format!(" [{}]", list.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Color32, Vec2};
+    use petgraph::{stable_graph::StableGraph, Directed, Undirected};
+
+    use crate::{from_dot, EdgeMarker, StyleEdge, StyleNode};
+
+    use super::*;
+
+    #[test]
+    fn test_to_dot() {
+        let mut g = StableGraph::<Node<()>, Edge<()>, Undirected>::default();
+        let a = g.add_node(
+            Node::new(Vec2::new(1., 2.), ())
+                .with_label("a \"quoted\"\nlabel".to_string())
+                .with_pinned(true),
+        );
+        let b = g.add_node(Node::new(Vec2::new(3.5, -4.), ()).with_label("b".to_string()));
+        g.node_weight_mut(b).unwrap().set_selected(true);
+        g.add_edge(a, b, Edge::new(()));
+
+        let dot = to_dot_custom(&Graph::new(g), |_, _| vec![], |_, _| {
+            vec![("label".to_string(), "e 1".to_string())]
+        });
+
+        assert_eq!(
+            dot,
+            concat!(
+                "graph {\n",
+                "    0 [label=\"a \\\"quoted\\\"\\nlabel\", pos=\"1,-2!\", shape=circle, ",
+                "width=0.1388889, height=0.1388889];\n",
+                "    1 [label=b, pos=\"3.5,4\", shape=circle, width=0.1388889, ",
+                "height=0.1388889, style=bold, selected=true];\n",
+                "    0 -- 1 [penwidth=2, label=\"e 1\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_dot_round_trip() {
+        let mut g = StableGraph::<Node<()>, Edge<()>, Directed>::default();
+        let a = g.add_node(
+            Node::new(Vec2::new(10., 20.), ())
+                .with_label("first\\node".to_string())
+                .with_pinned(true)
+                .with_style(StyleNode {
+                    radius: 18.,
+                    shape: NodeShape::RoundedRect,
+                    aspect_ratio: 2.,
+                    fill: Some(Color32::from_rgb(1, 2, 3)),
+                    stroke_color: Some(Color32::from_rgba_unmultiplied(4, 5, 6, 7)),
+                    stroke_width: Some(3.),
+                    ..Default::default()
+                }),
+        );
+        let b = g.add_node(Node::new(Vec2::new(-5., 0.), ()).with_label("second".to_string()));
+        g.add_edge(
+            a,
+            b,
+            Edge::new(()).with_style(StyleEdge {
+                width: 4.,
+                color: Some(Color32::from_rgb(8, 9, 10)),
+                stroke_pattern: StrokePattern::Dotted,
+                marker_start: Some(EdgeMarker::Diamond),
+                ..Default::default()
+            }),
+        );
+        let g = Graph::new(g);
+
+        let import = from_dot::<(), (), Directed>(&to_dot(&g)).unwrap();
+
+        assert_eq!(import.graph.g.node_count(), 2);
+        g.nodes_iter().for_each(|(idx, n)| {
+            let imported = import.graph.node(import.nodes[&idx.index().to_string()]).unwrap();
+            assert_eq!(imported.label(), n.label());
+            assert_eq!(imported.location(), n.location());
+            assert_eq!(imported.pinned(), n.pinned());
+            assert_eq!(imported.shape(), n.shape());
+            assert!((imported.radius() - n.radius()).abs() < 1e-4);
+            assert!((imported.aspect_ratio() - n.aspect_ratio()).abs() < 1e-4);
+            assert_eq!(imported.style().fill, n.style().fill);
+            assert_eq!(imported.style().stroke_color, n.style().stroke_color);
+            assert_eq!(imported.style().stroke_width, n.style().stroke_width);
+        });
+
+        let (_, e) = import.graph.edges_iter().next().unwrap();
+        assert_eq!(e.width(), 4.);
+        assert_eq!(e.style().color, Some(Color32::from_rgb(8, 9, 10)));
+        assert_eq!(*e.stroke_pattern(), StrokePattern::Dotted);
+        assert_eq!(e.markers(true), (EdgeMarker::Diamond, EdgeMarker::Triangle));
+    }
+}
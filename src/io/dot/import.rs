@@ -0,0 +1,435 @@
+use std::collections::{HashMap, HashSet};
+
+use petgraph::{
+    stable_graph::{NodeIndex, StableGraph},
+    EdgeType,
+};
+
+use crate::{
+    transform::{random_location, DEFAULT_SPAWN_SIZE},
+    Edge, Graph, Node,
+};
+
+use super::{
+    attributes::{apply_edge_attributes, apply_node_attributes},
+    parser::{parse, AttrList, AttrTarget, Operand, Stmt, Subgraph},
+    DotAttributes, DotError,
+};
+
+/// Subgraph of the imported DOT graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DotSubgraph {
+    /// Subgraph name. Anonymous subgraphs like `{ a b }` have no name.
+    pub id: Option<String>,
+    /// Index of the enclosing subgraph in [`DotImport::subgraphs`].
+    pub parent: Option<usize>,
+    /// Graph attributes set inside of the subgraph.
+    pub attributes: DotAttributes,
+    /// Nodes of the subgraph including the nodes of nested subgraphs.
+    pub nodes: Vec<NodeIndex>,
+}
+
+impl DotSubgraph {
+    /// Clusters are the subgraphs with names starting with `cluster`, Graphviz draws them in a separate box.
+    pub fn is_cluster(&self) -> bool {
+        self.id.as_ref().is_some_and(|id| id.starts_with("cluster"))
+    }
+}
+
+/// Result of the DOT import.
+#[derive(Debug, Clone)]
+pub struct DotImport<N: Clone, E: Clone, Ty: EdgeType> {
+    pub graph: Graph<N, E, Ty>,
+    /// Name of the graph.
+    pub id: Option<String>,
+    /// Whether the graph is declared `strict`. Parallel edges of strict graphs are merged.
+    pub strict: bool,
+    /// Graph attributes set outside of subgraphs.
+    pub attributes: DotAttributes,
+    /// Subgraphs in the order of their first appearance.
+    pub subgraphs: Vec<DotSubgraph>,
+    /// Node indices by their DOT names.
+    pub nodes: HashMap<String, NodeIndex>,
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType> DotImport<N, E, Ty> {
+    /// Returns subgraphs containing the node.
+    pub fn node_subgraphs(&self, idx: NodeIndex) -> impl Iterator<Item = &DotSubgraph> {
+        self.subgraphs.iter().filter(move |s| s.nodes.contains(&idx))
+    }
+
+    /// Returns the innermost cluster containing the node.
+    pub fn node_cluster(&self, idx: NodeIndex) -> Option<&DotSubgraph> {
+        self.node_subgraphs(idx).filter(|s| s.is_cluster()).last()
+    }
+}
+
+/// Parses graph in the Graphviz DOT language. Node and edge data are created with [`Default`],
+/// use [`from_dot_custom`] to build them from the DOT names and attributes.
+///
+/// See [`from_dot_custom`] for the supported attributes.
+pub fn from_dot<N: Clone + Default, E: Clone + Default, Ty: EdgeType>(
+    dot: &str,
+) -> Result<DotImport<N, E, Ty>, DotError> {
+    from_dot_custom(dot, |_, _| N::default(), |_| E::default())
+}
+
+/// Parses graph in the Graphviz DOT language building node and edge data with the provided functions.
+/// `node_data` receives node name and attributes, `edge_data` receives edge attributes.
+///
+/// The graph must be a `digraph` for directed `Ty` and a `graph` otherwise. Attributes are mapped onto the
+/// [`Node`] and [`Edge`] style:
+/// * `label` - node label, `\N` is replaced with the node name which is also the default label;
+/// * `pos` - node location in points, `y` axis points up as in Graphviz. Trailing `!` or `pin=true` pins
+///   the node. Nodes without position are placed randomly;
+/// * `shape` - node shape, `style=rounded` makes boxes rounded. Unsupported shapes are ignored;
+/// * `width`, `height` - node size in inches;
+/// * `color`, `fillcolor` - node outline and fill colors, edge color. Unsupported color names are ignored;
+/// * `penwidth` - node outline width and edge width, `width` is accepted for edges as well;
+/// * `style=dashed|dotted`, `dir`, `arrowhead`, `arrowtail` - edge stroke pattern and markers.
+///
+/// # Example
+/// ```
+/// use egui_graphs::from_dot;
+/// use petgraph::Directed;
+///
+/// let dot = r#"digraph {
+///     a [label="start", pos="0,0"]
+///     subgraph cluster_0 { b c }
+///     a -> b -> c [color=red]
+/// }"#;
+/// let import = from_dot::<(), (), Directed>(dot).unwrap();
+///
+/// assert_eq!(import.graph.node_count(), 3);
+/// assert_eq!(import.graph.edge_count(), 2);
+/// assert_eq!(import.graph.node(import.nodes["a"]).unwrap().label(), "start");
+/// assert!(import.node_cluster(import.nodes["b"]).is_some());
+/// ```
+pub fn from_dot_custom<N: Clone, E: Clone, Ty: EdgeType>(
+    dot: &str,
+    node_data: impl Fn(&str, &DotAttributes) -> N,
+    edge_data: impl Fn(&DotAttributes) -> E,
+) -> Result<DotImport<N, E, Ty>, DotError> {
+    let ast = parse(dot)?;
+    if ast.directed != Ty::is_directed() {
+        return Err(DotError::Directedness {
+            directed: ast.directed,
+        });
+    }
+
+    let mut builder = Builder {
+        directed: ast.directed,
+        strict: ast.strict,
+        ..Default::default()
+    };
+    builder.stmts(&ast.stmts, &mut Scope::default());
+
+    let graph_id = ast.id.clone().unwrap_or_default();
+    let mut g = StableGraph::<Node<N>, Edge<E>, Ty>::default();
+    let indices = builder
+        .nodes
+        .iter()
+        .map(|(id, attrs)| {
+            let mut node = Node::new(random_location(DEFAULT_SPAWN_SIZE), node_data(id, attrs));
+            apply_node_attributes(&mut node, id, &graph_id, attrs)?;
+            Ok(g.add_node(node))
+        })
+        .collect::<Result<Vec<_>, DotError>>()?;
+
+    let op = match ast.directed {
+        true => "->",
+        false => "--",
+    };
+    builder
+        .edges
+        .iter()
+        .try_for_each(|(start, end, attrs)| {
+            let element = format!("edge `{} {} {}`", builder.nodes[*start].0, op, builder.nodes[*end].0);
+            let mut edge = Edge::new(edge_data(attrs));
+            apply_edge_attributes(&mut edge, &element, ast.directed, attrs)?;
+            g.add_edge(indices[*start], indices[*end], edge);
+            Ok::<(), DotError>(())
+        })?;
+
+    let subgraphs = builder
+        .subgraphs
+        .into_iter()
+        .map(|s| DotSubgraph {
+            id: s.id,
+            parent: s.parent,
+            attributes: s.attributes,
+            nodes: s.nodes.into_iter().map(|n| indices[n]).collect(),
+        })
+        .collect();
+    let nodes = builder
+        .node_by_id
+        .into_iter()
+        .map(|(id, n)| (id, indices[n]))
+        .collect();
+
+    Ok(DotImport {
+        graph: Graph::new(g),
+        id: ast.id,
+        strict: ast.strict,
+        attributes: builder.attributes,
+        subgraphs,
+        nodes,
+    })
+}
+
+/// Default attributes and the subgraph of the current statement.
+#[derive(Default, Clone)]
+struct Scope {
+    node: DotAttributes,
+    edge: DotAttributes,
+    subgraph: Option<usize>,
+}
+
+#[derive(Default)]
+struct SubgraphEntry {
+    id: Option<String>,
+    parent: Option<usize>,
+    attributes: DotAttributes,
+    nodes: Vec<usize>,
+}
+
+/// Collects nodes, edges and subgraphs with their final attributes before the graph is built.
+#[derive(Default)]
+struct Builder {
+    directed: bool,
+    strict: bool,
+    attributes: DotAttributes,
+    nodes: Vec<(String, DotAttributes)>,
+    node_by_id: HashMap<String, usize>,
+    edges: Vec<(usize, usize, DotAttributes)>,
+    edge_by_ends: HashMap<(usize, usize), usize>,
+    subgraphs: Vec<SubgraphEntry>,
+    membership: HashSet<(usize, usize)>,
+}
+
+impl Builder {
+    fn stmts(&mut self, stmts: &[Stmt], scope: &mut Scope) {
+        stmts.iter().for_each(|stmt| match stmt {
+            Stmt::Node(id, attrs) => {
+                let n = self.node(id, scope);
+                extend(&mut self.nodes[n].1, attrs);
+            }
+            Stmt::Edge(operands, attrs) => self.edge(operands, attrs, scope),
+            Stmt::Attr(AttrTarget::Graph, attrs) => extend(self.graph_attributes(scope), attrs),
+            Stmt::Attr(AttrTarget::Node, attrs) => extend(&mut scope.node, attrs),
+            Stmt::Attr(AttrTarget::Edge, attrs) => extend(&mut scope.edge, attrs),
+            Stmt::Assign(key, value) => {
+                self.graph_attributes(scope)
+                    .insert(key.clone(), value.clone());
+            }
+            Stmt::Subgraph(subgraph) => {
+                self.subgraph(subgraph, scope);
+            }
+        });
+    }
+
+    fn graph_attributes(&mut self, scope: &Scope) -> &mut DotAttributes {
+        match scope.subgraph {
+            Some(s) => &mut self.subgraphs[s].attributes,
+            None => &mut self.attributes,
+        }
+    }
+
+    /// Processes the subgraph and returns its nodes.
+    fn subgraph(&mut self, subgraph: &Subgraph, scope: &Scope) -> Vec<usize> {
+        // subgraphs with the same name are merged
+        let existing = subgraph
+            .id
+            .as_ref()
+            .and_then(|id| self.subgraphs.iter().position(|s| s.id.as_ref() == Some(id)));
+        let idx = existing.unwrap_or_else(|| {
+            self.subgraphs.push(SubgraphEntry {
+                id: subgraph.id.clone(),
+                parent: scope.subgraph,
+                ..Default::default()
+            });
+            self.subgraphs.len() - 1
+        });
+
+        let mut inner = Scope {
+            subgraph: Some(idx),
+            ..scope.clone()
+        };
+        self.stmts(&subgraph.stmts, &mut inner);
+
+        self.subgraphs[idx].nodes.clone()
+    }
+
+    /// Returns the node with the given name creating it with the default attributes of the scope if needed.
+    fn node(&mut self, id: &str, scope: &Scope) -> usize {
+        let n = match self.node_by_id.get(id) {
+            Some(n) => *n,
+            None => {
+                self.nodes.push((id.to_string(), scope.node.clone()));
+                self.node_by_id.insert(id.to_string(), self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+
+        let mut subgraph = scope.subgraph;
+        while let Some(s) = subgraph {
+            if self.membership.insert((s, n)) {
+                self.subgraphs[s].nodes.push(n);
+            }
+            subgraph = self.subgraphs[s].parent;
+        }
+
+        n
+    }
+
+    fn edge(&mut self, operands: &[Operand], attrs: &AttrList, scope: &Scope) {
+        let groups = operands
+            .iter()
+            .map(|operand| match operand {
+                Operand::Node(id) => vec![self.node(id, scope)],
+                Operand::Subgraph(subgraph) => self.subgraph(subgraph, scope),
+            })
+            .collect::<Vec<_>>();
+
+        groups.windows(2).for_each(|pair| {
+            pair[0].iter().for_each(|start| {
+                pair[1].iter().for_each(|end| {
+                    let mut edge_attrs = scope.edge.clone();
+                    extend(&mut edge_attrs, attrs);
+                    self.add_edge(*start, *end, edge_attrs);
+                })
+            })
+        });
+    }
+
+    fn add_edge(&mut self, start: usize, end: usize, attrs: DotAttributes) {
+        if !self.strict {
+            self.edges.push((start, end, attrs));
+            return;
+        }
+
+        let key = match self.directed || start <= end {
+            true => (start, end),
+            false => (end, start),
+        };
+        match self.edge_by_ends.get(&key) {
+            Some(e) => self.edges[*e].2.extend(attrs),
+            None => {
+                self.edge_by_ends.insert(key, self.edges.len());
+                self.edges.push((start, end, attrs));
+            }
+        };
+    }
+}
+
+fn extend(target: &mut DotAttributes, attrs: &AttrList) {
+    target.extend(attrs.iter().cloned());
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Color32, Vec2};
+    use petgraph::{Directed, Undirected};
+
+    use crate::{EdgeMarker, NodeShape, StrokePattern};
+
+    use super::*;
+
+    #[test]
+    fn test_from_dot_attributes() {
+        let dot = r##"digraph G {
+            node [shape=box, style=rounded]
+            a [label="\N of \G", pos="10,20!", color=red, fillcolor="#00ff00", width=1, height=0.5]
+            b [shape=ellipse, penwidth=3]
+            a -> b [color=blue, penwidth=2, style=dashed, dir=both, arrowtail=dot]
+        }"##;
+
+        let import = from_dot::<(), (), Directed>(dot).unwrap();
+        let a = import.graph.node(import.nodes["a"]).unwrap();
+        assert_eq!(a.label(), "a of G");
+        assert_eq!(a.location(), Vec2::new(10., -20.));
+        assert!(a.pinned());
+        assert_eq!(a.shape(), NodeShape::RoundedRect);
+        assert_eq!(a.radius(), 18.);
+        assert_eq!(a.aspect_ratio(), 2.);
+        assert_eq!(a.style().stroke_color, Some(Color32::from_rgb(255, 0, 0)));
+        assert_eq!(a.style().fill, Some(Color32::from_rgb(0, 255, 0)));
+
+        let b = import.graph.node(import.nodes["b"]).unwrap();
+        assert_eq!(b.label(), "b");
+        assert!(!b.pinned());
+        assert_eq!(b.shape(), NodeShape::Ellipse);
+        assert_eq!(b.style().stroke_width, Some(3.));
+
+        let (_, e) = import.graph.edges_iter().next().unwrap();
+        assert_eq!(e.style().color, Some(Color32::from_rgb(0, 0, 255)));
+        assert_eq!(e.width(), 2.);
+        assert_eq!(*e.stroke_pattern(), StrokePattern::Dashed);
+        assert_eq!(e.markers(true), (EdgeMarker::Circle, EdgeMarker::Triangle));
+    }
+
+    #[test]
+    fn test_from_dot_subgraphs() {
+        let dot = r#"graph {
+            subgraph cluster_outer {
+                label = "outer"
+                a
+                subgraph cluster_inner { b -- c }
+            }
+            d -- { a b }
+        }"#;
+
+        let import = from_dot::<(), (), Undirected>(dot).unwrap();
+        assert_eq!(import.graph.g.node_count(), 4);
+        assert_eq!(import.graph.g.edge_count(), 3);
+        assert_eq!(import.subgraphs.len(), 3);
+
+        let (a, b, d) = (import.nodes["a"], import.nodes["b"], import.nodes["d"]);
+        let outer = &import.subgraphs[0];
+        assert!(outer.is_cluster());
+        assert_eq!(outer.attributes["label"], "outer");
+        assert_eq!(outer.nodes.len(), 3);
+        assert_eq!(import.subgraphs[1].parent, Some(0));
+        assert_eq!(import.node_cluster(a).unwrap().id.as_deref(), Some("cluster_outer"));
+        assert_eq!(import.node_cluster(b).unwrap().id.as_deref(), Some("cluster_inner"));
+        assert!(import.node_cluster(d).is_none());
+        assert!(!import.subgraphs[2].is_cluster());
+    }
+
+    #[test]
+    fn test_from_dot_strict_and_data() {
+        let dot = "strict digraph { a -> b [label=x]; a -> b [label=y]; b -> a }";
+
+        let import = from_dot_custom::<String, String, Directed>(
+            dot,
+            |id, _| id.to_uppercase(),
+            |attrs| attrs.get("label").cloned().unwrap_or_default(),
+        );
+        let import = import.unwrap();
+        assert_eq!(import.graph.g.edge_count(), 2);
+        assert_eq!(import.graph.node(import.nodes["a"]).unwrap().data, "A");
+
+        let data = import.graph.edges_iter().map(|(_, e)| e.data.clone());
+        assert_eq!(data.collect::<Vec<_>>(), vec!["y".to_string(), "".to_string()]);
+    }
+
+    #[test]
+    fn test_from_dot_errors() {
+        assert_eq!(
+            from_dot::<(), (), Undirected>("digraph { a -> b }").unwrap_err(),
+            DotError::Directedness { directed: true }
+        );
+        assert_eq!(
+            from_dot::<(), (), Directed>("digraph { a [pos=\"1\"] }")
+                .unwrap_err()
+                .to_string(),
+            "invalid value `1` of attribute `pos` of node `a`"
+        );
+        assert_eq!(
+            from_dot::<(), (), Directed>("digraph { a -> b [penwidth=wide] }")
+                .unwrap_err()
+                .to_string(),
+            "invalid value `wide` of attribute `penwidth` of edge `a -> b`"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use std::{iter::Peekable, str::Chars};
+
+use super::DotError;
+
+/// Position of the token in the source, both line and column start from `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Keyword {
+    Strict,
+    Graph,
+    Digraph,
+    Node,
+    Edge,
+    Subgraph,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Token {
+    Keyword(Keyword),
+    /// Identifier, numeral, quoted or HTML string.
+    Id(String),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Equal,
+    Semicolon,
+    Comma,
+    Colon,
+    Plus,
+    /// `->` edge operator of directed graphs.
+    Arrow,
+    /// `--` edge operator of undirected graphs.
+    Line,
+}
+
+impl Token {
+    /// Returns the token as it is written in the source, used in error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Keyword(k) => format!("keyword `{}`", format!("{:?}", k).to_lowercase()),
+            Token::Id(id) => format!("`{}`", id),
+            Token::LBrace => "`{`".to_string(),
+            Token::RBrace => "`}`".to_string(),
+            Token::LBracket => "`[`".to_string(),
+            Token::RBracket => "`]`".to_string(),
+            Token::Equal => "`=`".to_string(),
+            Token::Semicolon => "`;`".to_string(),
+            Token::Comma => "`,`".to_string(),
+            Token::Colon => "`:`".to_string(),
+            Token::Plus => "`+`".to_string(),
+            Token::Arrow => "`->`".to_string(),
+            Token::Line => "`--`".to_string(),
+        }
+    }
+}
+
+/// Splits the DOT source into tokens skipping whitespaces, comments and preprocessor lines.
+pub(super) fn tokenize(src: &str) -> Result<Vec<(Token, Position)>, DotError> {
+    let mut lexer = Lexer {
+        chars: src.chars().peekable(),
+        pos: Position { line: 1, column: 1 },
+        line_start: true,
+    };
+
+    let mut tokens = vec![];
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    pos: Position,
+    /// Whether only whitespaces were met since the start of the current line.
+    line_start: bool,
+}
+
+impl<'a> Lexer<'a> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        match c {
+            '\n' => {
+                self.pos.line += 1;
+                self.pos.column = 1;
+                self.line_start = true;
+            }
+            _ => {
+                self.pos.column += 1;
+                if !c.is_whitespace() {
+                    self.line_start = false;
+                }
+            }
+        };
+
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next()
+    }
+
+    fn error(&self, pos: Position, message: impl Into<String>) -> DotError {
+        DotError::Syntax {
+            line: pos.line,
+            column: pos.column,
+            message: message.into(),
+        }
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), DotError> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                // lines starting with `#` are treated as C preprocessor output and skipped
+                Some('#') if self.line_start => {
+                    while !matches!(self.peek(), Some('\n') | None) {
+                        self.bump();
+                    }
+                }
+                Some('/') if self.peek_second() == Some('/') => {
+                    while !matches!(self.peek(), Some('\n') | None) {
+                        self.bump();
+                    }
+                }
+                Some('/') if self.peek_second() == Some('*') => {
+                    let start = self.pos;
+                    self.bump();
+                    self.bump();
+                    loop {
+                        match self.bump() {
+                            Some('*') if self.peek() == Some('/') => {
+                                self.bump();
+                                break;
+                            }
+                            Some(_) => {}
+                            None => return Err(self.error(start, "unterminated comment")),
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<(Token, Position)>, DotError> {
+        self.skip_trivia()?;
+
+        let pos = self.pos;
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let token = match c {
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' | '+' => {
+                self.bump();
+                match c {
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    '=' => Token::Equal,
+                    ';' => Token::Semicolon,
+                    ',' => Token::Comma,
+                    ':' => Token::Colon,
+                    _ => Token::Plus,
+                }
+            }
+            '-' if self.peek_second() == Some('>') => {
+                self.bump();
+                self.bump();
+                Token::Arrow
+            }
+            '-' if self.peek_second() == Some('-') => {
+                self.bump();
+                self.bump();
+                Token::Line
+            }
+            '"' => Token::Id(self.quoted(pos)?),
+            '<' => Token::Id(self.html(pos)?),
+            '-' | '.' | '0'..='9' => Token::Id(self.numeral(pos)?),
+            c if is_id_start(c) => {
+                let mut id = String::new();
+                while let Some(c) = self.peek().filter(|c| is_id_start(*c) || c.is_ascii_digit()) {
+                    id.push(c);
+                    self.bump();
+                }
+                match keyword(&id) {
+                    Some(k) => Token::Keyword(k),
+                    None => Token::Id(id),
+                }
+            }
+            c => return Err(self.error(pos, format!("unexpected character `{}`", c))),
+        };
+
+        Ok(Some((token, pos)))
+    }
+
+    fn quoted(&mut self, start: Position) -> Result<String, DotError> {
+        self.bump();
+        let mut res = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(res),
+                Some('\\') => match self.bump() {
+                    Some('"') => res.push('"'),
+                    // escaped line break continues the string on the next line
+                    Some('\n') => {}
+                    Some(c) => {
+                        res.push('\\');
+                        res.push(c);
+                    }
+                    None => break,
+                },
+                Some(c) => res.push(c),
+                None => break,
+            }
+        }
+
+        Err(self.error(start, "unterminated string"))
+    }
+
+    fn html(&mut self, start: Position) -> Result<String, DotError> {
+        self.bump();
+        let mut res = String::new();
+        let mut depth = 1;
+        loop {
+            let c = match self.bump() {
+                Some(c) => c,
+                None => return Err(self.error(start, "unterminated HTML string")),
+            };
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            };
+            if depth == 0 {
+                return Ok(res);
+            }
+            res.push(c);
+        }
+    }
+
+    fn numeral(&mut self, start: Position) -> Result<String, DotError> {
+        let mut res = String::new();
+        if self.peek() == Some('-') {
+            res.push('-');
+            self.bump();
+        }
+
+        let mut dot = false;
+        let mut digits = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '0'..='9' => digits = true,
+                '.' if !dot => dot = true,
+                _ => break,
+            };
+            res.push(c);
+            self.bump();
+        }
+
+        if !digits {
+            return Err(self.error(start, format!("invalid numeral `{}`", res)));
+        }
+        if let Some(c) = self.peek().filter(|c| is_id_start(*c)) {
+            return Err(self.error(
+                self.pos,
+                format!("unexpected character `{}` after numeral `{}`", c, res),
+            ));
+        }
+
+        Ok(res)
+    }
+}
+
+fn is_id_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+pub(super) fn keyword(id: &str) -> Option<Keyword> {
+    match id.to_lowercase().as_str() {
+        "strict" => Some(Keyword::Strict),
+        "graph" => Some(Keyword::Graph),
+        "digraph" => Some(Keyword::Digraph),
+        "node" => Some(Keyword::Node),
+        "edge" => Some(Keyword::Edge),
+        "subgraph" => Some(Keyword::Subgraph),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        tokenize(src).unwrap().into_iter().map(|(t, _)| t).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("DiGraph G { a -> \"b c\" [w=-1.5] } // comment"),
+            vec![
+                Token::Keyword(Keyword::Digraph),
+                Token::Id("G".to_string()),
+                Token::LBrace,
+                Token::Id("a".to_string()),
+                Token::Arrow,
+                Token::Id("b c".to_string()),
+                Token::LBracket,
+                Token::Id("w".to_string()),
+                Token::Equal,
+                Token::Id("-1.5".to_string()),
+                Token::RBracket,
+                Token::RBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_strings_and_comments() {
+        assert_eq!(
+            tokens("# preprocessor\n/* multi\nline */ \"say \\\"hi\\\"\" <<b>bold</b>> a--b"),
+            vec![
+                Token::Id("say \"hi\"".to_string()),
+                Token::Id("<b>bold</b>".to_string()),
+                Token::Id("a".to_string()),
+                Token::Line,
+                Token::Id("b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert_eq!(
+            tokenize("graph {\n  a -- \"b\n}").unwrap_err(),
+            DotError::Syntax {
+                line: 2,
+                column: 8,
+                message: "unterminated string".to_string()
+            }
+        );
+        assert!(tokenize("graph { 1a }").is_err());
+        assert!(tokenize("graph { a @ b }").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! Import and export of graphs in the Graphviz [DOT language](https://graphviz.org/doc/info/lang.html).
+
+mod attributes;
+mod export;
+mod import;
+mod lexer;
+mod parser;
+
+use std::{collections::HashMap, fmt};
+
+pub use self::export::{to_dot, to_dot_custom};
+pub use self::import::{from_dot, from_dot_custom, DotImport, DotSubgraph};
+
+/// DOT attributes by their names.
+pub type DotAttributes = HashMap<String, String>;
+
+/// Error returned when DOT source can not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DotError {
+    /// Source is not valid DOT. Line and column start from `1`.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Source graph is `digraph` while undirected graph is requested or vice versa.
+    Directedness {
+        /// Whether the source graph is directed.
+        directed: bool,
+    },
+    /// Attribute value can not be interpreted, e.g. `pos` is not a pair of numbers.
+    Attribute {
+        /// Element with the attribute like ``node `a` ``.
+        element: String,
+        name: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for DotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DotError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "DOT syntax error at {}:{}: {}", line, column, message),
+            DotError::Directedness { directed } => match directed {
+                true => write!(f, "DOT source is a digraph but an undirected graph is expected"),
+                false => write!(f, "DOT source is an undirected graph but a digraph is expected"),
+            },
+            DotError::Attribute {
+                element,
+                name,
+                value,
+            } => write!(
+                f,
+                "invalid value `{}` of attribute `{}` of {}",
+                value, name, element
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DotError {}
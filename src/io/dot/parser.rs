@@ -0,0 +1,355 @@
+use super::{
+    lexer::{tokenize, Keyword, Position, Token},
+    DotError,
+};
+
+/// List of attributes in the order they are written.
+pub(super) type AttrList = Vec<(String, String)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Ast {
+    pub strict: bool,
+    pub directed: bool,
+    pub id: Option<String>,
+    pub stmts: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Subgraph {
+    pub id: Option<String>,
+    pub stmts: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum AttrTarget {
+    Graph,
+    Node,
+    Edge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Operand {
+    Node(String),
+    Subgraph(Subgraph),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Stmt {
+    Node(String, AttrList),
+    /// Chain of at least two operands connected with edges.
+    Edge(Vec<Operand>, AttrList),
+    /// Default attributes set with `graph`, `node` or `edge` statements.
+    Attr(AttrTarget, AttrList),
+    /// Graph attribute set with `key = value` statement.
+    Assign(String, String),
+    Subgraph(Subgraph),
+}
+
+/// Parses the DOT source into the syntax tree.
+pub(super) fn parse(src: &str) -> Result<Ast, DotError> {
+    let tokens = tokenize(src)?;
+    let last_line = src.rsplit('\n').next().unwrap_or_default();
+    let end = Position {
+        line: src.matches('\n').count() + 1,
+        column: last_line.chars().count() + 1,
+    };
+
+    let mut parser = Parser {
+        tokens,
+        cursor: 0,
+        end,
+        directed: false,
+    };
+    parser.graph()
+}
+
+struct Parser {
+    tokens: Vec<(Token, Position)>,
+    cursor: usize,
+    /// Position right after the last character, used for errors at the end of the input.
+    end: Position,
+    directed: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor).map(|(t, _)| t)
+    }
+
+    fn position(&self) -> Position {
+        match self.tokens.get(self.cursor) {
+            Some((_, pos)) => *pos,
+            None => self.end,
+        }
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.cursor).map(|(t, _)| t.clone());
+        self.cursor += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        match self.peek() == Some(token) {
+            true => {
+                self.cursor += 1;
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn error(&self, expected: &str) -> DotError {
+        let found = match self.peek() {
+            Some(t) => t.describe(),
+            None => "end of input".to_string(),
+        };
+        let pos = self.position();
+        DotError::Syntax {
+            line: pos.line,
+            column: pos.column,
+            message: format!("expected {}, found {}", expected, found),
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), DotError> {
+        match self.eat(&token) {
+            true => Ok(()),
+            false => Err(self.error(&token.describe())),
+        }
+    }
+
+    fn graph(&mut self) -> Result<Ast, DotError> {
+        let strict = self.eat(&Token::Keyword(Keyword::Strict));
+        self.directed = match self.peek() {
+            Some(Token::Keyword(Keyword::Digraph)) => true,
+            Some(Token::Keyword(Keyword::Graph)) => false,
+            _ => return Err(self.error("`graph` or `digraph`")),
+        };
+        self.bump();
+
+        let id = match self.peek() {
+            Some(Token::Id(_)) => Some(self.id()?),
+            _ => None,
+        };
+        let stmts = self.block()?;
+
+        if self.peek().is_some() {
+            return Err(self.error("end of input"));
+        }
+
+        Ok(Ast {
+            strict,
+            directed: self.directed,
+            id,
+            stmts,
+        })
+    }
+
+    /// Parses statements enclosed in braces.
+    fn block(&mut self) -> Result<Vec<Stmt>, DotError> {
+        self.expect(Token::LBrace)?;
+        let mut stmts = vec![];
+        while !self.eat(&Token::RBrace) {
+            stmts.push(self.stmt()?);
+            self.eat(&Token::Semicolon);
+        }
+
+        Ok(stmts)
+    }
+
+    /// Parses identifier including the concatenation of quoted strings with `+`.
+    fn id(&mut self) -> Result<String, DotError> {
+        let mut id = match self.bump() {
+            Some(Token::Id(id)) => id,
+            _ => {
+                self.cursor -= 1;
+                return Err(self.error("identifier"));
+            }
+        };
+        while self.eat(&Token::Plus) {
+            match self.bump() {
+                Some(Token::Id(next)) => id.push_str(&next),
+                _ => {
+                    self.cursor -= 1;
+                    return Err(self.error("identifier after `+`"));
+                }
+            }
+        }
+
+        Ok(id)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, DotError> {
+        let target = match self.peek() {
+            Some(Token::Keyword(Keyword::Graph)) => Some(AttrTarget::Graph),
+            Some(Token::Keyword(Keyword::Node)) => Some(AttrTarget::Node),
+            Some(Token::Keyword(Keyword::Edge)) => Some(AttrTarget::Edge),
+            _ => None,
+        };
+        if let Some(target) = target {
+            self.bump();
+            return Ok(Stmt::Attr(target, self.attr_lists()?));
+        }
+
+        let first = self.operand()?;
+        if let Operand::Node(id) = &first {
+            if self.eat(&Token::Equal) {
+                return Ok(Stmt::Assign(id.clone(), self.id()?));
+            }
+        }
+
+        let (op, wrong_op, expected) = match self.directed {
+            true => (Token::Arrow, Token::Line, "edge operator `->` in digraph"),
+            false => (Token::Line, Token::Arrow, "edge operator `--` in graph"),
+        };
+        let mut operands = vec![first];
+        while self.eat(&op) {
+            operands.push(self.operand()?);
+        }
+        if self.peek() == Some(&wrong_op) {
+            return Err(self.error(expected));
+        }
+
+        let attrs = match self.peek() {
+            Some(Token::LBracket) => self.attr_lists()?,
+            _ => vec![],
+        };
+
+        match operands.len() {
+            1 => match operands.pop().unwrap() {
+                Operand::Node(id) => Ok(Stmt::Node(id, attrs)),
+                Operand::Subgraph(subgraph) => Ok(Stmt::Subgraph(subgraph)),
+            },
+            _ => Ok(Stmt::Edge(operands, attrs)),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, DotError> {
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Subgraph)) => {
+                self.bump();
+                let id = match self.peek() {
+                    Some(Token::Id(_)) => Some(self.id()?),
+                    _ => None,
+                };
+                Ok(Operand::Subgraph(Subgraph {
+                    id,
+                    stmts: self.block()?,
+                }))
+            }
+            Some(Token::LBrace) => Ok(Operand::Subgraph(Subgraph {
+                id: None,
+                stmts: self.block()?,
+            })),
+            Some(Token::Id(_)) => {
+                let id = self.id()?;
+                // ports and compass points do not affect the graph structure and are skipped
+                while self.eat(&Token::Colon) {
+                    self.id()?;
+                }
+                Ok(Operand::Node(id))
+            }
+            _ => Err(self.error("node, subgraph or attribute statement")),
+        }
+    }
+
+    /// Parses one or more attribute lists in brackets.
+    fn attr_lists(&mut self) -> Result<AttrList, DotError> {
+        let mut attrs = vec![];
+        self.expect(Token::LBracket)?;
+        loop {
+            while !self.eat(&Token::RBracket) {
+                let key = self.id()?;
+                self.expect(Token::Equal)?;
+                attrs.push((key, self.id()?));
+                if !self.eat(&Token::Comma) {
+                    self.eat(&Token::Semicolon);
+                }
+            }
+            if !self.eat(&Token::LBracket) {
+                return Ok(attrs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attrs(list: &[(&str, &str)]) -> AttrList {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let ast = parse(
+            r#"strict digraph "my" + "graph" {
+                rankdir = LR
+                node [shape=box]; edge [color=red][width=2]
+                a:p1:n -> { b c } -> subgraph cluster_0 { d } [label="x", weight=1]
+                e
+            }"#,
+        )
+        .unwrap();
+
+        assert!(ast.strict);
+        assert!(ast.directed);
+        assert_eq!(ast.id, Some("mygraph".to_string()));
+        assert_eq!(
+            ast.stmts,
+            vec![
+                Stmt::Assign("rankdir".to_string(), "LR".to_string()),
+                Stmt::Attr(AttrTarget::Node, attrs(&[("shape", "box")])),
+                Stmt::Attr(AttrTarget::Edge, attrs(&[("color", "red"), ("width", "2")])),
+                Stmt::Edge(
+                    vec![
+                        Operand::Node("a".to_string()),
+                        Operand::Subgraph(Subgraph {
+                            id: None,
+                            stmts: vec![
+                                Stmt::Node("b".to_string(), vec![]),
+                                Stmt::Node("c".to_string(), vec![]),
+                            ],
+                        }),
+                        Operand::Subgraph(Subgraph {
+                            id: Some("cluster_0".to_string()),
+                            stmts: vec![Stmt::Node("d".to_string(), vec![])],
+                        }),
+                    ],
+                    attrs(&[("label", "x"), ("weight", "1")]),
+                ),
+                Stmt::Node("e".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |src: &str| parse(src).unwrap_err().to_string();
+
+        assert_eq!(
+            err("graph { a -> b }"),
+            "DOT syntax error at 1:11: expected edge operator `--` in graph, found `->`"
+        );
+        assert_eq!(
+            err("digraph {\n  a -> b [color]\n}"),
+            "DOT syntax error at 2:16: expected `=`, found `]`"
+        );
+        assert_eq!(
+            err("digraph { a -> b"),
+            "DOT syntax error at 1:17: expected node, subgraph or attribute statement, found end of input"
+        );
+        assert_eq!(
+            err("node { a }"),
+            "DOT syntax error at 1:1: expected `graph` or `digraph`, found keyword `node`"
+        );
+        assert_eq!(
+            err("graph { a } b"),
+            "DOT syntax error at 1:13: expected end of input, found `b`"
+        );
+    }
+}
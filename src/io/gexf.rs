@@ -0,0 +1,557 @@
+use std::collections::{BTreeMap, HashMap};
+
+use egui::{Color32, Vec2};
+use petgraph::{
+    stable_graph::{IndexType, StableGraph},
+    EdgeType,
+};
+
+use crate::{
+    transform::{random_location, DEFAULT_SPAWN_SIZE},
+    Edge, Graph, Node, NodeShape, StrokePattern,
+};
+
+use super::xml::{
+    declare, format_attributes, node_index, parse_document, AttributeData, AttributeMap,
+    AttributeValue, Element, XmlGraphError,
+};
+
+/// Declared GEXF attribute.
+struct Declaration {
+    title: String,
+    kind: String,
+    default: Option<String>,
+}
+
+/// Reads a graph in the [GEXF](https://gexf.net) format used by Gephi.
+///
+/// Typed `attvalues` are collected by the attribute titles and passed to [`AttributeData::from_attributes`].
+/// The `label` and `weight` of edges are passed as attributes as well. Node labels, positions, sizes,
+/// colors and shapes and edge colors, thicknesses and shapes are read from the `viz` elements. The `y`
+/// axis of GEXF points up, so it is flipped. Nodes without position are placed randomly. Nodes nested in
+/// other nodes are added to the graph and the nesting is ignored.
+///
+/// `defaultedgetype` of the graph and the `type` attributes of the edges must match `Ty`.
+pub fn from_gexf<N: Clone + AttributeData, E: Clone + AttributeData, Ty: EdgeType>(
+    xml: &str,
+) -> Result<Graph<N, E, Ty>, XmlGraphError> {
+    let root = parse_document(xml)?;
+    if root.name != "gexf" {
+        return Err(XmlGraphError::Format(format!(
+            "expected `gexf` root element, found `{}`",
+            root.name
+        )));
+    }
+
+    let graph = root
+        .child("graph")
+        .ok_or_else(|| XmlGraphError::Format("missing `graph` element".to_string()))?;
+    let directed = graph.attr("defaultedgetype").unwrap_or("undirected") != "undirected";
+    if directed != Ty::is_directed() {
+        return Err(XmlGraphError::Directedness { directed });
+    }
+
+    let node_declarations = declarations(graph, "node")?;
+    let edge_declarations = declarations(graph, "edge")?;
+
+    let mut nodes = vec![];
+    if let Some(container) = graph.child("nodes") {
+        collect_nodes(container, &mut nodes);
+    }
+
+    let mut g = StableGraph::<Node<N>, Edge<E>, Ty>::default();
+    let mut indices = HashMap::new();
+    nodes.into_iter().try_for_each(|element| {
+        let id = element.required_attr("id")?;
+        let attrs = read_attvalues(element, &node_declarations, &format!("node `{}`", id))?;
+
+        let label = element.attr("label").unwrap_or(id).to_string();
+        let mut node = Node::new(random_location(DEFAULT_SPAWN_SIZE), N::from_attributes(&attrs))
+            .with_label(label);
+        apply_node_viz(&mut node, element);
+        indices.insert(id.to_string(), g.add_node(node));
+        Ok::<(), XmlGraphError>(())
+    })?;
+
+    let edges = graph
+        .child("edges")
+        .map(|edges| edges.children_named("edge").collect::<Vec<_>>())
+        .unwrap_or_default();
+    edges.into_iter().try_for_each(|element| {
+        let start = node_index(&indices, element.required_attr("source")?)?;
+        let end = node_index(&indices, element.required_attr("target")?)?;
+        if let Some(kind) = element.attr("type") {
+            if (kind != "undirected") != Ty::is_directed() {
+                return Err(XmlGraphError::Directedness {
+                    directed: kind != "undirected",
+                });
+            }
+        }
+
+        let name = format!(
+            "edge `{}`",
+            element.attr("id").unwrap_or(element.required_attr("source")?)
+        );
+        let mut attrs = read_attvalues(element, &edge_declarations, &name)?;
+        if let Some(label) = element.attr("label") {
+            attrs.insert("label".to_string(), AttributeValue::String(label.to_string()));
+        }
+        if let Some(weight) = element.attr("weight") {
+            let value = AttributeValue::parse("double", weight).ok_or_else(|| {
+                XmlGraphError::Format(format!("invalid weight `{}` of {}", weight, name))
+            })?;
+            attrs.insert("weight".to_string(), value);
+        }
+
+        let mut edge = Edge::new(E::from_attributes(&attrs));
+        apply_edge_viz(&mut edge, element);
+        g.add_edge(start, end, edge);
+        Ok(())
+    })?;
+
+    Ok(Graph::new(g))
+}
+
+/// Writes the graph in the [GEXF](https://gexf.net) format used by Gephi.
+///
+/// Attributes returned by [`AttributeData::to_attributes`] are declared as typed GEXF attributes. Node
+/// labels, locations, radii, fill colors and shapes and edge colors, widths and stroke patterns are
+/// written as the `viz` elements. Rounded rectangles are written as squares, ellipses and hexagons as
+/// discs because GEXF does not have such shapes.
+pub fn to_gexf<N: Clone + AttributeData, E: Clone + AttributeData, Ty: EdgeType, Ix: IndexType>(
+    g: &Graph<N, E, Ty, Ix>,
+) -> String {
+    let node_attrs = g
+        .nodes_iter()
+        .map(|(idx, n)| (idx, n.data.to_attributes()))
+        .collect::<Vec<_>>();
+    let edge_attrs = g
+        .edges_iter()
+        .map(|(idx, e)| (idx, e.data.to_attributes()))
+        .collect::<Vec<_>>();
+    let node_declarations = declare(node_attrs.iter().map(|(_, attrs)| attrs), "");
+    let edge_declarations = declare(edge_attrs.iter().map(|(_, attrs)| attrs), "");
+
+    let mut res = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    res.push_str(
+        "<gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n",
+    );
+    let edge_type = match g.is_directed() {
+        true => "directed",
+        false => "undirected",
+    };
+    res.push_str(&format!(
+        "  <graph defaultedgetype=\"{}\" mode=\"static\">\n",
+        edge_type
+    ));
+
+    [("node", &node_declarations), ("edge", &edge_declarations)]
+        .iter()
+        .filter(|(_, declarations)| !declarations.is_empty())
+        .for_each(|(class, declarations)| {
+            res.push_str(&format!("    <attributes class=\"{}\">\n", class));
+            declarations.iter().for_each(|(title, (id, kind))| {
+                res.push_str(&format!(
+                    "      <attribute{}/>\n",
+                    format_attributes(&[
+                        ("id", id.clone()),
+                        ("title", title.clone()),
+                        ("type", kind.to_string()),
+                    ])
+                ));
+            });
+            res.push_str("    </attributes>\n");
+        });
+
+    res.push_str("    <nodes>\n");
+    node_attrs.iter().for_each(|(idx, attrs)| {
+        let n = g.node(*idx).unwrap();
+        let style = n.style();
+        res.push_str(&format!(
+            "      <node{}>\n",
+            format_attributes(&[("id", idx.index().to_string()), ("label", n.label().to_string())])
+        ));
+        write_attvalues(&mut res, attrs, &node_declarations);
+        if let Some(fill) = style.fill {
+            res.push_str(&format!("        <viz:color{}/>\n", format_color(fill)));
+        }
+        res.push_str(&format!(
+            "        <viz:position x=\"{}\" y=\"{}\" z=\"0\"/>\n",
+            n.location().x,
+            -n.location().y
+        ));
+        res.push_str(&format!("        <viz:size value=\"{}\"/>\n", style.radius));
+        res.push_str(&format!(
+            "        <viz:shape value=\"{}\"/>\n",
+            format_shape(style.shape)
+        ));
+        res.push_str("      </node>\n");
+    });
+    res.push_str("    </nodes>\n");
+
+    res.push_str("    <edges>\n");
+    edge_attrs.iter().for_each(|(idx, attrs)| {
+        let e = g.edge(*idx).unwrap();
+        let (start, end) = g.edge_endpoints(*idx).unwrap();
+        res.push_str(&format!(
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\">\n",
+            idx.index(),
+            start.index(),
+            end.index()
+        ));
+        write_attvalues(&mut res, attrs, &edge_declarations);
+        if let Some(color) = e.style().color {
+            res.push_str(&format!("        <viz:color{}/>\n", format_color(color)));
+        }
+        res.push_str(&format!("        <viz:thickness value=\"{}\"/>\n", e.width()));
+        let shape = match e.stroke_pattern() {
+            StrokePattern::Solid => "solid",
+            StrokePattern::Dashed | StrokePattern::Custom(_) => "dashed",
+            StrokePattern::Dotted => "dotted",
+        };
+        res.push_str(&format!("        <viz:shape value=\"{}\"/>\n", shape));
+        res.push_str("      </edge>\n");
+    });
+    res.push_str("    </edges>\n");
+
+    res.push_str("  </graph>\n</gexf>\n");
+    res
+}
+
+/// Reads attribute declarations of the class by their ids.
+fn declarations(graph: &Element, class: &str) -> Result<HashMap<String, Declaration>, XmlGraphError> {
+    graph
+        .children_named("attributes")
+        .filter(|a| a.attr("class") == Some(class))
+        .flat_map(|a| a.children_named("attribute"))
+        .map(|a| {
+            let id = a.required_attr("id")?.to_string();
+            let title = a.attr("title").unwrap_or(&id).to_string();
+            Ok((
+                id,
+                Declaration {
+                    title,
+                    kind: a.attr("type").unwrap_or("string").to_string(),
+                    default: a.child("default").map(|d| d.text.clone()),
+                },
+            ))
+        })
+        .collect()
+}
+
+fn read_attvalues(
+    element: &Element,
+    declarations: &HashMap<String, Declaration>,
+    name: &str,
+) -> Result<AttributeMap, XmlGraphError> {
+    let mut attrs = declarations
+        .values()
+        .filter_map(|d| {
+            let value = AttributeValue::parse(&d.kind, d.default.as_ref()?)?;
+            Some((d.title.clone(), value))
+        })
+        .collect::<AttributeMap>();
+
+    let attvalues = element
+        .child("attvalues")
+        .map(|a| a.children_named("attvalue").collect::<Vec<_>>())
+        .unwrap_or_default();
+    attvalues.into_iter().try_for_each(|attvalue| {
+        let id = attvalue.required_attr("for")?;
+        // GEXF 1.1 allows to refer to the attributes by their titles
+        let declaration = declarations
+            .get(id)
+            .or_else(|| declarations.values().find(|d| d.title == id))
+            .ok_or_else(|| {
+                XmlGraphError::Format(format!("{} refers to missing attribute `{}`", name, id))
+            })?;
+        let value = attvalue.required_attr("value")?;
+        let parsed = AttributeValue::parse(&declaration.kind, value).ok_or_else(|| {
+            XmlGraphError::Format(format!(
+                "invalid {} value `{}` of attribute `{}` of {}",
+                declaration.kind, value, declaration.title, name
+            ))
+        })?;
+        attrs.insert(declaration.title.clone(), parsed);
+        Ok(())
+    })?;
+
+    Ok(attrs)
+}
+
+fn write_attvalues(
+    res: &mut String,
+    attrs: &AttributeMap,
+    declarations: &BTreeMap<String, (String, &'static str)>,
+) {
+    if attrs.is_empty() {
+        return;
+    }
+
+    res.push_str("        <attvalues>\n");
+    declarations.iter().for_each(|(title, (id, _))| {
+        if let Some(value) = attrs.get(title) {
+            res.push_str(&format!(
+                "          <attvalue{}/>\n",
+                format_attributes(&[("for", id.clone()), ("value", value.to_string())])
+            ));
+        }
+    });
+    res.push_str("        </attvalues>\n");
+}
+
+/// Collects nodes of the container and the nodes nested in them.
+fn collect_nodes<'a>(container: &'a Element, nodes: &mut Vec<&'a Element>) {
+    container.children_named("node").for_each(|node| {
+        nodes.push(node);
+        if let Some(nested) = node.child("nodes") {
+            collect_nodes(nested, nodes);
+        }
+    });
+}
+
+fn float(element: &Element, name: &str) -> Option<f32> {
+    element.attr(name).and_then(|v| v.trim().parse().ok())
+}
+
+fn parse_color(element: &Element) -> Option<Color32> {
+    let channel = |name: &str| element.attr(name).and_then(|v| v.trim().parse::<u8>().ok());
+    let alpha = float(element, "a").unwrap_or(1.).clamp(0., 1.);
+    Some(Color32::from_rgba_unmultiplied(
+        channel("r")?,
+        channel("g")?,
+        channel("b")?,
+        (alpha * 255.).round() as u8,
+    ))
+}
+
+fn format_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    format_attributes(&[
+        ("r", r.to_string()),
+        ("g", g.to_string()),
+        ("b", b.to_string()),
+        ("a", (a as f32 / 255.).to_string()),
+    ])
+}
+
+fn format_shape(shape: NodeShape) -> &'static str {
+    match shape {
+        NodeShape::Circle | NodeShape::Ellipse | NodeShape::Hexagon => "disc",
+        NodeShape::Rectangle | NodeShape::RoundedRect => "square",
+        NodeShape::Diamond => "diamond",
+        NodeShape::Triangle => "triangle",
+    }
+}
+
+fn apply_node_viz<N: Clone>(node: &mut Node<N>, element: &Element) {
+    if let Some(position) = element.child("position") {
+        if let (Some(x), Some(y)) = (float(position, "x"), float(position, "y")) {
+            node.set_location(Vec2::new(x, -y));
+        }
+    }
+
+    let mut style = node.style().clone();
+    if let Some(size) = element.child("size").and_then(|s| float(s, "value")) {
+        style.radius = size;
+    }
+    if let Some(color) = element.child("color").and_then(parse_color) {
+        style.fill = Some(color);
+    }
+    style.shape = match element.child("shape").and_then(|s| s.attr("value")) {
+        Some("disc") => NodeShape::Circle,
+        Some("square") => NodeShape::Rectangle,
+        Some("triangle") => NodeShape::Triangle,
+        Some("diamond") => NodeShape::Diamond,
+        _ => style.shape,
+    };
+    node.set_style(style);
+}
+
+fn apply_edge_viz<E: Clone>(edge: &mut Edge<E>, element: &Element) {
+    let mut style = edge.style().clone();
+    if let Some(color) = element.child("color").and_then(parse_color) {
+        style.color = Some(color);
+    }
+    if let Some(thickness) = element.child("thickness").and_then(|t| float(t, "value")) {
+        style.width = thickness;
+    }
+    style.stroke_pattern = match element.child("shape").and_then(|s| s.attr("value")) {
+        Some("solid") => StrokePattern::Solid,
+        Some("dashed") => StrokePattern::Dashed,
+        Some("dotted") => StrokePattern::Dotted,
+        _ => style.stroke_pattern,
+    };
+    edge.set_style(style);
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{Directed, Undirected};
+
+    use crate::{StyleEdge, StyleNode};
+
+    use super::*;
+
+    #[test]
+    fn test_gexf_round_trip() {
+        let mut g = StableGraph::<Node<AttributeMap>, Edge<AttributeMap>, Undirected>::default();
+        let a = g.add_node(
+            Node::new(
+                Vec2::new(10., 20.),
+                AttributeMap::from([
+                    ("name".to_string(), AttributeValue::String("\"A\"".to_string())),
+                    ("rank".to_string(), AttributeValue::Integer(3)),
+                ]),
+            )
+            .with_label("a & b".to_string())
+            .with_style(StyleNode {
+                radius: 7.,
+                shape: NodeShape::Diamond,
+                fill: Some(Color32::from_rgb(1, 2, 3)),
+                ..Default::default()
+            }),
+        );
+        let b = g.add_node(
+            Node::new(
+                Vec2::new(-5., 0.),
+                AttributeMap::from([("active".to_string(), AttributeValue::Boolean(true))]),
+            )
+            .with_label("b".to_string()),
+        );
+        g.add_edge(
+            a,
+            b,
+            Edge::new(AttributeMap::from([(
+                "weight".to_string(),
+                AttributeValue::Float(2.5),
+            )]))
+            .with_style(StyleEdge {
+                width: 3.,
+                color: Some(Color32::from_rgb(8, 9, 10)),
+                stroke_pattern: StrokePattern::Dotted,
+                ..Default::default()
+            }),
+        );
+        let g = Graph::new(g);
+
+        let imported = from_gexf::<AttributeMap, AttributeMap, Undirected>(&to_gexf(&g)).unwrap();
+
+        assert_eq!(imported.g.node_count(), 2);
+        g.nodes_iter().zip(imported.nodes_iter()).for_each(|((_, n), (_, m))| {
+            assert_eq!(m.data, n.data);
+            assert_eq!(m.label(), n.label());
+            assert_eq!(m.location(), n.location());
+            assert_eq!(m.shape(), n.shape());
+            assert_eq!(m.radius(), n.radius());
+            assert_eq!(m.style().fill, n.style().fill);
+        });
+
+        let (_, e) = imported.edges_iter().next().unwrap();
+        assert_eq!(e.data["weight"], AttributeValue::Float(2.5));
+        assert_eq!(e.width(), 3.);
+        assert_eq!(e.style().color, Some(Color32::from_rgb(8, 9, 10)));
+        assert_eq!(*e.stroke_pattern(), StrokePattern::Dotted);
+    }
+
+    #[test]
+    fn test_from_gexf_gephi() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gexf xmlns="http://www.gexf.net/1.2draft" xmlns:viz="http://www.gexf.net/1.2draft/viz" version="1.2">
+              <graph defaultedgetype="directed">
+                <attributes class="node">
+                  <attribute id="0" title="modularity" type="integer"><default>1</default></attribute>
+                </attributes>
+                <nodes>
+                  <node id="a" label="Alpha">
+                    <attvalues><attvalue for="modularity" value="4"/></attvalues>
+                    <viz:position x="1.5" y="-2.0" z="0.0"/>
+                    <viz:color r="255" g="0" b="0" a="0.5"/>
+                  </node>
+                  <node id="b"/>
+                </nodes>
+                <edges>
+                  <edge id="0" source="a" target="b" label="ab" weight="2"/>
+                </edges>
+              </graph>
+            </gexf>"#;
+
+        let g = from_gexf::<AttributeMap, AttributeMap, Directed>(xml).unwrap();
+
+        let nodes = g.nodes_iter().map(|(_, n)| n).collect::<Vec<_>>();
+        assert_eq!(nodes[0].label(), "Alpha");
+        assert_eq!(nodes[0].location(), Vec2::new(1.5, 2.));
+        assert_eq!(nodes[0].data["modularity"], AttributeValue::Integer(4));
+        assert_eq!(
+            nodes[0].style().fill,
+            Some(Color32::from_rgba_unmultiplied(255, 0, 0, 128))
+        );
+        assert_eq!(nodes[1].label(), "b");
+        assert_eq!(nodes[1].data["modularity"], AttributeValue::Integer(1));
+
+        let (_, e) = g.edges_iter().next().unwrap();
+        assert_eq!(e.data["label"], AttributeValue::String("ab".to_string()));
+        assert_eq!(e.data["weight"], AttributeValue::Float(2.));
+    }
+
+    #[test]
+    fn test_from_gexf_errors() {
+        let graph = |body: &str| {
+            format!(
+                r#"<gexf><graph defaultedgetype="undirected">{}</graph></gexf>"#,
+                body
+            )
+        };
+
+        assert_eq!(
+            from_gexf::<(), (), Directed>(&graph("")).unwrap_err(),
+            XmlGraphError::Directedness { directed: false }
+        );
+        assert_eq!(
+            from_gexf::<(), (), Undirected>(&graph(
+                r#"<nodes><node id="a"/></nodes><edges><edge source="a" target="b"/></edges>"#
+            ))
+            .unwrap_err()
+            .to_string(),
+            "edge refers to missing node `b`"
+        );
+        assert_eq!(
+            from_gexf::<(), (), Undirected>(&graph(
+                r#"<nodes><node id="a"><attvalues><attvalue for="0" value="1"/></attvalues></node></nodes>"#
+            ))
+            .unwrap_err()
+            .to_string(),
+            "node `a` refers to missing attribute `0`"
+        );
+    }
+
+    #[test]
+    fn test_gexf_mixed_types() {
+        let mut g = StableGraph::<Node<AttributeMap>, Edge<AttributeMap>, Directed>::default();
+        let attrs = |w, tag| AttributeMap::from([("w".to_string(), w), ("tag".to_string(), tag)]);
+        g.add_node(Node::new(
+            Vec2::ZERO,
+            attrs(AttributeValue::Integer(1), AttributeValue::Boolean(true)),
+        ));
+        g.add_node(Node::new(
+            Vec2::ZERO,
+            attrs(AttributeValue::Float(0.5), AttributeValue::Integer(2)),
+        ));
+        let g = Graph::new(g);
+
+        let imported =
+            from_gexf::<AttributeMap, AttributeMap, Directed>(&to_gexf(&g)).unwrap();
+
+        let nodes = imported.nodes_iter().map(|(_, n)| n).collect::<Vec<_>>();
+        assert_eq!(nodes[0].data["w"], AttributeValue::Float(1.));
+        assert_eq!(nodes[1].data["w"], AttributeValue::Float(0.5));
+        assert_eq!(
+            nodes[0].data["tag"],
+            AttributeValue::String("true".to_string())
+        );
+        assert_eq!(
+            nodes[1].data["tag"],
+            AttributeValue::String("2".to_string())
+        );
+    }
+}
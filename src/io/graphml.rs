@@ -0,0 +1,708 @@
+use std::collections::{BTreeMap, HashMap};
+
+use egui::{Color32, Vec2};
+use petgraph::{
+    stable_graph::{IndexType, StableGraph},
+    EdgeType,
+};
+use quick_xml::escape::escape;
+
+use crate::{
+    transform::{random_location, DEFAULT_SPAWN_SIZE},
+    Edge, EdgeMarker, Graph, Node, NodeShape, StrokePattern,
+};
+
+use super::{
+    color::{format_hex_color, parse_hex_color},
+    xml::{
+        declare, format_attributes, node_index, parse_document, AttributeData, AttributeMap,
+        AttributeValue, Element, XmlGraphError,
+    },
+};
+
+/// Id of the key with the yEd node graphics.
+const NODE_GRAPHICS_KEY: &str = "ng";
+
+/// Id of the key with the yEd edge graphics.
+const EDGE_GRAPHICS_KEY: &str = "eg";
+
+/// Declared GraphML key.
+struct Key {
+    name: String,
+    kind: String,
+    /// Value of the `yfiles.type` attribute for the keys with the yEd graphics.
+    yfiles: Option<String>,
+    default: Option<String>,
+}
+
+/// Reads a graph in the [GraphML](http://graphml.graphdrawing.org) format.
+///
+/// Typed `data` values are collected by the key names and passed to [`AttributeData::from_attributes`].
+/// Positions, sizes, shapes, colors and labels are read from the yEd `ShapeNode` and edge graphics.
+/// Keys named `label`, `x`, `y`, `size`, `r`, `g` and `b` as written by Gephi are applied to the style
+/// as well. Nodes without position are placed randomly. Nodes of nested graphs are added to the graph
+/// and the nesting is ignored.
+///
+/// `edgedefault` of the graph and the `directed` attributes of the edges must match `Ty`.
+pub fn from_graphml<N: Clone + AttributeData, E: Clone + AttributeData, Ty: EdgeType>(
+    xml: &str,
+) -> Result<Graph<N, E, Ty>, XmlGraphError> {
+    let root = parse_document(xml)?;
+    if root.name != "graphml" {
+        return Err(XmlGraphError::Format(format!(
+            "expected `graphml` root element, found `{}`",
+            root.name
+        )));
+    }
+
+    let keys = root
+        .children_named("key")
+        .map(|key| {
+            let id = key.required_attr("id")?.to_string();
+            let name = key.attr("attr.name").unwrap_or(&id).to_string();
+            Ok((
+                id,
+                Key {
+                    name,
+                    kind: key.attr("attr.type").unwrap_or("string").to_string(),
+                    yfiles: key.attr("yfiles.type").map(|t| t.to_string()),
+                    default: key.child("default").map(|d| d.text.clone()),
+                },
+            ))
+        })
+        .collect::<Result<HashMap<_, _>, XmlGraphError>>()?;
+    let defaults = |domain: &str| {
+        root.children_named("key")
+            .filter(|key| matches!(key.attr("for"), Some(d) if d == domain || d == "all"))
+            .filter_map(|key| {
+                let key = &keys[key.attr("id")?];
+                let value = AttributeValue::parse(&key.kind, key.default.as_ref()?)?;
+                Some((key.name.clone(), value))
+            })
+            .collect::<AttributeMap>()
+    };
+    let (node_defaults, edge_defaults) = (defaults("node"), defaults("edge"));
+
+    let graph = root
+        .child("graph")
+        .ok_or_else(|| XmlGraphError::Format("missing `graph` element".to_string()))?;
+    let directed = graph.attr("edgedefault").unwrap_or("directed") == "directed";
+    if directed != Ty::is_directed() {
+        return Err(XmlGraphError::Directedness { directed });
+    }
+
+    let (mut nodes, mut edges) = (vec![], vec![]);
+    collect_elements(graph, &mut nodes, &mut edges);
+
+    let mut g = StableGraph::<Node<N>, Edge<E>, Ty>::default();
+    let mut indices = HashMap::new();
+    nodes.into_iter().try_for_each(|element| {
+        let id = element.required_attr("id")?;
+        let mut attrs = node_defaults.clone();
+        let mut visual = Visual::default();
+        element.children_named("data").try_for_each(|data| {
+            read_data(data, &keys, &mut attrs, &mut visual, &format!("node `{}`", id))
+        })?;
+
+        let mut node = Node::new(random_location(DEFAULT_SPAWN_SIZE), N::from_attributes(&attrs))
+            .with_label(id.to_string());
+        visual.apply_to_node(&mut node);
+        indices.insert(id.to_string(), g.add_node(node));
+        Ok::<(), XmlGraphError>(())
+    })?;
+
+    edges.into_iter().try_for_each(|element| {
+        let start = node_index(&indices, element.required_attr("source")?)?;
+        let end = node_index(&indices, element.required_attr("target")?)?;
+        if let Some(edge_directed) = element.attr("directed") {
+            if (edge_directed == "true") != Ty::is_directed() {
+                return Err(XmlGraphError::Directedness {
+                    directed: edge_directed == "true",
+                });
+            }
+        }
+
+        let element_name = format!(
+            "edge `{}`",
+            element.attr("id").unwrap_or(element.required_attr("source")?)
+        );
+        let mut attrs = edge_defaults.clone();
+        let mut visual = Visual::default();
+        element
+            .children_named("data")
+            .try_for_each(|data| read_data(data, &keys, &mut attrs, &mut visual, &element_name))?;
+
+        let mut edge = Edge::new(E::from_attributes(&attrs));
+        visual.apply_to_edge(&mut edge);
+        g.add_edge(start, end, edge);
+        Ok(())
+    })?;
+
+    Ok(Graph::new(g))
+}
+
+/// Writes the graph in the [GraphML](http://graphml.graphdrawing.org) format.
+///
+/// Attributes returned by [`AttributeData::to_attributes`] are declared as typed keys. Node locations,
+/// sizes, shapes, colors and labels together with edge colors, widths, stroke patterns and markers are
+/// written as the yEd graphics, so the graph opens in yEd with the same layout and style.
+pub fn to_graphml<N: Clone + AttributeData, E: Clone + AttributeData, Ty: EdgeType, Ix: IndexType>(
+    g: &Graph<N, E, Ty, Ix>,
+) -> String {
+    let node_attrs = g
+        .nodes_iter()
+        .map(|(idx, n)| (idx, n.data.to_attributes()))
+        .collect::<Vec<_>>();
+    let edge_attrs = g
+        .edges_iter()
+        .map(|(idx, e)| (idx, e.data.to_attributes()))
+        .collect::<Vec<_>>();
+    let node_keys = declare(node_attrs.iter().map(|(_, attrs)| attrs), "dn");
+    let edge_keys = declare(edge_attrs.iter().map(|(_, attrs)| attrs), "de");
+
+    let mut res = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    res.push_str(
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:y=\"http://www.yworks.com/xml/graphml\">\n",
+    );
+    [("node", &node_keys), ("edge", &edge_keys)]
+        .iter()
+        .for_each(|(domain, keys)| {
+            keys.iter().for_each(|(name, (id, kind))| {
+                res.push_str(&format!(
+                    "  <key{}/>\n",
+                    format_attributes(&[
+                        ("id", id.clone()),
+                        ("for", domain.to_string()),
+                        ("attr.name", name.clone()),
+                        ("attr.type", kind.to_string()),
+                    ])
+                ));
+            })
+        });
+    res.push_str(&format!(
+        "  <key id=\"{}\" for=\"node\" yfiles.type=\"nodegraphics\"/>\n",
+        NODE_GRAPHICS_KEY
+    ));
+    res.push_str(&format!(
+        "  <key id=\"{}\" for=\"edge\" yfiles.type=\"edgegraphics\"/>\n",
+        EDGE_GRAPHICS_KEY
+    ));
+
+    let edgedefault = match g.is_directed() {
+        true => "directed",
+        false => "undirected",
+    };
+    res.push_str(&format!("  <graph id=\"G\" edgedefault=\"{}\">\n", edgedefault));
+
+    node_attrs.iter().for_each(|(idx, attrs)| {
+        let n = g.node(*idx).unwrap();
+        res.push_str(&format!("    <node id=\"n{}\">\n", idx.index()));
+        write_data(&mut res, attrs, &node_keys);
+        res.push_str(&format!(
+            "      <data key=\"{}\">{}</data>\n",
+            NODE_GRAPHICS_KEY,
+            node_graphics(n)
+        ));
+        res.push_str("    </node>\n");
+    });
+
+    edge_attrs.iter().for_each(|(idx, attrs)| {
+        let e = g.edge(*idx).unwrap();
+        let (start, end) = g.edge_endpoints(*idx).unwrap();
+        res.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\n",
+            idx.index(),
+            start.index(),
+            end.index()
+        ));
+        write_data(&mut res, attrs, &edge_keys);
+        res.push_str(&format!(
+            "      <data key=\"{}\">{}</data>\n",
+            EDGE_GRAPHICS_KEY,
+            edge_graphics(e, g.is_directed())
+        ));
+        res.push_str("    </edge>\n");
+    });
+
+    res.push_str("  </graph>\n</graphml>\n");
+    res
+}
+
+/// Collects nodes and edges of the graph and of the graphs nested in its nodes.
+fn collect_elements<'a>(graph: &'a Element, nodes: &mut Vec<&'a Element>, edges: &mut Vec<&'a Element>) {
+    graph.children.iter().for_each(|c| match c.name.as_str() {
+        "node" => {
+            nodes.push(c);
+            c.children_named("graph")
+                .for_each(|nested| collect_elements(nested, nodes, edges));
+        }
+        "edge" => edges.push(c),
+        _ => {}
+    });
+}
+
+fn write_data(res: &mut String, attrs: &AttributeMap, keys: &BTreeMap<String, (String, &'static str)>) {
+    keys.iter().for_each(|(name, (id, _))| {
+        if let Some(value) = attrs.get(name) {
+            res.push_str(&format!(
+                "      <data key=\"{}\">{}</data>\n",
+                id,
+                escape_text(&value.to_string())
+            ));
+        }
+    });
+}
+
+fn read_data(
+    data: &Element,
+    keys: &HashMap<String, Key>,
+    attrs: &mut AttributeMap,
+    visual: &mut Visual,
+    element: &str,
+) -> Result<(), XmlGraphError> {
+    let id = data.required_attr("key")?;
+    let key = keys
+        .get(id)
+        .ok_or_else(|| XmlGraphError::Format(format!("{} refers to missing key `{}`", element, id)))?;
+
+    if key.yfiles.is_some() {
+        visual.read_graphics(data);
+        return Ok(());
+    }
+
+    let value = AttributeValue::parse(&key.kind, &data.text).ok_or_else(|| {
+        XmlGraphError::Format(format!(
+            "invalid {} value `{}` of key `{}` of {}",
+            key.kind, data.text, key.name, element
+        ))
+    })?;
+    visual.read_attribute(&key.name, &value);
+    attrs.insert(key.name.clone(), value);
+
+    Ok(())
+}
+
+/// Visual properties read from the yEd graphics or the Gephi attributes.
+#[derive(Default)]
+struct Visual {
+    label: Option<String>,
+    x: Option<f32>,
+    y: Option<f32>,
+    /// Top left corner and size of the node.
+    geometry: Option<(Vec2, Vec2)>,
+    radius: Option<f32>,
+    shape: Option<String>,
+    fill: Option<Color32>,
+    stroke_color: Option<Color32>,
+    stroke_width: Option<f32>,
+    rgb: [Option<u8>; 3],
+    line: Option<String>,
+    arrows: Option<(EdgeMarker, EdgeMarker)>,
+}
+
+impl Visual {
+    fn read_attribute(&mut self, name: &str, value: &AttributeValue) {
+        let number = value.as_f64().map(|v| v as f32);
+        match name {
+            "label" => self.label = Some(value.to_string()),
+            "x" => self.x = number,
+            "y" => self.y = number,
+            "size" => self.radius = number,
+            "r" => self.rgb[0] = number.map(|v| v as u8),
+            "g" => self.rgb[1] = number.map(|v| v as u8),
+            "b" => self.rgb[2] = number.map(|v| v as u8),
+            _ => {}
+        };
+    }
+
+    fn read_graphics(&mut self, data: &Element) {
+        let float = |e: &Element, name: &str| e.attr(name).and_then(|v| v.parse::<f32>().ok());
+
+        if let Some(geometry) = descendant(data, "Geometry") {
+            if let (Some(x), Some(y), Some(w), Some(h)) = (
+                float(geometry, "x"),
+                float(geometry, "y"),
+                float(geometry, "width"),
+                float(geometry, "height"),
+            ) {
+                self.geometry = Some((Vec2::new(x, y), Vec2::new(w, h)));
+            }
+        }
+        if let Some(fill) = descendant(data, "Fill") {
+            if fill.attr("transparent") != Some("true") {
+                self.fill = fill.attr("color").and_then(parse_hex_color);
+            }
+        }
+        if let Some(border) = descendant(data, "BorderStyle") {
+            self.stroke_color = border.attr("color").and_then(parse_hex_color);
+            self.stroke_width = float(border, "width");
+        }
+        if let Some(label) = descendant(data, "NodeLabel") {
+            self.label = Some(label.text.clone());
+        }
+        if let Some(shape) = descendant(data, "Shape") {
+            self.shape = shape.attr("type").map(|t| t.to_string());
+        }
+        if let Some(line) = descendant(data, "LineStyle") {
+            self.stroke_color = line.attr("color").and_then(parse_hex_color);
+            self.stroke_width = float(line, "width");
+            self.line = line.attr("type").map(|t| t.to_string());
+        }
+        if let Some(arrows) = descendant(data, "Arrows") {
+            let marker = |end: &str| parse_arrow(arrows.attr(end).unwrap_or("none"));
+            self.arrows = Some((marker("source"), marker("target")));
+        }
+    }
+
+    fn rgb(&self) -> Option<Color32> {
+        match self.rgb {
+            [Some(r), Some(g), Some(b)] => Some(Color32::from_rgb(r, g, b)),
+            _ => None,
+        }
+    }
+
+    fn apply_to_node<N: Clone>(&self, node: &mut Node<N>) {
+        if let Some(label) = &self.label {
+            node.set_label(label.clone());
+        }
+
+        let mut style = node.style().clone();
+        if let (Some(x), Some(y)) = (self.x, self.y) {
+            node.set_location(Vec2::new(x, y));
+        }
+        if let Some(radius) = self.radius {
+            style.radius = radius;
+        }
+        if let Some((corner, size)) = self.geometry {
+            node.set_location(corner + size / 2.);
+            style.radius = size.y / 2.;
+            if size.y > 0. {
+                style.aspect_ratio = size.x / size.y;
+            }
+        }
+        style.shape = match self.shape.as_deref() {
+            Some("ellipse") if style.aspect_ratio == 1. => NodeShape::Circle,
+            Some("ellipse") => NodeShape::Ellipse,
+            Some("rectangle") => NodeShape::Rectangle,
+            Some("roundrectangle") => NodeShape::RoundedRect,
+            Some("diamond") => NodeShape::Diamond,
+            Some("triangle") => NodeShape::Triangle,
+            Some("hexagon") => NodeShape::Hexagon,
+            _ => style.shape,
+        };
+        style.fill = self.fill.or(self.rgb()).or(style.fill);
+        style.stroke_color = self.stroke_color.or(style.stroke_color);
+        style.stroke_width = self.stroke_width.or(style.stroke_width);
+        node.set_style(style);
+    }
+
+    fn apply_to_edge<E: Clone>(&self, edge: &mut Edge<E>) {
+        let mut style = edge.style().clone();
+        style.color = self.stroke_color.or(self.rgb()).or(style.color);
+        style.width = self.stroke_width.unwrap_or(style.width);
+        style.stroke_pattern = match self.line.as_deref() {
+            Some("dashed") | Some("dashed_dotted") => StrokePattern::Dashed,
+            Some("dotted") => StrokePattern::Dotted,
+            Some("line") => StrokePattern::Solid,
+            _ => style.stroke_pattern,
+        };
+        if let Some((start, end)) = self.arrows {
+            style.marker_start = Some(start);
+            style.marker_end = Some(end);
+        }
+        edge.set_style(style);
+    }
+}
+
+/// Finds the first element with the given name among all descendants in the document order.
+fn descendant<'a>(element: &'a Element, name: &str) -> Option<&'a Element> {
+    element.children.iter().find_map(|c| match c.name == name {
+        true => Some(c),
+        false => descendant(c, name),
+    })
+}
+
+fn escape_text(text: &str) -> String {
+    escape(text).to_string()
+}
+
+fn parse_arrow(arrow: &str) -> EdgeMarker {
+    match arrow {
+        "standard" | "delta" | "white_delta" | "short" | "concave" | "convex" => EdgeMarker::Triangle,
+        "plain" => EdgeMarker::OpenArrow,
+        "diamond" | "white_diamond" => EdgeMarker::Diamond,
+        "circle" | "transparent_circle" => EdgeMarker::Circle,
+        "t_shape" | "dash" => EdgeMarker::Bar,
+        _ => EdgeMarker::None,
+    }
+}
+
+fn format_arrow(marker: EdgeMarker) -> &'static str {
+    match marker {
+        EdgeMarker::None => "none",
+        EdgeMarker::Triangle => "standard",
+        EdgeMarker::OpenArrow => "plain",
+        EdgeMarker::Diamond => "diamond",
+        EdgeMarker::Circle => "circle",
+        EdgeMarker::Bar => "t_shape",
+    }
+}
+
+fn node_graphics<N: Clone, Ix: IndexType>(n: &Node<N, Ix>) -> String {
+    let style = n.style();
+    let half = style.shape.half_size(style.radius, style.aspect_ratio);
+    let corner = n.location() - half;
+    let shape = match style.shape {
+        NodeShape::Circle | NodeShape::Ellipse => "ellipse",
+        NodeShape::Rectangle => "rectangle",
+        NodeShape::RoundedRect => "roundrectangle",
+        NodeShape::Diamond => "diamond",
+        NodeShape::Triangle => "triangle",
+        NodeShape::Hexagon => "hexagon",
+    };
+
+    let mut res = String::from("<y:ShapeNode>");
+    res.push_str(&format!(
+        "<y:Geometry{}/>",
+        format_attributes(&[
+            ("x", corner.x.to_string()),
+            ("y", corner.y.to_string()),
+            ("width", (2. * half.x).to_string()),
+            ("height", (2. * half.y).to_string()),
+        ])
+    ));
+    if let Some(fill) = style.fill {
+        res.push_str(&format!(
+            "<y:Fill color=\"{}\" transparent=\"false\"/>",
+            format_hex_color(fill)
+        ));
+    }
+    let mut border = vec![("type", "line".to_string())];
+    if let Some(color) = style.stroke_color {
+        border.push(("color", format_hex_color(color)));
+    }
+    if let Some(width) = style.stroke_width {
+        border.push(("width", width.to_string()));
+    }
+    res.push_str(&format!("<y:BorderStyle{}/>", format_attributes(&border)));
+    res.push_str(&format!("<y:NodeLabel>{}</y:NodeLabel>", escape_text(n.label())));
+    res.push_str(&format!("<y:Shape type=\"{}\"/>", shape));
+    res.push_str("</y:ShapeNode>");
+
+    res
+}
+
+fn edge_graphics<E: Clone, Ix: IndexType>(e: &Edge<E, Ix>, directed: bool) -> String {
+    let style = e.style();
+    let line = match style.stroke_pattern {
+        StrokePattern::Solid => "line",
+        StrokePattern::Dashed | StrokePattern::Custom(_) => "dashed",
+        StrokePattern::Dotted => "dotted",
+    };
+    let mut line_style = vec![("type", line.to_string()), ("width", style.width.to_string())];
+    if let Some(color) = style.color {
+        line_style.push(("color", format_hex_color(color)));
+    }
+    let (start, end) = e.markers(directed);
+
+    format!(
+        "<y:PolyLineEdge><y:LineStyle{}/><y:Arrows source=\"{}\" target=\"{}\"/></y:PolyLineEdge>",
+        format_attributes(&line_style),
+        format_arrow(start),
+        format_arrow(end)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{Directed, Undirected};
+
+    use crate::{StyleEdge, StyleNode};
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct City {
+        name: String,
+        population: i64,
+        capital: bool,
+    }
+
+    impl AttributeData for City {
+        fn to_attributes(&self) -> AttributeMap {
+            AttributeMap::from([
+                ("name".to_string(), AttributeValue::String(self.name.clone())),
+                ("population".to_string(), AttributeValue::Integer(self.population)),
+                ("capital".to_string(), AttributeValue::Boolean(self.capital)),
+            ])
+        }
+
+        fn from_attributes(attributes: &AttributeMap) -> Self {
+            Self {
+                name: attributes["name"].as_str().unwrap().to_string(),
+                population: attributes["population"].as_i64().unwrap(),
+                capital: attributes["capital"].as_bool().unwrap(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_graphml_round_trip() {
+        let mut g = StableGraph::<Node<City>, Edge<AttributeMap>, Directed>::default();
+        let city = |name: &str, population, capital| City {
+            name: name.to_string(),
+            population,
+            capital,
+        };
+        let a = g.add_node(
+            Node::new(Vec2::new(10., 20.), city("A & B", 100, true))
+                .with_label("<a>".to_string())
+                .with_style(StyleNode {
+                    radius: 8.,
+                    shape: NodeShape::Hexagon,
+                    aspect_ratio: 1.5,
+                    fill: Some(Color32::from_rgb(1, 2, 3)),
+                    stroke_color: Some(Color32::from_rgba_unmultiplied(4, 5, 6, 7)),
+                    stroke_width: Some(2.),
+                    ..Default::default()
+                }),
+        );
+        let b = g.add_node(Node::new(Vec2::new(-5., 0.), city("C", 5, false)).with_label("c".to_string()));
+        g.add_edge(
+            a,
+            b,
+            Edge::new(AttributeMap::from([(
+                "weight".to_string(),
+                AttributeValue::Float(0.5),
+            )]))
+            .with_style(StyleEdge {
+                width: 3.,
+                color: Some(Color32::from_rgb(8, 9, 10)),
+                stroke_pattern: StrokePattern::Dashed,
+                marker_start: Some(EdgeMarker::Circle),
+                ..Default::default()
+            }),
+        );
+        let g = Graph::new(g);
+
+        let imported = from_graphml::<City, AttributeMap, Directed>(&to_graphml(&g)).unwrap();
+
+        assert_eq!(imported.g.node_count(), 2);
+        g.nodes_iter().zip(imported.nodes_iter()).for_each(|((_, n), (_, m))| {
+            assert_eq!(m.data, n.data);
+            assert_eq!(m.label(), n.label());
+            assert_eq!(m.location(), n.location());
+            assert_eq!(m.shape(), n.shape());
+            assert_eq!(m.radius(), n.radius());
+            assert_eq!(m.aspect_ratio(), n.aspect_ratio());
+            assert_eq!(m.style().fill, n.style().fill);
+            assert_eq!(m.style().stroke_color, n.style().stroke_color);
+            assert_eq!(m.style().stroke_width, n.style().stroke_width);
+        });
+
+        let (_, e) = imported.edges_iter().next().unwrap();
+        assert_eq!(e.data["weight"], AttributeValue::Float(0.5));
+        assert_eq!(e.width(), 3.);
+        assert_eq!(e.style().color, Some(Color32::from_rgb(8, 9, 10)));
+        assert_eq!(*e.stroke_pattern(), StrokePattern::Dashed);
+        assert_eq!(e.markers(true), (EdgeMarker::Circle, EdgeMarker::Triangle));
+    }
+
+    #[test]
+    fn test_from_graphml_plain() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+              <key id="d0" for="node" attr.name="color" attr.type="string">
+                <default>yellow</default>
+              </key>
+              <key id="x" for="node" attr.name="x" attr.type="float"/>
+              <key id="y" for="node" attr.name="y" attr.type="float"/>
+              <graph id="G" edgedefault="undirected">
+                <node id="n0"><data key="x">1.5</data><data key="y">2</data></node>
+                <node id="n1">
+                  <data key="d0">green</data>
+                  <graph id="n1:"><node id="n1::n0"/></graph>
+                </node>
+                <edge source="n0" target="n1"/>
+                <edge source="n1" target="n1::n0"/>
+              </graph>
+            </graphml>"#;
+
+        let g = from_graphml::<AttributeMap, (), Undirected>(xml).unwrap();
+
+        assert_eq!(g.g.node_count(), 3);
+        assert_eq!(g.g.edge_count(), 2);
+        let nodes = g.nodes_iter().map(|(_, n)| n).collect::<Vec<_>>();
+        assert_eq!(nodes[0].label(), "n0");
+        assert_eq!(nodes[0].location(), Vec2::new(1.5, 2.));
+        assert_eq!(nodes[0].data["color"], AttributeValue::String("yellow".to_string()));
+        assert_eq!(nodes[1].data["color"], AttributeValue::String("green".to_string()));
+    }
+
+    #[test]
+    fn test_from_graphml_errors() {
+        let graph = |body: &str| {
+            format!(
+                r#"<graphml><key id="d0" for="node" attr.type="int"/>
+                <graph edgedefault="directed">{}</graph></graphml>"#,
+                body
+            )
+        };
+
+        assert_eq!(
+            from_graphml::<(), (), Undirected>(&graph("")).unwrap_err(),
+            XmlGraphError::Directedness { directed: true }
+        );
+        assert_eq!(
+            from_graphml::<(), (), Directed>(&graph(r#"<node id="a"/><edge source="a" target="b"/>"#))
+                .unwrap_err()
+                .to_string(),
+            "edge refers to missing node `b`"
+        );
+        assert_eq!(
+            from_graphml::<(), (), Directed>(&graph(r#"<node id="a"><data key="d0">x</data></node>"#))
+                .unwrap_err()
+                .to_string(),
+            "invalid int value `x` of key `d0` of node `a`"
+        );
+        assert!(matches!(
+            from_graphml::<(), (), Directed>("<graphml><graph>"),
+            Err(XmlGraphError::Xml { .. })
+        ));
+        assert_eq!(
+            from_graphml::<(), (), Directed>("<gexf/>").unwrap_err().to_string(),
+            "expected `graphml` root element, found `gexf`"
+        );
+    }
+
+    #[test]
+    fn test_graphml_mixed_types() {
+        let mut g = StableGraph::<Node<AttributeMap>, Edge<AttributeMap>, Directed>::default();
+        let attrs = |w, tag| AttributeMap::from([("w".to_string(), w), ("tag".to_string(), tag)]);
+        g.add_node(Node::new(
+            Vec2::ZERO,
+            attrs(AttributeValue::Integer(1), AttributeValue::Boolean(true)),
+        ));
+        g.add_node(Node::new(
+            Vec2::ZERO,
+            attrs(AttributeValue::Float(0.5), AttributeValue::Integer(2)),
+        ));
+        let g = Graph::new(g);
+
+        let imported =
+            from_graphml::<AttributeMap, AttributeMap, Directed>(&to_graphml(&g)).unwrap();
+
+        let nodes = imported.nodes_iter().map(|(_, n)| n).collect::<Vec<_>>();
+        assert_eq!(nodes[0].data["w"], AttributeValue::Float(1.));
+        assert_eq!(nodes[1].data["w"], AttributeValue::Float(0.5));
+        assert_eq!(
+            nodes[0].data["tag"],
+            AttributeValue::String("true".to_string())
+        );
+        assert_eq!(
+            nodes[1].data["tag"],
+            AttributeValue::String("2".to_string())
+        );
+    }
+}
@@ -0,0 +1,25 @@
+mod color;
+mod csv;
+mod dot;
+#[cfg(feature = "gexf")]
+mod gexf;
+#[cfg(feature = "graphml")]
+mod graphml;
+#[cfg(any(feature = "graphml", feature = "gexf"))]
+mod xml;
+
+pub(crate) use self::color::format_hex_color;
+pub use self::csv::{
+    from_csv, from_csv_custom, AdjacencyMatrix, CsvColumn, CsvEdge, CsvError, CsvImport,
+    CsvOptions, CsvRecord,
+};
+pub use self::dot::{
+    from_dot, from_dot_custom, to_dot, to_dot_custom, DotAttributes, DotError, DotImport,
+    DotSubgraph,
+};
+#[cfg(feature = "gexf")]
+pub use self::gexf::{from_gexf, to_gexf};
+#[cfg(feature = "graphml")]
+pub use self::graphml::{from_graphml, to_graphml};
+#[cfg(any(feature = "graphml", feature = "gexf"))]
+pub use self::xml::{AttributeData, AttributeMap, AttributeValue, XmlGraphError};
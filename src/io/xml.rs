@@ -0,0 +1,431 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use petgraph::stable_graph::NodeIndex;
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+/// Typed value of a GraphML or GEXF attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Boolean(bool),
+    /// Value of the `int`, `long` and `integer` types.
+    Integer(i64),
+    /// Value of the `float` and `double` types.
+    Float(f64),
+    /// Value of the `string` type and of the types without a dedicated variant.
+    String(String),
+}
+
+impl AttributeValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttributeValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            AttributeValue::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a float. Integers are converted.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AttributeValue::Float(v) => Some(*v),
+            AttributeValue::Integer(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Parses the value of the attribute declared with the given type.
+    pub(crate) fn parse(kind: &str, value: &str) -> Option<Self> {
+        let value = value.trim();
+        match kind {
+            "boolean" => match value {
+                "true" | "1" => Some(AttributeValue::Boolean(true)),
+                "false" | "0" => Some(AttributeValue::Boolean(false)),
+                _ => None,
+            },
+            "int" | "long" | "integer" => value.parse().ok().map(AttributeValue::Integer),
+            "float" | "double" => value.parse().ok().map(AttributeValue::Float),
+            _ => Some(AttributeValue::String(value.to_string())),
+        }
+    }
+
+    /// Returns the type name used in both GraphML and GEXF declarations.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            AttributeValue::Boolean(_) => "boolean",
+            AttributeValue::Integer(_) => "long",
+            AttributeValue::Float(_) => "double",
+            AttributeValue::String(_) => "string",
+        }
+    }
+
+    /// Returns the type name which can hold values of both types. Integers and floats are widened to
+    /// `double`, other conflicting types fall back to `string`.
+    fn merge_kinds(a: &'static str, b: &'static str) -> &'static str {
+        match (a, b) {
+            (a, b) if a == b => a,
+            ("long", "double") | ("double", "long") => "double",
+            _ => "string",
+        }
+    }
+}
+
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeValue::Boolean(v) => write!(f, "{}", v),
+            AttributeValue::Integer(v) => write!(f, "{}", v),
+            AttributeValue::Float(v) => write!(f, "{}", v),
+            AttributeValue::String(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Attribute values by the attribute names.
+pub type AttributeMap = HashMap<String, AttributeValue>;
+
+/// Converts node or edge data to the typed GraphML and GEXF attributes and back.
+///
+/// # Example
+/// ```
+/// use egui_graphs::{AttributeData, AttributeMap, AttributeValue};
+///
+/// #[derive(Clone)]
+/// struct City {
+///     name: String,
+///     population: i64,
+/// }
+///
+/// impl AttributeData for City {
+///     fn to_attributes(&self) -> AttributeMap {
+///         AttributeMap::from([
+///             ("name".to_string(), AttributeValue::String(self.name.clone())),
+///             ("population".to_string(), AttributeValue::Integer(self.population)),
+///         ])
+///     }
+///
+///     fn from_attributes(attributes: &AttributeMap) -> Self {
+///         Self {
+///             name: attributes.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+///             population: attributes.get("population").and_then(|v| v.as_i64()).unwrap_or_default(),
+///         }
+///     }
+/// }
+/// ```
+pub trait AttributeData {
+    /// Returns attributes written for the element.
+    fn to_attributes(&self) -> AttributeMap;
+
+    /// Creates data from the attributes read for the element. Attributes declared with a default value
+    /// are present even if the element does not set them.
+    fn from_attributes(attributes: &AttributeMap) -> Self;
+}
+
+impl AttributeData for () {
+    fn to_attributes(&self) -> AttributeMap {
+        AttributeMap::new()
+    }
+
+    fn from_attributes(_: &AttributeMap) -> Self {}
+}
+
+impl AttributeData for AttributeMap {
+    fn to_attributes(&self) -> AttributeMap {
+        self.clone()
+    }
+
+    fn from_attributes(attributes: &AttributeMap) -> Self {
+        attributes.clone()
+    }
+}
+
+/// Error returned when a GraphML or GEXF document can not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlGraphError {
+    /// Document is not well-formed XML. Position is the byte offset in the document.
+    Xml { position: usize, message: String },
+    /// Document is well-formed but does not follow the format, e.g. an edge refers to a missing node.
+    Format(String),
+    /// Document describes a directed graph while undirected graph is requested or vice versa.
+    Directedness {
+        /// Whether the document graph is directed.
+        directed: bool,
+    },
+}
+
+impl fmt::Display for XmlGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlGraphError::Xml { position, message } => {
+                write!(f, "malformed XML at byte {}: {}", position, message)
+            }
+            XmlGraphError::Format(message) => write!(f, "{}", message),
+            XmlGraphError::Directedness { directed } => match directed {
+                true => write!(f, "document graph is directed but an undirected graph is expected"),
+                false => write!(f, "document graph is undirected but a directed graph is expected"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for XmlGraphError {}
+
+/// XML element with its attributes, children and text. Namespace prefixes are dropped from the names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the attribute or the format error naming the element if it is missing.
+    pub fn required_attr(&self, name: &str) -> Result<&str, XmlGraphError> {
+        self.attr(name).ok_or_else(|| {
+            XmlGraphError::Format(format!(
+                "element `{}` is missing attribute `{}`",
+                self.name, name
+            ))
+        })
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+/// Parses the document and returns its root element.
+pub(crate) fn parse_document(xml: &str) -> Result<Element, XmlGraphError> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut stack: Vec<Element> = vec![];
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| xml_error(&reader, e))?;
+        match event {
+            Event::Start(e) => stack.push(element(&reader, &e)?),
+            Event::Empty(e) => {
+                stack.push(element(&reader, &e)?);
+                if let Some(root) = close(&mut stack) {
+                    return Ok(root);
+                }
+            }
+            Event::End(_) => {
+                if let Some(root) = close(&mut stack) {
+                    return Ok(root);
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape().map_err(|e| xml_error(&reader, e))?;
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&text);
+                }
+            }
+            Event::CData(t) => {
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&String::from_utf8_lossy(&t.into_inner()));
+                }
+            }
+            Event::Eof => {
+                return Err(XmlGraphError::Xml {
+                    position: reader.buffer_position(),
+                    message: "unexpected end of document".to_string(),
+                })
+            }
+            _ => {}
+        };
+    }
+}
+
+fn xml_error(reader: &Reader<&[u8]>, e: quick_xml::Error) -> XmlGraphError {
+    XmlGraphError::Xml {
+        position: reader.buffer_position(),
+        message: e.to_string(),
+    }
+}
+
+fn element(reader: &Reader<&[u8]>, e: &BytesStart) -> Result<Element, XmlGraphError> {
+    let attributes = e
+        .attributes()
+        .map(|attr| {
+            let attr = attr.map_err(|e| xml_error(reader, e.into()))?;
+            let value = attr.unescape_value().map_err(|e| xml_error(reader, e))?;
+            let name = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+            Ok((name, value.to_string()))
+        })
+        .collect::<Result<Vec<_>, XmlGraphError>>()?;
+
+    Ok(Element {
+        name: String::from_utf8_lossy(e.local_name().as_ref()).to_string(),
+        attributes,
+        ..Default::default()
+    })
+}
+
+/// Moves the last open element to its parent. Returns the element if it is the root.
+fn close(stack: &mut Vec<Element>) -> Option<Element> {
+    let element = stack.pop()?;
+    match stack.last_mut() {
+        Some(parent) => {
+            parent.children.push(element);
+            None
+        }
+        None => Some(element),
+    }
+}
+
+/// Returns ids and types of the attributes by their names. Ids are the prefix followed by the index of
+/// the attribute. Type of the attribute is the common type of all its values, see [`AttributeValue::merge_kinds`].
+pub(crate) fn declare<'a>(
+    attrs: impl Iterator<Item = &'a AttributeMap>,
+    prefix: &str,
+) -> BTreeMap<String, (String, &'static str)> {
+    let mut kinds = BTreeMap::<String, &'static str>::new();
+    attrs.for_each(|attrs| {
+        attrs.iter().for_each(|(name, value)| {
+            kinds
+                .entry(name.clone())
+                .and_modify(|kind| *kind = AttributeValue::merge_kinds(kind, value.kind()))
+                .or_insert(value.kind());
+        })
+    });
+
+    kinds
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, kind))| (name, (format!("{}{}", prefix, i), kind)))
+        .collect()
+}
+
+/// Returns the node with the given document id or the format error if there is no such node.
+pub(crate) fn node_index(
+    indices: &HashMap<String, NodeIndex>,
+    id: &str,
+) -> Result<NodeIndex, XmlGraphError> {
+    indices
+        .get(id)
+        .copied()
+        .ok_or_else(|| XmlGraphError::Format(format!("edge refers to missing node `{}`", id)))
+}
+
+/// Formats XML attributes with escaped values, each preceded by a space.
+pub(crate) fn format_attributes(attributes: &[(&str, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!(" {}=\"{}\"", name, escape(value.as_str())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document() {
+        let root = parse_document(
+            r#"<?xml version="1.0"?>
+            <!-- comment -->
+            <g:root xmlns:g="urn:g" a="1 &amp; 2">
+                <child b="x"/>
+                <child>text &lt;here&gt;<![CDATA[ & more]]></child>
+            </g:root>"#,
+        )
+        .unwrap();
+
+        assert_eq!(root.name, "root");
+        assert_eq!(root.attr("a"), Some("1 & 2"));
+        assert_eq!(root.children_named("child").count(), 2);
+        assert_eq!(root.child("child").unwrap().attr("b"), Some("x"));
+        assert_eq!(root.children[1].text, "text <here> & more");
+        assert!(root.required_attr("missing").is_err());
+    }
+
+    #[test]
+    fn test_parse_document_errors() {
+        assert!(matches!(
+            parse_document("<a><b></a>"),
+            Err(XmlGraphError::Xml { .. })
+        ));
+        assert!(matches!(
+            parse_document("<a>"),
+            Err(XmlGraphError::Xml { .. })
+        ));
+    }
+
+    #[test]
+    fn test_attribute_value() {
+        assert_eq!(
+            AttributeValue::parse("boolean", "1"),
+            Some(AttributeValue::Boolean(true))
+        );
+        assert_eq!(AttributeValue::parse("int", "-3"), Some(AttributeValue::Integer(-3)));
+        assert_eq!(AttributeValue::parse("double", "x"), None);
+        assert_eq!(
+            AttributeValue::parse("anyURI", "http://a"),
+            Some(AttributeValue::String("http://a".to_string()))
+        );
+        assert_eq!(AttributeValue::Integer(2).as_f64(), Some(2.));
+        assert_eq!(AttributeValue::Float(2.5).to_string(), "2.5");
+    }
+
+    #[test]
+    fn test_declare_conflicting_kinds() {
+        let attrs = [
+            AttributeMap::from([
+                ("w".to_string(), AttributeValue::Integer(1)),
+                ("x".to_string(), AttributeValue::Boolean(true)),
+                ("y".to_string(), AttributeValue::Integer(2)),
+            ]),
+            AttributeMap::from([
+                ("w".to_string(), AttributeValue::Float(0.5)),
+                ("x".to_string(), AttributeValue::Integer(3)),
+            ]),
+        ];
+
+        let declared = declare(attrs.iter(), "d");
+        assert_eq!(declared["w"], ("d0".to_string(), "double"));
+        assert_eq!(declared["x"], ("d1".to_string(), "string"));
+        assert_eq!(declared["y"], ("d2".to_string(), "long"));
+    }
+
+    #[test]
+    fn test_format_attributes() {
+        assert_eq!(
+            format_attributes(&[("a", "1".to_string()), ("b", "<\"&\">".to_string())]),
+            " a=\"1\" b=\"&lt;&quot;&amp;&quot;&gt;\""
+        );
+    }
+}
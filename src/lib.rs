@@ -1,19 +1,50 @@
+mod alignment;
+mod backends;
 mod computed;
 mod draw;
 mod elements;
+mod export;
 mod graph;
 mod graph_view;
+mod io;
 mod metadata;
 mod settings;
+mod snapshot;
 mod transform;
 
+pub use self::backends::SourceGraph;
 pub use self::computed::ComputedNode;
-pub use self::draw::{default_edges_draw, default_node_draw, FnEdgeDraw, FnNodeDraw, WidgetState, Layers};
-pub use self::elements::{Edge, Node};
-pub use self::graph::Graph;
+pub use self::draw::{
+    default_edges_draw, default_node_detect, default_node_draw, EdgeDrawer, FnEdgeDraw,
+    FnNodeDetect, FnNodeDraw, Layer, Layers, NodeDetector, NodeDrawer, WidgetState,
+};
+pub use self::elements::{
+    Edge, EdgeFlow, EdgeMarker, Node, NodeShape, StrokePattern, StyleEdge, StyleNode,
+};
+pub use self::export::{ExportArea, GraphExport};
+#[cfg(feature = "png")]
+pub use self::export::ExportError;
+pub use self::graph::{Graph, IndexRemap};
 pub use self::graph_view::GraphView;
+pub use self::io::{
+    from_csv, from_csv_custom, AdjacencyMatrix, CsvColumn, CsvEdge, CsvError, CsvImport,
+    CsvOptions, CsvRecord,
+};
+pub use self::io::{
+    from_dot, from_dot_custom, to_dot, to_dot_custom, DotAttributes, DotError, DotImport,
+    DotSubgraph,
+};
+#[cfg(feature = "gexf")]
+pub use self::io::{from_gexf, to_gexf};
+#[cfg(feature = "graphml")]
+pub use self::io::{from_graphml, to_graphml};
+#[cfg(any(feature = "graphml", feature = "gexf"))]
+pub use self::io::{AttributeData, AttributeMap, AttributeValue, XmlGraphError};
 pub use self::metadata::Metadata;
-pub use self::settings::{SettingsInteraction, SettingsNavigation, SettingsStyle};
+pub use self::settings::{
+    EdgeRouting, FitToScreen, SettingsInteraction, SettingsNavigation, SettingsStyle,
+};
+pub use self::snapshot::{Camera, Snapshot, SnapshotError, SNAPSHOT_VERSION};
 pub use self::transform::{
     add_edge, add_edge_custom, add_node, add_node_custom, default_edge_transform,
     default_node_transform, to_graph, to_graph_custom,
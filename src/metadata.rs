@@ -1,8 +1,13 @@
-use egui::{Id, Vec2};
+use egui::{Id, Pos2, Rect, Vec2};
 
 #[cfg(feature = "egui_persistence")]
 use serde::{Deserialize, Serialize};
 
+use crate::settings::{FitToScreen, SettingsNavigation};
+
+/// Share of the remaining distance to the fitted zoom and pan covered by animation every second.
+const FIT_ANIMATION_SPEED: f32 = 8.;
+
 #[cfg_attr(feature = "egui_persistence", derive(Serialize, Deserialize))]
 #[derive(Clone)]
 pub struct Metadata {
@@ -12,6 +17,40 @@ pub struct Metadata {
     pub zoom: f32,
     /// Current pan offset
     pub pan: Vec2,
+    /// Whether fit to screen is requested for the next frame
+    pub fit_requested: bool,
+    /// Graph bounds computed on the last fit to screen
+    pub fit_bounds: Option<Rect>,
+    /// Zoom and pan which animated fit to screen is moving to
+    pub fit_target: Option<(f32, Vec2)>,
+    /// Location of the dragged node moved by the pointer before snapping. It keeps the offset
+    /// between the pointer and the node center during the drag.
+    #[cfg_attr(feature = "egui_persistence", serde(skip))]
+    pub(crate) drag_location: Option<Vec2>,
+
+    /// Selection and node positions stored by the widget on the last frame
+    #[cfg(feature = "egui_persistence")]
+    pub(crate) graph_state: Option<PersistedGraphState>,
+    /// Whether the graph state was loaded from the storage and is not applied to the graph yet
+    #[cfg(feature = "egui_persistence")]
+    #[serde(skip, default = "restore_pending")]
+    pub(crate) restore_pending: bool,
+}
+
+/// Graph state persisted through the egui storage together with the metadata.
+/// Nodes are stored by their indices, so the state does not depend on the graph index type.
+#[cfg(feature = "egui_persistence")]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PersistedGraphState {
+    pub selected: Vec<usize>,
+    /// Empty if node positions are not persisted.
+    pub positions: Vec<(usize, Vec2)>,
+}
+
+/// Deserialized metadata comes from the previous run, so its graph state has to be restored.
+#[cfg(feature = "egui_persistence")]
+fn restore_pending() -> bool {
+    true
 }
 
 impl Default for Metadata {
@@ -20,21 +59,364 @@ impl Default for Metadata {
             first_frame: true,
             zoom: 1.,
             pan: Default::default(),
+            fit_requested: Default::default(),
+            fit_bounds: Default::default(),
+            fit_target: Default::default(),
+            drag_location: Default::default(),
+            #[cfg(feature = "egui_persistence")]
+            graph_state: Default::default(),
+            #[cfg(feature = "egui_persistence")]
+            restore_pending: false,
         }
     }
 }
 
 impl Metadata {
+    /// Returns metadata of the widget with the default id.
     pub fn get(ui: &egui::Ui) -> Self {
-        ui.data_mut(|data| {
-            data.get_persisted::<Metadata>(Id::null())
-                .unwrap_or_default()
-        })
+        Self::get_with_id(ui, Id::null())
+    }
+
+    /// Returns metadata of the widget with the given id, see [`crate::GraphView::with_id`].
+    pub fn get_with_id(ui: &egui::Ui, id: Id) -> Self {
+        ui.data_mut(|data| data.get_persisted::<Metadata>(id).unwrap_or_default())
     }
 
+    /// Converts position in graph coordinates to the screen position.
+    pub fn graph_to_screen(&self, pos: Pos2) -> Pos2 {
+        (pos.to_vec2() * self.zoom + self.pan).to_pos2()
+    }
+
+    /// Converts screen position to the position in graph coordinates.
+    pub fn screen_to_graph(&self, pos: Pos2) -> Pos2 {
+        ((pos.to_vec2() - self.pan) / self.zoom).to_pos2()
+    }
+
+    /// Returns zoom and pan which fit the graph with the given bounds into the rect if it is the first frame,
+    /// fit is requested or the fit to screen mode requires it. Animated fit only sets the target which is
+    /// approached by [`Metadata::animate_fit_to_screen`].
+    pub(crate) fn fit_to_screen(
+        &mut self,
+        navigation: &SettingsNavigation,
+        rect: &Rect,
+        bounds: Rect,
+        dragging: bool,
+    ) -> Option<(f32, Vec2)> {
+        let needs_fit = self.first_frame
+            || self.fit_requested
+            || match navigation.fit_to_screen {
+                FitToScreen::Once => false,
+                FitToScreen::OnBoundsChange => !dragging && self.fit_bounds != Some(bounds),
+                FitToScreen::Always => true,
+            };
+        if !needs_fit {
+            return None;
+        }
+
+        let target = fit_to_screen_target(rect, bounds, navigation.screen_padding);
+        let animated = navigation.fit_to_screen_animated && !self.first_frame;
+
+        self.fit_bounds = Some(bounds);
+        self.fit_requested = false;
+        self.first_frame = false;
+        match animated {
+            true => {
+                self.fit_target = Some(target);
+                None
+            }
+            false => {
+                self.fit_target = None;
+                Some(target)
+            }
+        }
+    }
+
+    /// Returns zoom and pan moved towards the target of the animated fit to screen in `dt` seconds.
+    /// The target is cleared when it is reached.
+    pub(crate) fn animate_fit_to_screen(&mut self, dt: f32) -> Option<(f32, Vec2)> {
+        let (target_zoom, target_pan) = self.fit_target?;
+
+        let t = (dt * FIT_ANIMATION_SPEED).min(1.);
+        let zoom = self.zoom + (target_zoom - self.zoom) * t;
+        let pan = self.pan + (target_pan - self.pan) * t;
+
+        let finished =
+            (target_zoom - zoom).abs() < 1e-3 * target_zoom && (target_pan - pan).length() < 0.5;
+        if finished {
+            self.fit_target = None;
+            return Some((target_zoom, target_pan));
+        }
+
+        Some((zoom, pan))
+    }
+
+    /// Stores metadata of the widget with the default id.
     pub fn store_into_ui(self, ui: &mut egui::Ui) {
+        self.store_into_ui_with_id(ui, Id::null());
+    }
+
+    /// Stores metadata of the widget with the given id. With the `egui_persistence` feature it is
+    /// saved by egui together with the rest of the persisted memory.
+    pub fn store_into_ui_with_id(self, ui: &mut egui::Ui, id: Id) {
         ui.data_mut(|data| {
-            data.insert_persisted(Id::null(), self);
+            data.insert_persisted(id, self);
         });
     }
 }
+
+/// Computes zoom and pan which fit the graph with the given bounds into the rect.
+fn fit_to_screen_target(rect: &Rect, bounds: Rect, padding: f32) -> (f32, Vec2) {
+    // calculate graph dimensions with decorative padding
+    let mut diag = bounds.max - bounds.min;
+
+    // if the graph is empty or consists from one node, use a default size
+    if diag == Vec2::ZERO {
+        diag = Vec2::new(1., 100.);
+    }
+
+    let graph_size = diag * (1. + padding);
+    let (width, height) = (graph_size.x, graph_size.y);
+
+    // calculate canvas dimensions
+    let canvas_size = rect.size();
+    let (canvas_width, canvas_height) = (canvas_size.x, canvas_size.y);
+
+    // calculate zoom factors for x and y to fit the graph inside the canvas
+    let zoom_x = canvas_width / width;
+    let zoom_y = canvas_height / height;
+
+    // choose the minimum of the two zoom factors to avoid distortion
+    let new_zoom = zoom_x.min(zoom_y);
+
+    // calculate the center of the graph
+    let graph_center = (bounds.min.to_vec2() + bounds.max.to_vec2()) / 2.0;
+
+    // the pan aligns the centers of the graph and the canvas
+    let new_pan = rect.center().to_vec2() - graph_center * new_zoom;
+
+    (new_zoom, new_pan)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "egui_persistence")]
+    use egui::{CentralPanel, Context, RawInput};
+    #[cfg(feature = "egui_persistence")]
+    use petgraph::{
+        stable_graph::{NodeIndex, StableGraph},
+        Directed,
+    };
+
+    #[cfg(feature = "egui_persistence")]
+    use crate::{to_graph, Graph, GraphView};
+
+    use super::*;
+
+    /// Screen rect of the widget in the fit to screen tests.
+    const RECT: Rect = Rect {
+        min: Pos2::ZERO,
+        max: Pos2::new(400., 200.),
+    };
+
+    /// Graph bounds which fit the rect with zoom `1` without padding.
+    fn bounds() -> Rect {
+        Rect::from_min_max(Pos2::new(-100., -50.), Pos2::new(100., 50.))
+    }
+
+    fn navigation(mode: FitToScreen) -> SettingsNavigation {
+        SettingsNavigation::new()
+            .with_fit_to_screen(mode)
+            .with_screen_padding(1.)
+    }
+
+    /// Metadata after the fit on the first frame.
+    fn fitted(navigation: &SettingsNavigation) -> Metadata {
+        let mut meta = Metadata::default();
+        let (zoom, pan) = meta
+            .fit_to_screen(navigation, &RECT, bounds(), false)
+            .unwrap();
+        meta.zoom = zoom;
+        meta.pan = pan;
+        meta
+    }
+
+    #[test]
+    fn test_fit_to_screen_once() {
+        let navigation = navigation(FitToScreen::Once);
+        let mut meta = fitted(&navigation);
+
+        assert_eq!(meta.zoom, 1.);
+        assert_eq!(meta.pan, Vec2::new(200., 100.));
+        assert!(!meta.first_frame);
+        assert_eq!(meta.fit_bounds, Some(bounds()));
+
+        let moved = bounds().translate(Vec2::new(50., 0.));
+        assert_eq!(meta.fit_to_screen(&navigation, &RECT, moved, false), None);
+
+        // double click on the empty canvas and `GraphView::request_fit_to_screen` request the fit
+        meta.fit_requested = true;
+        assert_eq!(
+            meta.fit_to_screen(&navigation, &RECT, moved, false),
+            Some((1., Vec2::new(150., 100.)))
+        );
+        assert!(!meta.fit_requested);
+    }
+
+    #[test]
+    fn test_fit_to_screen_on_bounds_change() {
+        let navigation = navigation(FitToScreen::OnBoundsChange);
+        let mut meta = fitted(&navigation);
+
+        assert_eq!(
+            meta.fit_to_screen(&navigation, &RECT, bounds(), false),
+            None
+        );
+
+        // the fit waits for the end of the drag
+        let moved = bounds().expand2(Vec2::new(100., 50.));
+        assert_eq!(meta.fit_to_screen(&navigation, &RECT, moved, true), None);
+        assert_eq!(
+            meta.fit_to_screen(&navigation, &RECT, moved, false),
+            Some((0.5, Vec2::new(200., 100.)))
+        );
+        assert_eq!(meta.fit_bounds, Some(moved));
+        assert_eq!(meta.fit_to_screen(&navigation, &RECT, moved, false), None);
+    }
+
+    #[test]
+    fn test_fit_to_screen_always() {
+        let navigation = navigation(FitToScreen::Always);
+        let mut meta = fitted(&navigation);
+
+        // user zoom and pan are overridden even if the bounds do not change
+        meta.zoom = 3.;
+        meta.pan = Vec2::new(5., 5.);
+        assert_eq!(
+            meta.fit_to_screen(&navigation, &RECT, bounds(), true),
+            Some((1., Vec2::new(200., 100.)))
+        );
+    }
+
+    #[test]
+    fn test_fit_to_screen_animated() {
+        let navigation = navigation(FitToScreen::Once).with_fit_to_screen_animated(true);
+
+        // the first frame is fitted immediately
+        let mut meta = fitted(&navigation);
+        assert_eq!(meta.fit_target, None);
+        assert_eq!(meta.animate_fit_to_screen(0.1), None);
+
+        meta.fit_requested = true;
+        let moved = bounds().translate(Vec2::new(50., 0.));
+        assert_eq!(meta.fit_to_screen(&navigation, &RECT, moved, false), None);
+        assert_eq!(meta.fit_target, Some((1., Vec2::new(150., 100.))));
+
+        // every step covers a share of the remaining distance
+        let (zoom, pan) = meta.animate_fit_to_screen(0.05).unwrap();
+        assert_eq!(zoom, 1.);
+        assert!((pan - Vec2::new(180., 100.)).length() < 1e-3, "{:?}", pan);
+        meta.pan = pan;
+
+        // the target is reached after a long enough step
+        assert_eq!(
+            meta.animate_fit_to_screen(1.),
+            Some((1., Vec2::new(150., 100.)))
+        );
+        assert_eq!(meta.fit_target, None);
+    }
+
+    #[cfg(feature = "egui_persistence")]
+    fn graph() -> Graph<(), (), Directed> {
+        let mut g = StableGraph::new();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        g.add_edge(a, b, ());
+        to_graph(&g)
+    }
+
+    /// Shows the widget for one frame and returns its metadata.
+    #[cfg(feature = "egui_persistence")]
+    fn show(ctx: &Context, g: &mut Graph<(), (), Directed>, id: Id) -> Metadata {
+        let navigation = SettingsNavigation::new().with_fit_to_screen(FitToScreen::Once);
+        let mut meta = None;
+        let _ = ctx.run(RawInput::default(), |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                ui.add(
+                    &mut GraphView::new(g)
+                        .with_id(id)
+                        .with_navigations(&navigation)
+                        .with_persisted_positions(true),
+                );
+                meta = Some(Metadata::get_with_id(ui, id));
+            });
+        });
+        meta.unwrap()
+    }
+
+    #[cfg(feature = "egui_persistence")]
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut meta = Metadata {
+            first_frame: false,
+            zoom: 2.,
+            pan: Vec2::new(3., 4.),
+            ..Default::default()
+        };
+        meta.graph_state = Some(PersistedGraphState {
+            selected: vec![1],
+            positions: vec![(0, Vec2::new(5., 6.))],
+        });
+
+        let json = serde_json::to_string(&meta).unwrap();
+        let loaded: Metadata = serde_json::from_str(&json).unwrap();
+
+        assert!(!loaded.first_frame);
+        assert_eq!(loaded.zoom, 2.);
+        assert_eq!(loaded.pan, Vec2::new(3., 4.));
+        assert_eq!(loaded.graph_state, meta.graph_state);
+        assert!(loaded.restore_pending);
+        assert!(!Metadata::default().restore_pending);
+    }
+
+    #[cfg(feature = "egui_persistence")]
+    #[test]
+    fn test_widget_state_round_trip() {
+        let id = Id::new("graph");
+        let (a, b) = (NodeIndex::new(0), NodeIndex::new(1));
+
+        let ctx = Context::default();
+        let mut g = graph();
+        g.node_mut(a).unwrap().set_location(Vec2::new(10., 20.));
+        g.node_mut(b).unwrap().set_selected(true);
+        show(&ctx, &mut g, id);
+        ctx.memory_mut(|mem| {
+            let mut meta = mem.data.get_persisted::<Metadata>(id).unwrap();
+            meta.zoom = 2.;
+            meta.pan = Vec2::new(3., 4.);
+            mem.data.insert_persisted(id, meta);
+        });
+        show(&ctx, &mut g, id);
+
+        // other widgets do not share the state
+        assert!(ctx.memory_mut(|mem| mem.data.get_persisted::<Metadata>(Id::null()).is_none()));
+
+        let memory = ctx.memory(|mem| serde_json::to_string(mem).unwrap());
+
+        // restart with the graph in the initial state
+        let ctx = Context::default();
+        ctx.memory_mut(|mem| *mem = serde_json::from_str(&memory).unwrap());
+        let mut g = graph();
+        let meta = show(&ctx, &mut g, id);
+
+        assert_eq!(meta.zoom, 2.);
+        assert_eq!(meta.pan, Vec2::new(3., 4.));
+        assert!(!g.node(a).unwrap().selected());
+        assert!(g.node(b).unwrap().selected());
+        assert_eq!(g.node(a).unwrap().location(), Vec2::new(10., 20.));
+
+        // the state is restored only once, later changes are kept
+        g.node_mut(b).unwrap().set_selected(false);
+        show(&ctx, &mut g, id);
+        assert!(!g.node(b).unwrap().selected());
+    }
+}
@@ -1,10 +1,33 @@
+use serde::{Deserialize, Serialize};
+
 /// Represents graph interaction settings.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct SettingsInteraction {
     pub(crate) dragging_enabled: bool,
     pub(crate) clicking_enabled: bool,
     pub(crate) selection_enabled: bool,
     pub(crate) selection_multi_enabled: bool,
+    pub(crate) snap_to_grid_enabled: bool,
+    pub(crate) alignment_guides_enabled: bool,
+    pub(crate) alignment_guides_threshold: f32,
+    pub(crate) pinning_enabled: bool,
+    pub(crate) pinned_dragging_locked: bool,
+}
+
+impl Default for SettingsInteraction {
+    fn default() -> Self {
+        Self {
+            dragging_enabled: Default::default(),
+            clicking_enabled: Default::default(),
+            selection_enabled: Default::default(),
+            selection_multi_enabled: Default::default(),
+            snap_to_grid_enabled: Default::default(),
+            alignment_guides_enabled: Default::default(),
+            alignment_guides_threshold: 5.,
+            pinning_enabled: Default::default(),
+            pinned_dragging_locked: Default::default(),
+        }
+    }
 }
 
 impl SettingsInteraction {
@@ -48,12 +71,70 @@ impl SettingsInteraction {
         self.selection_multi_enabled = enabled;
         self
     }
+
+    /// Snaps dragged node to the nearest intersection of the grid.
+    ///
+    /// Grid spacing is taken from [`SettingsStyle`] even if the grid itself is not drawn.
+    ///
+    /// Default: `false`
+    pub fn with_snap_to_grid_enabled(mut self, enabled: bool) -> Self {
+        self.snap_to_grid_enabled = enabled;
+        self
+    }
+
+    /// Shows guides when the dragged node lines up with the center of any other node
+    /// and snaps the node onto the guide.
+    ///
+    /// Default: `false`
+    pub fn with_alignment_guides_enabled(mut self, enabled: bool) -> Self {
+        self.alignment_guides_enabled = enabled;
+        self
+    }
+
+    /// Distance in screen pixels at which the dragged node is considered aligned with another node.
+    ///
+    /// Default: `5.`
+    pub fn with_alignment_guides_threshold(mut self, threshold: f32) -> Self {
+        self.alignment_guides_threshold = threshold;
+        self
+    }
+
+    /// Pins node when its drag ends. Double click on a node toggles its pinned state.
+    ///
+    /// Default: `false`
+    pub fn with_pinning_enabled(mut self, enabled: bool) -> Self {
+        self.pinning_enabled = enabled;
+        self
+    }
+
+    /// Pinned nodes can not be dragged.
+    ///
+    /// Default: `false`
+    pub fn with_pinned_dragging_locked(mut self, locked: bool) -> Self {
+        self.pinned_dragging_locked = locked;
+        self
+    }
+}
+
+/// Defines when the graph is fitted to the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitToScreen {
+    /// Fits the graph on the first frame and then only on demand: with [`crate::GraphView::request_fit_to_screen`]
+    /// or with double click on the empty canvas if it is enabled.
+    Once,
+    /// Fits the graph every time its bounds change, e.g. when nodes are added, removed or moved.
+    /// Bounds changes caused by dragging a node are applied after the drag ends.
+    OnBoundsChange,
+    /// Fits the graph on every frame. User zoom and pan are overridden.
+    Always,
 }
 
 /// Represents graph navigation settings.
 #[derive(Debug, Clone)]
 pub struct SettingsNavigation {
-    pub(crate) fit_to_screen_enabled: bool,
+    pub(crate) fit_to_screen: FitToScreen,
+    pub(crate) fit_to_screen_animated: bool,
+    pub(crate) fit_to_screen_on_double_click: bool,
     pub(crate) zoom_and_pan_enabled: bool,
     pub(crate) screen_padding: f32,
     pub(crate) zoom_speed: f32,
@@ -64,7 +145,9 @@ impl Default for SettingsNavigation {
         Self {
             screen_padding: 0.3,
             zoom_speed: 0.1,
-            fit_to_screen_enabled: true,
+            fit_to_screen: FitToScreen::Always,
+            fit_to_screen_animated: false,
+            fit_to_screen_on_double_click: false,
             zoom_and_pan_enabled: false,
         }
     }
@@ -79,12 +162,40 @@ impl SettingsNavigation {
     /// Fits the graph to the screen.
     ///
     /// With this enabled, the graph will be scaled and panned to fit the screen on every frame.
+    /// It is a shortcut for [`FitToScreen::Always`] when enabled and [`FitToScreen::Once`] when disabled.
     ///
     /// You can configure the padding around the graph with `screen_padding` setting.
     ///
     /// Default: `true`
     pub fn with_fit_to_screen_enabled(mut self, enabled: bool) -> Self {
-        self.fit_to_screen_enabled = enabled;
+        self.fit_to_screen = match enabled {
+            true => FitToScreen::Always,
+            false => FitToScreen::Once,
+        };
+        self
+    }
+
+    /// Defines when the graph is fitted to the screen.
+    ///
+    /// Default: [`FitToScreen::Always`]
+    pub fn with_fit_to_screen(mut self, fit: FitToScreen) -> Self {
+        self.fit_to_screen = fit;
+        self
+    }
+
+    /// Smoothly moves zoom and pan to the fitted values instead of applying them at once.
+    ///
+    /// Default: `false`
+    pub fn with_fit_to_screen_animated(mut self, animated: bool) -> Self {
+        self.fit_to_screen_animated = animated;
+        self
+    }
+
+    /// Fits the graph to the screen on double click on the empty canvas.
+    ///
+    /// Default: `false`
+    pub fn with_fit_to_screen_on_double_click(mut self, enabled: bool) -> Self {
+        self.fit_to_screen_on_double_click = enabled;
         self
     }
 
@@ -109,14 +220,36 @@ impl SettingsNavigation {
     }
 }
 
+/// Defines how edges between different nodes are routed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeRouting {
+    /// Straight lines. Parallel edges between the same nodes are drawn as curves.
+    Straight,
+    /// Axis aligned polylines with rounded corners going around other nodes.
+    Orthogonal,
+    /// Smooth curves following the shortest paths around other nodes.
+    /// Routes are cached until any node is moved or resized.
+    Spline,
+    /// Edges with similar direction, length and position are pulled together into bundles.
+    /// Bundles are cached until any node is moved or resized and edges are drawn straight while
+    /// a node is dragged.
+    Bundled,
+}
+
 /// `SettingsStyle` stores settings for the style of the graph.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SettingsStyle {
     pub(crate) labels_always: bool,
     pub(crate) edge_radius_weight: f32,
 
     /// Loop size for looped edges.
     pub(crate) edge_looped_size: f32,
+
+    pub(crate) grid_enabled: bool,
+    pub(crate) grid_spacing: f32,
+
+    pub(crate) edge_routing: EdgeRouting,
 }
 
 impl Default for SettingsStyle {
@@ -125,6 +258,9 @@ impl Default for SettingsStyle {
             edge_radius_weight: 1.,
             edge_looped_size: 3.,
             labels_always: Default::default(),
+            grid_enabled: Default::default(),
+            grid_spacing: 20.,
+            edge_routing: EdgeRouting::Straight,
         }
     }
 }
@@ -154,4 +290,28 @@ impl SettingsStyle {
         self.edge_radius_weight = weight;
         self
     }
+
+    /// Draws background grid which scales with zoom.
+    ///
+    /// Default: `false`
+    pub fn with_grid_enabled(mut self, enabled: bool) -> Self {
+        self.grid_enabled = enabled;
+        self
+    }
+
+    /// Distance between grid lines in graph coordinates. Also used for snapping nodes to the grid.
+    ///
+    /// Default: `20.`
+    pub fn with_grid_spacing(mut self, spacing: f32) -> Self {
+        self.grid_spacing = spacing;
+        self
+    }
+
+    /// Defines how edges between different nodes are routed. Looped edges are not affected.
+    ///
+    /// Default: [`EdgeRouting::Straight`]
+    pub fn with_edge_routing(mut self, routing: EdgeRouting) -> Self {
+        self.edge_routing = routing;
+        self
+    }
 }
@@ -0,0 +1,356 @@
+//! Versioned snapshot of the graph together with the view state.
+//!
+//! A snapshot bundles the [`Graph`] with node positions, node and edge styles and selection,
+//! the [`SettingsStyle`] and the [`Camera`]. It is serialized with serde, so any serde format can
+//! be used. `json` and `ron` features add helpers which also migrate snapshots of older versions.
+//!
+//! Versions:
+//! - `0`: the serialized [`Graph`] without any view state;
+//! - `1`: the graph with the style settings and the camera.
+
+use std::fmt::Display;
+
+use egui::{Id, Ui, Vec2};
+use petgraph::{
+    stable_graph::{DefaultIx, IndexType},
+    EdgeType,
+};
+#[cfg(any(feature = "json", feature = "ron"))]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Graph, Metadata, SettingsStyle};
+
+/// Version of the snapshots created by this version of the crate.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Zoom and pan of the widget.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    pub zoom: f32,
+    pub pan: Vec2,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            zoom: 1.,
+            pan: Vec2::ZERO,
+        }
+    }
+}
+
+impl From<&Metadata> for Camera {
+    fn from(meta: &Metadata) -> Self {
+        Self {
+            zoom: meta.zoom,
+            pan: meta.pan,
+        }
+    }
+}
+
+impl Camera {
+    /// Returns the camera of the widget with the default id.
+    pub fn get(ui: &Ui) -> Self {
+        Self::get_with_id(ui, Id::null())
+    }
+
+    /// Returns the camera of the widget with the given id, see [`crate::GraphView::with_id`].
+    pub fn get_with_id(ui: &Ui, id: Id) -> Self {
+        Self::from(&Metadata::get_with_id(ui, id))
+    }
+
+    /// Sets the camera of the widget with the default id. The graph is not fitted to the screen
+    /// on the first frame afterwards, but the camera is still overridden by [`crate::FitToScreen::Always`]
+    /// and [`crate::FitToScreen::OnBoundsChange`] modes.
+    pub fn store_into_ui(self, ui: &mut Ui) {
+        self.store_into_ui_with_id(ui, Id::null());
+    }
+
+    /// The same as [`Camera::store_into_ui`] for the widget with the given id.
+    pub fn store_into_ui_with_id(self, ui: &mut Ui, id: Id) {
+        let mut meta = Metadata::get_with_id(ui, id);
+        self.apply(&mut meta);
+        meta.store_into_ui_with_id(ui, id);
+    }
+
+    /// Sets the camera in the widget metadata.
+    pub fn apply(self, meta: &mut Metadata) {
+        meta.zoom = self.zoom;
+        meta.pan = self.pan;
+        meta.first_frame = false;
+        meta.fit_target = None;
+    }
+}
+
+/// Error of the snapshot loading.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The document can not be parsed or serialized.
+    Format(String),
+    /// The snapshot was created by a newer version of the crate.
+    Version { version: u32 },
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Format(message) => write!(f, "invalid snapshot: {}", message),
+            SnapshotError::Version { version } => write!(
+                f,
+                "unsupported snapshot version {}, the latest supported version is {}",
+                version, SNAPSHOT_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Snapshot of the graph and the view state. See the [module documentation](self) for the versions.
+///
+/// Node positions, styles and selection are stored in the nodes of the graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "N: Serialize, E: Serialize, Ix: Serialize",
+    deserialize = "N: Deserialize<'de>, E: Deserialize<'de>, Ix: Deserialize<'de>"
+))]
+pub struct Snapshot<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType = DefaultIx> {
+    version: u32,
+
+    pub graph: Graph<N, E, Ty, Ix>,
+    pub style: SettingsStyle,
+    /// Camera of the widget. `None` if the graph should be fitted to the screen.
+    pub camera: Option<Camera>,
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Snapshot<N, E, Ty, Ix> {
+    /// Creates the snapshot of the graph with the default style and without the camera.
+    pub fn new(graph: Graph<N, E, Ty, Ix>) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            graph,
+            style: Default::default(),
+            camera: Default::default(),
+        }
+    }
+
+    /// Sets the style settings of the snapshot.
+    pub fn with_styles(mut self, style: &SettingsStyle) -> Self {
+        self.style = style.clone();
+        self
+    }
+
+    /// Sets the camera of the snapshot.
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+#[cfg(feature = "json")]
+impl<N, E, Ty, Ix> Snapshot<N, E, Ty, Ix>
+where
+    N: Clone + Serialize + DeserializeOwned,
+    E: Clone + Serialize + DeserializeOwned,
+    Ty: EdgeType,
+    Ix: IndexType + Serialize + DeserializeOwned,
+{
+    /// Serializes the snapshot into the pretty printed JSON.
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        serde_json::to_string_pretty(self).map_err(|err| SnapshotError::Format(err.to_string()))
+    }
+
+    /// Loads the snapshot from JSON migrating it from older versions.
+    pub fn from_json(json: &str) -> Result<Self, SnapshotError> {
+        load::<Json, _, _, _, _>(json)
+    }
+}
+
+#[cfg(feature = "ron")]
+impl<N, E, Ty, Ix> Snapshot<N, E, Ty, Ix>
+where
+    N: Clone + Serialize + DeserializeOwned,
+    E: Clone + Serialize + DeserializeOwned,
+    Ty: EdgeType,
+    Ix: IndexType + Serialize + DeserializeOwned,
+{
+    /// Serializes the snapshot into the pretty printed RON.
+    pub fn to_ron(&self) -> Result<String, SnapshotError> {
+        ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| SnapshotError::Format(err.to_string()))
+    }
+
+    /// Loads the snapshot from RON migrating it from older versions.
+    pub fn from_ron(ron: &str) -> Result<Self, SnapshotError> {
+        load::<Ron, _, _, _, _>(ron)
+    }
+}
+
+/// Serde format of the snapshot documents.
+#[cfg(any(feature = "json", feature = "ron"))]
+trait Format {
+    fn parse<T: DeserializeOwned>(document: &str) -> Result<T, SnapshotError>;
+}
+
+#[cfg(feature = "json")]
+struct Json;
+
+#[cfg(feature = "json")]
+impl Format for Json {
+    fn parse<T: DeserializeOwned>(document: &str) -> Result<T, SnapshotError> {
+        serde_json::from_str(document).map_err(|err| SnapshotError::Format(err.to_string()))
+    }
+}
+
+#[cfg(feature = "ron")]
+struct Ron;
+
+#[cfg(feature = "ron")]
+impl Format for Ron {
+    fn parse<T: DeserializeOwned>(document: &str) -> Result<T, SnapshotError> {
+        ron::from_str(document).map_err(|err| SnapshotError::Format(err.to_string()))
+    }
+}
+
+/// Part of the document identifying the snapshot version.
+#[cfg(any(feature = "json", feature = "ron"))]
+#[derive(Deserialize)]
+struct Header {
+    /// Missing in the version `0` documents.
+    #[serde(default)]
+    version: u32,
+}
+
+/// Reads the version of the document and parses it with the structure of this version.
+/// Snapshots of older versions are migrated to the current one.
+#[cfg(any(feature = "json", feature = "ron"))]
+fn load<F, N, E, Ty, Ix>(document: &str) -> Result<Snapshot<N, E, Ty, Ix>, SnapshotError>
+where
+    F: Format,
+    N: Clone + DeserializeOwned,
+    E: Clone + DeserializeOwned,
+    Ty: EdgeType,
+    Ix: IndexType + DeserializeOwned,
+{
+    let header: Header = F::parse(document)?;
+    match header.version {
+        0 => Ok(Snapshot::new(F::parse(document)?)),
+        SNAPSHOT_VERSION => F::parse(document),
+        version => Err(SnapshotError::Version { version }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "json", feature = "ron"))]
+    use petgraph::{
+        stable_graph::{NodeIndex, StableGraph},
+        Directed,
+    };
+
+    #[cfg(any(feature = "json", feature = "ron"))]
+    use crate::{to_graph, EdgeRouting};
+
+    use super::*;
+
+    #[cfg(any(feature = "json", feature = "ron"))]
+    fn snapshot() -> Snapshot<String, (), Directed> {
+        let mut g = StableGraph::new();
+        let a = g.add_node("a".to_string());
+        let b = g.add_node("b".to_string());
+        g.add_edge(a, b, ());
+
+        let mut g = to_graph(&g);
+        g.node_mut(a).unwrap().set_location(Vec2::new(10., -5.));
+        g.node_mut(b).unwrap().set_selected(true);
+
+        Snapshot::new(g)
+            .with_styles(&SettingsStyle::new().with_edge_routing(EdgeRouting::Spline))
+            .with_camera(Camera {
+                zoom: 2.,
+                pan: Vec2::new(3., 4.),
+            })
+    }
+
+    #[cfg(any(feature = "json", feature = "ron"))]
+    fn assert_snapshot(loaded: &Snapshot<String, (), Directed>) {
+        let (a, b) = (NodeIndex::new(0), NodeIndex::new(1));
+        assert_eq!(loaded.graph.node(a).unwrap().location(), Vec2::new(10., -5.));
+        assert_eq!(loaded.graph.node(a).unwrap().data, "a");
+        assert!(loaded.graph.node(b).unwrap().selected());
+        assert_eq!(loaded.graph.g.edge_count(), 1);
+        assert_eq!(loaded.style.edge_routing, EdgeRouting::Spline);
+        assert_eq!(
+            loaded.camera,
+            Some(Camera {
+                zoom: 2.,
+                pan: Vec2::new(3., 4.)
+            })
+        );
+    }
+
+    #[test]
+    fn test_camera_apply() {
+        let mut meta = Metadata::default();
+        let camera = Camera {
+            zoom: 2.,
+            pan: Vec2::new(3., 4.),
+        };
+        camera.apply(&mut meta);
+
+        assert!(!meta.first_frame);
+        assert_eq!(Camera::from(&meta), camera);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() {
+        let json = snapshot().to_json().unwrap();
+        assert!(json.contains("\"version\": 1"));
+
+        assert_snapshot(&Snapshot::from_json(&json).unwrap());
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_ron_round_trip() {
+        let ron = snapshot().to_ron().unwrap();
+
+        assert_snapshot(&Snapshot::from_ron(&ron).unwrap());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_migrate_graph() {
+        let json = serde_json::to_string(&snapshot().graph).unwrap();
+
+        let loaded = Snapshot::<String, (), Directed>::from_json(&json).unwrap();
+        assert_eq!(loaded.version, SNAPSHOT_VERSION);
+        assert_eq!(loaded.graph.node(NodeIndex::new(0)).unwrap().location(), Vec2::new(10., -5.));
+        assert_eq!(loaded.style.edge_routing, EdgeRouting::Straight);
+        assert_eq!(loaded.camera, None);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_migrate_graph_ron() {
+        let ron = ron::to_string(&snapshot().graph).unwrap();
+
+        let loaded = Snapshot::<String, (), Directed>::from_ron(&ron).unwrap();
+        assert!(loaded.graph.node(NodeIndex::new(1)).unwrap().selected());
+        assert_eq!(loaded.camera, None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_newer_version() {
+        let json = snapshot()
+            .to_json()
+            .unwrap()
+            .replace("\"version\": 1", "\"version\": 99");
+
+        let err = Snapshot::<String, (), Directed>::from_json(&json).unwrap_err();
+        assert!(matches!(err, SnapshotError::Version { version: 99 }));
+    }
+}
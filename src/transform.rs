@@ -1,52 +1,53 @@
 use crate::{Edge, Graph, Node};
 use egui::Vec2;
 use petgraph::{
-    stable_graph::{EdgeIndex, NodeIndex, StableGraph},
-    visit::IntoNodeReferences,
+    stable_graph::{EdgeIndex, IndexType, NodeIndex, StableGraph},
     EdgeType,
 };
 use rand::Rng;
-use std::collections::HashMap;
 
 pub const DEFAULT_SPAWN_SIZE: f32 = 250.;
 
 /// Helper function which adds user's node to the [`super::Graph`] instance.
 ///
 /// If graph is not empty it picks any node position and adds new node in the vicinity of it.
-pub fn add_node<N: Clone, E: Clone, Ty: EdgeType>(g: &mut Graph<N, E, Ty>, n: &N) -> NodeIndex {
+pub fn add_node<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &mut Graph<N, E, Ty, Ix>,
+    n: &N,
+) -> NodeIndex<Ix> {
     add_node_custom(g, n, default_node_transform)
 }
 
 /// Helper function which adds user's node to the [`super::Graph`] instance with custom node transform function.
 ///
 /// If graph is not empty it picks any node position and adds new node in the vicinity of it.
-pub fn add_node_custom<N: Clone, E: Clone, Ty: EdgeType>(
-    g: &mut Graph<N, E, Ty>,
+pub fn add_node_custom<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &mut Graph<N, E, Ty, Ix>,
     n: &N,
-    node_transform: impl Fn(NodeIndex, &N) -> Node<N>,
-) -> NodeIndex {
-    g.g.add_node(node_transform(NodeIndex::new(g.g.node_count() + 1), n))
+    node_transform: impl Fn(NodeIndex<Ix>, &N) -> Node<N, Ix>,
+) -> NodeIndex<Ix> {
+    g.add_node(node_transform(NodeIndex::new(g.g.node_count() + 1), n))
 }
 
 /// Helper function which adds user's edge to the [`super::Graph`] instance.
-pub fn add_edge<N: Clone, E: Clone, Ty: EdgeType>(
-    g: &mut Graph<N, E, Ty>,
-    start: NodeIndex,
-    end: NodeIndex,
+pub fn add_edge<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &mut Graph<N, E, Ty, Ix>,
+    start: NodeIndex<Ix>,
+    end: NodeIndex<Ix>,
     e: &E,
-) -> EdgeIndex {
+) -> EdgeIndex<Ix> {
     add_edge_custom(g, start, end, e, default_edge_transform)
 }
 
 /// Helper function which adds user's edge to the [`super::Graph`] instance with custom edge transform function.
-pub fn add_edge_custom<N: Clone, E: Clone, Ty: EdgeType>(
-    g: &mut Graph<N, E, Ty>,
-    start: NodeIndex,
-    end: NodeIndex,
+pub fn add_edge_custom<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &mut Graph<N, E, Ty, Ix>,
+    start: NodeIndex<Ix>,
+    end: NodeIndex<Ix>,
     e: &E,
-    edge_transform: impl Fn(EdgeIndex, &E) -> Edge<E>,
-) -> EdgeIndex {
-    g.g.add_edge(
+    edge_transform: impl Fn(EdgeIndex<Ix>, &E) -> Edge<E, Ix>,
+) -> EdgeIndex<Ix> {
+    g.add_edge(
         start,
         end,
         edge_transform(EdgeIndex::new(g.g.edge_count() + 1), e),
@@ -61,11 +62,11 @@ pub fn add_edge_custom<N: Clone, E: Clone, Ty: EdgeType>(
 ///
 /// # Arguments
 /// * `g` - A reference to a [`petgraph::stable_graph::StableGraph`]. The graph can have any data type for nodes and edges, and
-/// can be either directed or undirected.
+///   can be either directed or undirected.
 ///
 /// # Returns
 /// * A new [`petgraph::stable_graph::StableGraph`] with the same topology as the input graph, but the nodes and edges encapsulated
-/// into Node and Edge structs compatible as an input to [`super::GraphView`] widget.
+///   into Node and Edge structs compatible as an input to [`super::GraphView`] widget.
 ///
 /// # Example
 /// ```
@@ -80,83 +81,67 @@ pub fn add_edge_custom<N: Clone, E: Clone, Ty: EdgeType>(
 ///
 /// let input_graph = to_graph(&user_graph);
 ///
-/// assert_eq!(input_graph.g.node_count(), 2);
-/// assert_eq!(input_graph.g.edge_count(), 1);
+/// assert_eq!(input_graph.node_count(), 2);
+/// assert_eq!(input_graph.edge_count(), 1);
 ///
-/// let mut input_indices = input_graph.g.node_indices();
+/// let mut input_indices = input_graph.stable_graph().node_indices();
 /// let input_node_1 = input_indices.next().unwrap();
 /// let input_node_2 = input_indices.next().unwrap();
-/// assert_eq!(*input_graph.g.node_weight(input_node_1).unwrap().data().clone().unwrap(), "A");
-/// assert_eq!(*input_graph.g.node_weight(input_node_2).unwrap().data().clone().unwrap(), "B");
+/// assert_eq!(input_graph.node(input_node_1).unwrap().data, "A");
+/// assert_eq!(input_graph.node(input_node_2).unwrap().data, "B");
 ///
-/// assert_eq!(*input_graph.g.edge_weight(input_graph.g.edge_indices().next().unwrap()).unwrap().data().clone().unwrap(), "edge1");
+/// assert_eq!(input_graph.edge(input_graph.stable_graph().edge_indices().next().unwrap()).unwrap().data, "edge1");
 ///
-/// assert_eq!(*input_graph.g.node_weight(input_node_1).unwrap().label().clone(), input_node_1.index().to_string());
-/// assert_eq!(*input_graph.g.node_weight(input_node_2).unwrap().label().clone(), input_node_2.index().to_string());
+/// assert_eq!(*input_graph.node(input_node_1).unwrap().label().clone(), input_node_1.index().to_string());
+/// assert_eq!(*input_graph.node(input_node_2).unwrap().label().clone(), input_node_2.index().to_string());
 ///
-/// let loc_1 = input_graph.g.node_weight(input_node_1).unwrap().location();
-/// let loc_2 = input_graph.g.node_weight(input_node_2).unwrap().location();
+/// let loc_1 = input_graph.node(input_node_1).unwrap().location();
+/// let loc_2 = input_graph.node(input_node_2).unwrap().location();
 /// assert!(loc_1 != Vec2::ZERO);
 /// assert!(loc_2 != Vec2::ZERO);
 /// ```
-pub fn to_graph<N: Clone, E: Clone, Ty: EdgeType>(g: &StableGraph<N, E, Ty>) -> Graph<N, E, Ty> {
+pub fn to_graph<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &StableGraph<N, E, Ty, Ix>,
+) -> Graph<N, E, Ty, Ix> {
     transform(g, default_node_transform, default_edge_transform)
 }
 
 /// The same as [`to_graph`], but allows to define custom transformation procedures for nodes and edges.
-pub fn to_graph_custom<N: Clone, E: Clone, Ty: EdgeType>(
-    g: &StableGraph<N, E, Ty>,
-    node_transform: impl Fn(NodeIndex, &N) -> Node<N>,
-    edge_transform: impl Fn(EdgeIndex, &E) -> Edge<E>,
-) -> Graph<N, E, Ty> {
+pub fn to_graph_custom<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &StableGraph<N, E, Ty, Ix>,
+    node_transform: impl Fn(NodeIndex<Ix>, &N) -> Node<N, Ix>,
+    edge_transform: impl Fn(EdgeIndex<Ix>, &E) -> Edge<E, Ix>,
+) -> Graph<N, E, Ty, Ix> {
     transform(g, node_transform, edge_transform)
 }
 
 /// Default node transform function. Keeps original data and creates a new node with a random location and
 /// label equal to the index of the node in the graph.
-pub fn default_node_transform<N: Clone>(idx: NodeIndex, data: &N) -> Node<N> {
+pub fn default_node_transform<N: Clone, Ix: IndexType>(
+    idx: NodeIndex<Ix>,
+    data: &N,
+) -> Node<N, Ix> {
     let loc = random_location(DEFAULT_SPAWN_SIZE);
     Node::new(loc, data.clone()).with_label(idx.index().to_string())
 }
 
 /// Default edge transform function. Keeps original data and creates a new edge.
-pub fn default_edge_transform<E: Clone>(_: EdgeIndex, data: &E) -> Edge<E> {
+pub fn default_edge_transform<E: Clone, Ix: IndexType>(_: EdgeIndex<Ix>, data: &E) -> Edge<E, Ix> {
     Edge::new(data.clone())
 }
 
-fn random_location(size: f32) -> Vec2 {
+pub(crate) fn random_location(size: f32) -> Vec2 {
     let mut rng = rand::thread_rng();
     Vec2::new(rng.gen_range(0. ..size), rng.gen_range(0. ..size))
 }
 
-fn transform<N: Clone, E: Clone, Ty: EdgeType>(
-    g: &StableGraph<N, E, Ty>,
-    node_transform: impl Fn(NodeIndex, &N) -> Node<N>,
-    edge_transform: impl Fn(EdgeIndex, &E) -> Edge<E>,
-) -> Graph<N, E, Ty> {
-    let mut input_g = StableGraph::<Node<N>, Edge<E>, Ty>::default();
-
-    let input_by_user = g
-        .node_references()
-        .map(|(user_n_idx, user_n)| {
-            let input_n_index = input_g.add_node(node_transform(user_n_idx, user_n));
-            (user_n_idx, input_n_index)
-        })
-        .collect::<HashMap<NodeIndex, NodeIndex>>();
-
-    g.edge_indices().for_each(|user_e_idx| {
-        let (user_source_n_idx, user_target_n_idx) = g.edge_endpoints(user_e_idx).unwrap();
-        let user_e = g.edge_weight(user_e_idx).unwrap();
-
-        let input_source_n = *input_by_user.get(&user_source_n_idx).unwrap();
-        let input_target_n = *input_by_user.get(&user_target_n_idx).unwrap();
-
-        input_g.add_edge(
-            input_source_n,
-            input_target_n,
-            edge_transform(user_e_idx, user_e),
-        );
-    });
+fn transform<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    g: &StableGraph<N, E, Ty, Ix>,
+    node_transform: impl Fn(NodeIndex<Ix>, &N) -> Node<N, Ix>,
+    edge_transform: impl Fn(EdgeIndex<Ix>, &E) -> Edge<E, Ix>,
+) -> Graph<N, E, Ty, Ix> {
+    // mapping keeps node and edge indices of the user graph including vacant ones
+    let input_g = g.map(node_transform, edge_transform);
 
     Graph::new(input_g)
 }
@@ -196,6 +181,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_to_graph_keeps_indices() {
+        let mut user_g: StableGraph<_, _, Directed, u8> = StableGraph::default();
+        let n1 = user_g.add_node("Node1");
+        let n2 = user_g.add_node("Node2");
+        let n3 = user_g.add_node("Node3");
+        let e = user_g.add_edge(n1, n3, "Edge1");
+        user_g.remove_node(n2);
+
+        let input_g = to_graph(&user_g);
+
+        assert!(input_g.node(n2).is_none());
+        assert_eq!(input_g.node(n3).unwrap().data, "Node3");
+        assert_eq!(input_g.node(n3).unwrap().id(), n3);
+        assert_eq!(input_g.edge_endpoints(e), Some((n1, n3)));
+    }
+
     #[test]
     fn test_to_graph_undirected() {
         let mut user_g: StableGraph<_, _, Undirected> = StableGraph::default();
@@ -224,4 +226,25 @@ mod tests {
             assert!(!input_n.dragged());
         }
     }
+
+    #[test]
+    fn test_elements_store_their_indices() {
+        let mut user_g: StableGraph<_, _, Directed> = StableGraph::new();
+        let n1 = user_g.add_node("Node1");
+        let n2 = user_g.add_node("Node2");
+        user_g.add_edge(n1, n2, "Edge1");
+
+        let mut input_g = to_graph(&user_g);
+        input_g.g.node_indices().for_each(|idx| {
+            assert_eq!(input_g.node(idx).unwrap().id(), idx);
+        });
+        input_g.g.edge_indices().for_each(|idx| {
+            assert_eq!(input_g.edge(idx).unwrap().id(), idx);
+        });
+
+        let n3 = add_node(&mut input_g, &"Node3");
+        let e2 = add_edge(&mut input_g, n3, n1, &"Edge2");
+        assert_eq!(input_g.node(n3).unwrap().id(), n3);
+        assert_eq!(input_g.edge(e2).unwrap().id(), e2);
+    }
 }